@@ -1,7 +1,9 @@
+use crate::events::EventSink;
+use crate::html::{self, References};
 use crate::models::Ticket;
+use crate::storage::Storage;
 use serde::{Deserialize, Serialize};
 use std::process::Command;
-use tauri::{AppHandle, Emitter};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TranslationResult {
@@ -37,31 +39,88 @@ where
     }
 }
 
+/// 翻译使用的正文形式；HTML 形式下没有 HTML 的部分仍按纯文本翻译
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslateFormat {
+    #[default]
+    Text,
+    Html,
+}
+
+impl std::str::FromStr for TranslateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(TranslateFormat::Text),
+            "html" => Ok(TranslateFormat::Html),
+            other => Err(format!("Unknown translate format: {} (expected text or html)", other)),
+        }
+    }
+}
+
+/// 待翻译的一段正文：链接与图片已替换为占位符
+struct Segment {
+    source: String,
+    is_html: bool,
+    refs: References,
+}
+
+impl Segment {
+    fn new(format: TranslateFormat, html: Option<&str>, text: &str) -> Self {
+        let (source, is_html) = match (format, html) {
+            (TranslateFormat::Html, Some(h)) if !h.trim().is_empty() => (h, true),
+            _ => (text, false),
+        };
+        let (source, refs) = References::protect(source);
+        Segment { source, is_html, refs }
+    }
+
+    /// 还原占位符，返回 (HTML, 纯文本)
+    fn apply(&self, translated: &str) -> (Option<String>, String) {
+        let restored = self.refs.restore(translated);
+        if self.is_html {
+            let clean = html::sanitize(&restored);
+            let text = html::to_text(&clean);
+            (Some(clean), text)
+        } else {
+            (None, restored)
+        }
+    }
+}
+
 pub struct GeminiClient;
 
 impl GeminiClient {
-    pub fn log(app: &AppHandle, msg: &str) {
-        eprintln!("[GeminiLog] {}", msg);
-        let _ = app.emit("log", msg.to_string());
+    pub fn log(sink: &dyn EventSink, msg: &str) {
+        sink.log(msg);
     }
 
     pub async fn translate_ticket(
-        app: &AppHandle,
+        sink: &dyn EventSink,
         ticket: &Ticket,
         target_lang: &str,
+        format: TranslateFormat,
     ) -> Result<Ticket, String> {
         Self::log(
-            app,
-            &format!("🤖 Translating ticket #{} to {}...", ticket.id, target_lang),
+            sink,
+            &format!("🤖 Translating ticket #{} to {} ({:?})...", ticket.id, target_lang, format),
         );
 
+        let description = ticket
+            .description_text
+            .as_deref()
+            .map(|text| Segment::new(format, ticket.description.as_deref(), text));
+        let conversations: Vec<(u64, Segment)> = ticket
+            .conversations
+            .iter()
+            .map(|c| (c.id, Segment::new(format, c.body.as_deref(), &c.body_text)))
+            .collect();
+        let has_html = description.iter().chain(conversations.iter().map(|(_, s)| s)).any(|s| s.is_html);
+
         // Prepare prompt
-        let lang_name = match target_lang {
-            "cn" | "zh-CN" => "Simplified Chinese",
-            "en" => "English",
-            "jp" => "Japanese",
-            _ => target_lang, // 兜底使用原始代码
-        };
+        let lang_name = crate::lang::display_name(target_lang);
         let mut prompt = format!(
             "You are a professional customer support translator. \
             Translate the following support ticket into {}. \
@@ -72,24 +131,31 @@ impl GeminiClient {
             3. You MUST translate BOTH the subject/description AND EVERY item in the 'conversations' list.\
             4. Maintain the original 'id' for each conversation item.\
             5. Ensure the content is ONLY in {} - DO NOT output in English if the target is {}.\
-            6. JSON Structure Example:\
+            6. Copy every placeholder like {{{{REF_1}}}} exactly as it appears; they stand for links and images.\
+            7. JSON Structure Example:\
             {{\n  \"subject\": \"翻译后的标题\",\n  \"description_text\": \"翻译后的正文内容\",\n  \"conversations\": [\n    {{\"id\": 123, \"body_text\": \"翻译后的对话消息\"}}\n  ]\n}}\n\n",
             lang_name, lang_name, lang_name
         );
+        if has_html {
+            prompt.push_str(
+                "Parts marked [HTML] are HTML: translate only the visible text, keep every tag and attribute \
+                unchanged, and return them as HTML in the same JSON fields.\n\n",
+            );
+        }
 
         prompt.push_str(&format!(
             "--- TICKET TO TRANSLATE ---\n\
             SUBJECT: {}\n",
             ticket.subject.clone().unwrap_or_default()
         ));
-        if let Some(desc) = &ticket.description_text {
-            prompt.push_str(&format!("DESCRIPTION: {}\n", desc));
+        if let Some(desc) = &description {
+            prompt.push_str(&format!("DESCRIPTION{}: {}\n", html_marker(desc), desc.source));
         }
 
-        if !ticket.conversations.is_empty() {
+        if !conversations.is_empty() {
             prompt.push_str("CONVERSATIONS:\n");
-            for c in &ticket.conversations {
-                prompt.push_str(&format!("MSG_ID {}: {}\n", c.id, c.body_text));
+            for (id, body) in &conversations {
+                prompt.push_str(&format!("MSG_ID {}{}: {}\n", id, html_marker(body), body.source));
             }
         }
 
@@ -129,26 +195,30 @@ impl GeminiClient {
             new_ticket.subject = Some(translated_data.subject);
         }
 
-        // If AI returns an empty string or None, fallback to original content
-        new_ticket.description_text = match translated_data.description_text {
-            Some(ref s) if !s.trim().is_empty() => Some(s.clone()),
-            _ => ticket.description_text.clone(),
-        };
+        // If AI returns an empty string or None, fallback to original content.
+        // 按纯文本翻译时原文 HTML 不再对应译文，不保留
+        if let (Some(desc), Some(translated)) = (&description, translated_data.description_text.as_deref()) {
+            if !translated.trim().is_empty() {
+                let (html, text) = desc.apply(translated);
+                new_ticket.description = html;
+                new_ticket.description_text = Some(text);
+            }
+        }
 
-        if !new_ticket.conversations.is_empty() {
-            for conv in new_ticket.conversations.iter_mut() {
-                if let Some(trans_conv) = translated_data
-                    .conversations
-                    .iter()
-                    .find(|tc| tc.id == conv.id)
-                {
-                    conv.body_text = trans_conv.body_text.clone();
-                }
+        for (conv, (_, body)) in new_ticket.conversations.iter_mut().zip(&conversations) {
+            if let Some(trans_conv) = translated_data
+                .conversations
+                .iter()
+                .find(|tc| tc.id == conv.id && !tc.body_text.trim().is_empty())
+            {
+                let (html, text) = body.apply(&trans_conv.body_text);
+                conv.body = html;
+                conv.body_text = text;
             }
         }
 
         Self::log(
-            app,
+            sink,
             &format!(
                 "✅ Translation to {} complete. Result: Title({} chars), Desc({} chars), Conversations({} items)",
                 target_lang,
@@ -159,4 +229,31 @@ impl GeminiClient {
         );
         Ok(new_ticket)
     }
+
+    /// 翻译本地已同步的工单，并以语言代码保存译文
+    pub async fn translate_local_ticket(
+        sink: &dyn EventSink,
+        storage: &Storage,
+        ticket_id: u64,
+        target_lang: &str,
+        format: TranslateFormat,
+    ) -> Result<Ticket, String> {
+        let original = match storage.load_ticket(ticket_id, None)? {
+            Some(t) => t,
+            None => return Err(format!("Ticket #{} not found locally", ticket_id)),
+        };
+
+        let translated = Self::translate_ticket(sink, &original, target_lang, format).await?;
+        storage.save_ticket(&translated, Some(target_lang))?;
+
+        Ok(translated)
+    }
+}
+
+fn html_marker(segment: &Segment) -> &'static str {
+    if segment.is_html {
+        " [HTML]"
+    } else {
+        ""
+    }
 }
@@ -1,23 +1,181 @@
-use crate::models::{Conversation, Ticket};
+use crate::attachments::{AttachmentStore, StoredAttachment};
+use crate::events::EventSink;
+use crate::jobs::SyncJob;
+use crate::rate_limit::RateLimiter;
+use crate::models::{Agent, Attachment, Company, Contact, Conversation, Ticket, TicketField};
 use crate::storage::Storage;
+use futures_util::stream::{self, Stream, StreamExt};
 use reqwest::Client;
+use std::sync::Arc;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
 use std::collections::HashSet;
-use serde::Deserialize;
-use chrono::{Utc, Duration as ChronoDuration, NaiveDate};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+
+/// 全量同步的日期范围 (首尾均包含) 与每个查询窗口的天数
+#[derive(Debug, Clone)]
+pub struct SyncRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub window_days: u32,
+    /// 未设置截止日期 (取今天)；此时截止日期较早的断点也可以继续
+    pub open_end: bool,
+}
+
+impl SyncRange {
+    /// 切分为互不重叠的 [start, end] 窗口 (Freshdesk 的 `:>` / `:<` 均包含边界)
+    pub fn windows(&self) -> Vec<(NaiveDate, NaiveDate)> {
+        let step = ChronoDuration::days(self.window_days.max(1) as i64);
+        let mut windows = Vec::new();
+        let mut current = self.start;
+        while current <= self.end {
+            let last = (current + step - ChronoDuration::days(1)).min(self.end);
+            windows.push((current, last));
+            current = last + ChronoDuration::days(1);
+        }
+        windows
+    }
+}
+
+/// Freshdesk 搜索接口每页固定 30 条，最多 10 页
+const SEARCH_PAGE_SIZE: usize = 30;
+const SEARCH_RESULT_CAP: usize = 300;
+
+/// 搜索时间窗口 (首尾均包含)；结果达到上限时先拆分为单天，单天再按优先级拆分。
+/// Freshdesk 的 created_at 条件只接受 `yyyy-mm-dd`，单天单优先级的窗口无法再拆分
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchWindow {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// 只查询该优先级 (1-4)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+}
+
+/// Freshdesk 工单优先级：Low / Medium / High / Urgent
+const TICKET_PRIORITIES: [i32; 4] = [1, 2, 3, 4];
+
+impl SearchWindow {
+    pub fn days(start: NaiveDate, end: NaiveDate) -> Self {
+        SearchWindow { start, end, priority: None }
+    }
+
+    /// created_at (及优先级) 查询条件
+    fn query(&self) -> String {
+        let mut query = format!("created_at:>'{}' AND created_at:<'{}'", self.start, self.end);
+        if let Some(priority) = self.priority {
+            query.push_str(&format!(" AND priority:{}", priority));
+        }
+        query
+    }
+
+    /// 多天 → 每天；单天 → 每个优先级；单天单优先级返回 None
+    fn split(&self) -> Option<Vec<SearchWindow>> {
+        if self.start < self.end {
+            return Some(
+                self.start
+                    .iter_days()
+                    .take_while(|d| *d <= self.end)
+                    .map(|d| SearchWindow::days(d, d))
+                    .collect(),
+            );
+        }
+        if self.priority.is_some() {
+            return None;
+        }
+        Some(
+            TICKET_PRIORITIES
+                .iter()
+                .map(|p| SearchWindow { priority: Some(*p), ..*self })
+                .collect(),
+        )
+    }
+}
+
+impl std::fmt::Display for SearchWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)?;
+        } else {
+            write!(f, "{} ~ {}", self.start, self.end)?;
+        }
+        match self.priority {
+            Some(priority) => write!(f, " (priority {})", priority),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 结果超过 300 条上限、只取到前 300 条的单天单优先级窗口
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TruncatedWindow {
+    pub status_code: i32,
+    pub window: SearchWindow,
+    /// Freshdesk 报告的结果总数
+    pub total: usize,
+}
+
+/// 全量同步结果
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullSyncReport {
+    pub saved: usize,
+    /// 因结果达到 300 条上限而被拆分的窗口数
+    pub split_windows: usize,
+    /// 拆分到单天单优先级仍超过上限、结果不完整的窗口
+    pub truncated_windows: Vec<TruncatedWindow>,
+    /// 请求出错而中止同步的次数；出错的窗口保留在断点中，下次从该页重新获取
+    #[serde(default)]
+    pub failed_windows: usize,
+}
 
 #[derive(Debug, Deserialize)]
 struct SearchResult {
     results: Vec<Ticket>,
-    #[allow(dead_code)]
+    #[serde(default)]
     total: i64,
 }
 
+/// 全量同步断点文件 (位于数据目录)
+pub const SYNC_CHECKPOINT_FILE: &str = "full_sync_checkpoint.json";
+
+/// 全量同步断点：记录当前状态、剩余窗口与下一页，中断后从这里继续
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncCheckpoint {
+    pub range_start: NaiveDate,
+    pub range_end: NaiveDate,
+    pub window_days: u32,
+    /// 截止日期是否为启动当天 (未设置截止日期)
+    #[serde(default)]
+    pub open_end: bool,
+    /// 正在同步的 Freshdesk 状态码
+    pub status_code: i32,
+    /// 该状态下尚未完成的窗口，队首为当前窗口
+    pub pending_windows: Vec<SearchWindow>,
+    /// 当前窗口下一次要请求的页码
+    pub page: usize,
+    pub report: FullSyncReport,
+    pub updated_at: String,
+}
+
+impl SyncCheckpoint {
+    /// 起始日期与窗口相同，且截止日期相同或两者都未设置截止日期 (隔天继续时“今天”已变化)
+    fn matches(&self, range: &SyncRange) -> bool {
+        let end_ok = self.range_end == range.end || (self.open_end && range.open_end && self.range_end <= range.end);
+        self.range_start == range.start && self.window_days == range.window_days && end_ok
+    }
+}
+
 pub struct FreshdeskClient {
     client: Client,
     base_url: String,
     api_key: String,
+    /// 按域名共享，所有请求都经过它
+    limiter: Arc<RateLimiter>,
 }
 
 impl FreshdeskClient {
@@ -28,19 +186,44 @@ impl FreshdeskClient {
             .build()
             .unwrap_or_else(|_| Client::new());
             
+        let domain = Self::normalize_domain(domain);
         FreshdeskClient {
             client,
             base_url: format!("https://{}/api/v2", domain),
             api_key: api_key.to_string(),
+            limiter: RateLimiter::for_domain(&domain),
         }
     }
 
-    fn log(app: &AppHandle, msg: &str) {
-        let _ = app.emit("log", msg.to_string());
+    /// 接受 "acme"、"acme.freshdesk.com" 或 "https://acme.freshdesk.com/" 等写法
+    pub fn normalize_domain(domain: &str) -> String {
+        let host = domain
+            .trim()
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/');
+        if host.is_empty() {
+            crate::settings::DEFAULT_FRESHDESK_DOMAIN.to_string()
+        } else if host.contains('.') {
+            host.to_string()
+        } else {
+            format!("{}.freshdesk.com", host)
+        }
     }
 
-    /// Fetch ALL tickets and save immediately after each batch
-    pub async fn fetch_and_save_all_tickets(&self, app: &AppHandle, storage: &Storage, client: &FreshdeskClient) -> Result<usize, String> {
+    /// Fetch ALL tickets and save immediately after each page.
+    /// 每页保存后写入断点，下次以相同范围启动时从断点继续；
+    /// 每页请求前响应任务的暂停与取消 (取消后断点保留)。
+    /// 搜索请求出错时保留断点并返回错误。
+    pub async fn fetch_and_save_all_tickets(
+        &self,
+        sink: &dyn EventSink,
+        storage: &Storage,
+        range: &SyncRange,
+        job: &SyncJob,
+        concurrency: usize,
+        attachments: Option<&AttachmentStore>,
+    ) -> Result<FullSyncReport, String> {
         let statuses = [
             (2, "Open"),
             (3, "Pending"),
@@ -49,141 +232,186 @@ impl FreshdeskClient {
         ];
         
         let mut seen_ids: HashSet<u64> = HashSet::new();
-        let mut total_saved = 0;
-        
-        // Generate weekly ranges from 2025-01-01 to now
-        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
-        let today = Utc::now().date_naive();
-        
-        let mut weeks = Vec::new();
-        let mut current = start;
-        while current < today {
-            let end = current + ChronoDuration::days(7);
-            weeks.push((current, end.min(today)));
-            current = end;
-        }
-        
-        Self::log(app, &format!("📅 {} weeks × {} statuses = {} queries", weeks.len(), statuses.len(), weeks.len() * statuses.len()));
+
+        let resume = match storage.read_state::<SyncCheckpoint>(SYNC_CHECKPOINT_FILE) {
+            Some(cp) if cp.matches(range) && statuses.iter().any(|(code, _)| *code == cp.status_code) => {
+                sink.log(&format!("⏯️ Resuming from checkpoint ({}): status {}, {} windows left, page {}",
+                    cp.updated_at, cp.status_code, cp.pending_windows.len(), cp.page));
+                Some(cp)
+            }
+            Some(cp) => {
+                sink.log(&format!("🗑️ Ignoring checkpoint for a different range ({} ~ {}, {} days)",
+                    cp.range_start, cp.range_end, cp.window_days));
+                None
+            }
+            None => None,
+        };
+        // 继续的同步沿用断点的截止日期，之后创建的工单由下次增量同步获取
+        let range = &SyncRange {
+            end: resume.as_ref().map_or(range.end, |cp| cp.range_end),
+            ..range.clone()
+        };
+
+        let windows: Vec<SearchWindow> = range.windows().into_iter()
+            .map(|(start, end)| SearchWindow::days(start, end))
+            .collect();
+
+        sink.log(&format!("📅 {} ~ {}: {} windows of {} days × {} statuses = {} queries",
+            range.start, range.end, windows.len(), range.window_days, statuses.len(), windows.len() * statuses.len()));
+        let mut report = resume.as_ref().map(|cp| cp.report.clone()).unwrap_or_default();
         
-        let total_queries = weeks.len() * statuses.len();
+        // 窗口被拆分时总查询数会增加，进度按实时总数计算
+        let mut total_queries = (windows.len() * statuses.len()).max(1);
         let mut query_num = 0;
+        let mut resume = resume;
         
         for (status_code, status_name) in statuses.iter() {
-            Self::log(app, &format!("📋 Fetching {} tickets...", status_name));
+            // 断点之前的状态已完成
+            let (mut pending, mut page) = match resume.take() {
+                Some(cp) if cp.status_code != *status_code => {
+                    query_num += windows.len();
+                    resume = Some(cp);
+                    continue;
+                }
+                Some(cp) => {
+                    query_num += windows.len().saturating_sub(cp.pending_windows.len());
+                    (cp.pending_windows, cp.page.max(1))
+                }
+                None => (windows.clone(), 1),
+            };
+
+            sink.log(&format!("📋 Fetching {} tickets...", status_name));
             let mut status_saved = 0;
             
-            for (start_date, end_date) in &weeks {
+            // 按时间顺序处理，拆分出的子窗口插回队首
+            while let Some(window) = pending.first().copied() {
                 query_num += 1;
                 let progress = (query_num as f32 / total_queries as f32 * 100.0) as i32;
-                let _ = app.emit("progress", serde_json::json!({"phase": "fetching", "current": progress, "total": 100}));
+                sink.progress(serde_json::json!({"phase": "fetching", "current": progress, "total": 100}));
                 
-                let query = format!("\"status:{} AND created_at:>'{}' AND created_at:<'{}'\"", 
-                    status_code, start_date, end_date);
+                let query = format!("\"status:{} AND {}\"", status_code, window.query());
+                let mut window_saved = 0;
                 
-                match self.search_tickets(&query).await {
-                    Ok(tickets) => {
-                        let mut batch_saved = 0;
-                        for ticket in tickets {
-                            if !seen_ids.contains(&ticket.id) {
-                                seen_ids.insert(ticket.id);
-                                
-                                // Fetch conversations and save immediately
-                                let mut full_ticket = ticket;
-                                if let Ok(convs) = client.list_conversations(full_ticket.id).await {
-                                    full_ticket.conversations = convs;
-                                }
-                                
-                                if storage.save_ticket(&full_ticket, None).is_ok() {
-                                    batch_saved += 1;
-                                    status_saved += 1;
-                                    total_saved += 1;
-                                }
-                            }
+                loop {
+                    job.checkpoint().await?;
+                    let result = match self.search_page(&query, page).await {
+                        Ok(r) => r,
+                        Err(e) if e.contains("0 results") => break,
+                        Err(e) => {
+                            // 断点停在当前窗口与页码，下次从这里重新获取
+                            report.failed_windows += 1;
+                            self.write_checkpoint(storage, range, *status_code, &pending, page, &report);
+                            sink.log(&format!("   ❌ {} {} page {}: {}", status_name, window, page, e));
+                            return Err(format!("Search failed for {} {} (page {}): {}", status_name, window, page, e));
                         }
-                        if batch_saved > 0 {
-                            Self::log(app, &format!("   {} {} ~ {}: +{} saved", status_name, start_date, end_date, batch_saved));
+                    };
+
+                    let total = result.total.max(0) as usize;
+                    if page == 1 && total > SEARCH_RESULT_CAP {
+                        if let Some(children) = window.split() {
+                            sink.log(&format!("   ✂️ {} {}: {} results exceed the {} cap, splitting into {} windows",
+                                status_name, window, total, SEARCH_RESULT_CAP, children.len()));
+                            report.split_windows += 1;
+                            total_queries += children.len();
+                            pending.splice(0..1, children);
+                            break;
                         }
+                        sink.log(&format!("   ⚠️ {} {}: {} results but only {} retrievable, window cannot be split further",
+                            status_name, window, total, SEARCH_RESULT_CAP));
+                        report.truncated_windows.push(TruncatedWindow { status_code: *status_code, window, total });
                     }
-                    Err(e) => {
-                        if !e.contains("0 results") {
-                            Self::log(app, &format!("   ⚠️ {} {}: {}", status_name, start_date, e));
+
+                    let count = result.results.len();
+                    let new_tickets: Vec<Ticket> = result.results.into_iter()
+                        .filter(|t| seen_ids.insert(t.id))
+                        .collect();
+                    // Fetch conversations concurrently and save tickets in search order
+                    let mut fetches = self.with_conversations(new_tickets, concurrency, attachments);
+                    while let Some((mut full_ticket, fetch_error)) = fetches.next().await {
+                        if let Some(e) = &fetch_error {
+                            sink.log(&format!("   ⚠️ #{} saved with incomplete conversations: {}", full_ticket.id, e));
                         }
+                        match storage.save_synced_ticket(&mut full_ticket, fetch_error.as_deref()) {
+                            Ok(()) => {
+                                window_saved += 1;
+                                status_saved += 1;
+                                report.saved += 1;
+                            }
+                            Err(e) => sink.log(&format!("   ❌ #{} save failed: {}", full_ticket.id, e)),
+                        }
+                    }
+
+                    if count < SEARCH_PAGE_SIZE || page * SEARCH_PAGE_SIZE >= SEARCH_RESULT_CAP {
+                        break;
                     }
+                    page += 1;
+                    self.write_checkpoint(storage, range, *status_code, &pending, page, &report);
                 }
-                
-                // Delay between queries
-                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+                // 当前窗口被拆分时队首已替换为子窗口，否则移出已完成的窗口
+                if pending.first() == Some(&window) {
+                    pending.remove(0);
+                    if window_saved > 0 {
+                        sink.log(&format!("   {} {}: +{} saved", status_name, window, window_saved));
+                    }
+                }
+                page = 1;
+                self.write_checkpoint(storage, range, *status_code, &pending, page, &report);
             }
             
-            Self::log(app, &format!("   ✓ {} saved: {} (total: {})", status_name, status_saved, total_saved));
+            sink.log(&format!("   ✓ {} saved: {} (total: {})", status_name, status_saved, report.saved));
         }
         
-        Self::log(app, &format!("✓ All done: {} tickets saved", total_saved));
-        Ok(total_saved)
+        let _ = storage.remove_state(SYNC_CHECKPOINT_FILE);
+        sink.log(&format!("✓ All done: {} tickets saved, {} windows split, {} windows truncated, {} failed requests resumed",
+            report.saved, report.split_windows, report.truncated_windows.len(), report.failed_windows));
+        for truncated in &report.truncated_windows {
+            sink.log(&format!("   ⚠️ status {} {}: only {} of {} tickets fetched",
+                truncated.status_code, truncated.window, SEARCH_RESULT_CAP, truncated.total));
+        }
+        Ok(report)
     }
 
-    /// Search tickets with a query (max 10 pages = 300 results)
-    async fn search_tickets(&self, query: &str) -> Result<Vec<Ticket>, String> {
-        let url = format!("{}/search/tickets", self.base_url);
-        
-        let mut all_tickets = Vec::new();
-        let mut page = 1;
-        let max_pages = 10;
+    fn write_checkpoint(&self, storage: &Storage, range: &SyncRange, status_code: i32, pending: &[SearchWindow], page: usize, report: &FullSyncReport) {
+        let checkpoint = SyncCheckpoint {
+            range_start: range.start,
+            range_end: range.end,
+            window_days: range.window_days,
+            open_end: range.open_end,
+            status_code,
+            pending_windows: pending.to_vec(),
+            page,
+            report: report.clone(),
+            updated_at: Utc::now().to_rfc3339(),
+        };
+        let _ = storage.write_state(SYNC_CHECKPOINT_FILE, &checkpoint);
+    }
 
-        loop {
-            if page > max_pages {
-                break;
-            }
+    /// Fetch one page of search results (30 per page, at most 10 pages)
+    async fn search_page(&self, query: &str, page: usize) -> Result<SearchResult, String> {
+        let url = format!("{}/search/tickets", self.base_url);
+        let page = page.to_string();
 
-            let request = self.client.get(&url)
+        let resp = self.limiter.send(|| {
+            self.client.get(&url)
                 .basic_auth(&self.api_key, Some("X"))
                 .query(&[
                     ("query", query),
-                    ("page", &page.to_string()),
-                ]);
-            
-            let resp = match request.send().await {
-                Ok(r) => r,
-                Err(e) => return Err(e.to_string()),
-            };
-
-            let status_code = resp.status();
-            
-            if status_code.as_u16() == 429 {
-                tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
-                continue;
-            }
-            
-            if !status_code.is_success() {
-                let text = resp.text().await.unwrap_or_default();
-                return Err(format!("{}: {}", status_code, text));
-            }
+                    ("page", &page),
+                ])
+        }).await?;
 
-            let result: SearchResult = match resp.json().await {
-                Ok(r) => r,
-                Err(e) => return Err(format!("JSON: {}", e)),
-            };
-            
-            if result.results.is_empty() {
-                break;
-            }
-            
-            let count = result.results.len();
-            all_tickets.extend(result.results);
-            
-            if count < 30 {
-                break;
-            }
-            
-            tokio::time::sleep(tokio::time::Duration::from_millis(150)).await;
-            page += 1;
+        let status_code = resp.status();
+        if !status_code.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(format!("{}: {}", status_code, text));
         }
 
-        Ok(all_tickets)
+        resp.json().await.map_err(|e| format!("JSON: {}", e))
     }
 
     /// Fetch tickets updated since a specific date - for incremental sync
-    pub async fn list_tickets_since(&self, updated_since: Option<&str>, _app: &AppHandle) -> Result<Vec<Ticket>, String> {
+    pub async fn list_tickets_since(&self, updated_since: Option<&str>, _sink: &dyn EventSink) -> Result<Vec<Ticket>, String> {
         let url = format!("{}/tickets", self.base_url);
         
         let mut all_tickets = Vec::new();
@@ -195,27 +423,20 @@ impl FreshdeskClient {
                 break;
             }
 
-            let mut request = self.client.get(&url)
-                .basic_auth(&self.api_key, Some("X"))
-                .query(&[
-                    ("include", "description"),
-                    ("per_page", "100"),
-                    ("page", &page.to_string()),
-                ]);
-            
-            if let Some(since) = updated_since {
-                request = request.query(&[("updated_since", since)]);
-            }
-            
-            let resp = match request.send().await {
-                Ok(r) => r,
-                Err(e) => return Err(e.to_string()),
-            };
-
-            if resp.status().as_u16() == 429 {
-                tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
-                continue;
-            }
+            let page_param = page.to_string();
+            let resp = self.limiter.send(|| {
+                let mut request = self.client.get(&url)
+                    .basic_auth(&self.api_key, Some("X"))
+                    .query(&[
+                        ("include", "description"),
+                        ("per_page", "100"),
+                        ("page", &page_param),
+                    ]);
+                if let Some(since) = updated_since {
+                    request = request.query(&[("updated_since", since)]);
+                }
+                request
+            }).await?;
             
             if !resp.status().is_success() {
                 return Err(format!("API error {}", resp.status()));
@@ -237,60 +458,338 @@ impl FreshdeskClient {
                 break;
             }
             
-            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
             page += 1;
         }
 
         Ok(all_tickets)
     }
 
-    pub async fn list_conversations(&self, ticket_id: u64) -> Result<Vec<Conversation>, String> {
-        let url = format!("{}/tickets/{}/conversations", self.base_url, ticket_id);
-        let mut all_conversations = Vec::new();
-        let mut page = 1;
-
-        loop {
-            if page > 5 {
-                break;
-            }
-            
-            let resp = match self.client.get(&url)
+    /// Fetch a single ticket (with description)
+    pub async fn get_ticket(&self, ticket_id: u64) -> Result<Ticket, String> {
+        let url = format!("{}/tickets/{}", self.base_url, ticket_id);
+        let resp = self.limiter.send(|| {
+            self.client.get(&url)
                 .basic_auth(&self.api_key, Some("X"))
-                .query(&[("page", page)])
-                .send()
-                .await 
-            {
-                Ok(r) => r,
-                Err(_) => break,
+                .query(&[("include", "description")])
+        }).await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(format!("{}: {}", status, text));
+        }
+        resp.json().await.map_err(|e| format!("JSON: {}", e))
+    }
+
+    /// 工单字段定义 (含自定义字段的类型、标签与选项)
+    pub async fn list_ticket_fields(&self) -> Result<Vec<TicketField>, String> {
+        let url = format!("{}/ticket_fields", self.base_url);
+        let resp = self.limiter.send(|| {
+            self.client.get(&url).basic_auth(&self.api_key, Some("X"))
+        }).await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(format!("{}: {}", status, text));
+        }
+        resp.json().await.map_err(|e| format!("JSON: {}", e))
+    }
+
+    /// 以最多 `concurrency` 个并发请求为工单补全会话 (给出 `attachments` 时同时下载附件)，
+    /// 按输入顺序产出；拉取失败时附带错误，工单保留原有会话
+    pub fn with_conversations<'a>(
+        &'a self,
+        tickets: Vec<Ticket>,
+        concurrency: usize,
+        attachments: Option<&'a AttachmentStore>,
+    ) -> impl Stream<Item = (Ticket, Option<String>)> + 'a {
+        concurrent_in_order(tickets, concurrency, move |mut ticket| async move {
+            let fetch_error = match self.list_conversations(ticket.id).await {
+                Ok(convs) => {
+                    ticket.conversations = convs;
+                    None
+                }
+                Err(e) => Some(e),
             };
+            if let Some(store) = attachments {
+                self.download_attachments(&mut ticket, store).await;
+            }
+            (ticket, fetch_error)
+        })
+    }
 
-            if resp.status().as_u16() == 429 {
-                tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
+    /// 下载工单及其会话的附件到本地存储并记录内容哈希；
+    /// 已下载过的直接复用，不符合限制或下载失败的记录原因 (下次同步时重试)
+    pub async fn download_attachments(&self, ticket: &mut Ticket, store: &AttachmentStore) {
+        let attachments = ticket
+            .attachments
+            .iter_mut()
+            .chain(ticket.conversations.iter_mut().flat_map(|c| c.attachments.iter_mut()));
+        for attachment in attachments {
+            attachment.skipped = None;
+            if let Some(stored) = store.get(attachment.id) {
+                attachment.sha256 = Some(stored.sha256);
+                continue;
+            }
+            if let Err(reason) = store.policy().check(attachment) {
+                attachment.skipped = Some(reason);
                 continue;
             }
+            match self.download_attachment(attachment, store).await {
+                Ok(stored) => attachment.sha256 = Some(stored.sha256),
+                Err(e) => attachment.skipped = Some(format!("download failed: {}", e)),
+            }
+        }
+    }
 
-            if !resp.status().is_success() {
-                break;
+    async fn download_attachment(&self, attachment: &Attachment, store: &AttachmentStore) -> Result<StoredAttachment, String> {
+        let url = attachment.attachment_url.as_deref().ok_or("no attachment_url")?;
+        // attachment_url 是预签名的文件地址：不带 Freshdesk 认证，也不占用 API 配额
+        let mut resp = self.client.get(url).send().await.map_err(|e| e.to_string())?;
+        if !resp.status().is_success() {
+            return Err(resp.status().to_string());
+        }
+        let max = store.policy().max_bytes;
+        if resp.content_length().is_some_and(|len| len > max) {
+            return Err(format!("exceeds the {} byte limit", max));
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+            if (bytes.len() + chunk.len()) as u64 > max {
+                return Err(format!("exceeds the {} byte limit", max));
             }
+            bytes.extend_from_slice(&chunk);
+        }
+        store.store(attachment, &bytes)
+    }
 
-            let page_convs: Vec<Conversation> = match resp.json().await {
-                Ok(c) => c,
-                Err(_) => break,
-            };
-            
-            if page_convs.is_empty() {
-                break;
+    /// 拉取工单的全部会话，按 `link` 响应头翻页直到最后一页；任何一页失败都返回错误
+    pub async fn list_conversations(&self, ticket_id: u64) -> Result<Vec<Conversation>, String> {
+        let url = format!("{}/tickets/{}/conversations?per_page=100", self.base_url, ticket_id);
+        self.list_all(url).await
+            .map_err(|e| format!("Conversations of #{}: {}", ticket_id, e))
+    }
+
+    /// 联系人；给出 `updated_since` 时只返回此后更新的
+    pub async fn list_contacts(&self, updated_since: Option<&str>) -> Result<Vec<Contact>, String> {
+        let mut url = format!("{}/contacts?per_page=100", self.base_url);
+        if let Some(since) = updated_since {
+            url.push_str(&format!("&_updated_since={}", since.replace('+', "%2B")));
+        }
+        self.list_all(url).await.map_err(|e| format!("Contacts: {}", e))
+    }
+
+    pub async fn list_companies(&self) -> Result<Vec<Company>, String> {
+        let url = format!("{}/companies?per_page=100", self.base_url);
+        self.list_all(url).await.map_err(|e| format!("Companies: {}", e))
+    }
+
+    pub async fn list_agents(&self) -> Result<Vec<Agent>, String> {
+        let url = format!("{}/agents?per_page=100", self.base_url);
+        self.list_all(url).await.map_err(|e| format!("Agents: {}", e))
+    }
+
+    /// GET 列表接口，按 `link` 响应头翻页直到最后一页
+    async fn list_all<T: DeserializeOwned>(&self, first_page: String) -> Result<Vec<T>, String> {
+        let mut url = first_page;
+        let mut all = Vec::new();
+        let mut visited = HashSet::new();
+
+        loop {
+            if !visited.insert(url.clone()) {
+                return Err(format!("pagination loops back to {}", url));
             }
 
-            let count = page_convs.len();
-            all_conversations.extend(page_convs);
+            let resp = self.limiter.send(|| {
+                self.client.get(&url).basic_auth(&self.api_key, Some("X"))
+            }).await?;
 
-            if count < 30 {
-                break;
+            let status = resp.status();
+            if !status.is_success() {
+                let text = resp.text().await.unwrap_or_default();
+                return Err(format!("{}: {}", status, text));
+            }
+
+            let next = resp.headers()
+                .get(reqwest::header::LINK)
+                .and_then(|v| v.to_str().ok())
+                .and_then(next_link);
+
+            let page: Vec<T> = resp.json().await.map_err(|e| format!("JSON: {}", e))?;
+            all.extend(page);
+
+            match next {
+                Some(next) => url = next,
+                None => break,
             }
-            page += 1;
         }
 
-        Ok(all_conversations)
+        Ok(all)
+    }
+}
+
+/// 从 `link: <https://...?page=2>; rel="next"` 中取出下一页地址
+fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|part| {
+        let (target, params) = part.split_once(';')?;
+        let is_next = params
+            .split(';')
+            .any(|p| p.trim().trim_start_matches("rel=").trim_matches('"') == "next");
+        if !is_next {
+            return None;
+        }
+        let target = target.trim();
+        Some(target.strip_prefix('<')?.strip_suffix('>')?.to_string())
+    })
+}
+
+/// 以最多 `concurrency` 个并发执行 `f`，结果按输入顺序产出
+fn concurrent_in_order<'a, T: 'a, F, Fut>(items: Vec<T>, concurrency: usize, f: F) -> impl Stream<Item = Fut::Output> + 'a
+where
+    F: FnMut(T) -> Fut + 'a,
+    Fut: std::future::Future + 'a,
+{
+    stream::iter(items).map(f).buffered(concurrency.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn multi_day_window_splits_into_days() {
+        let window = SearchWindow::days(date("2024-02-27"), date("2024-03-01"));
+        let days: Vec<String> = window.split().unwrap().iter().map(|w| w.to_string()).collect();
+        assert_eq!(days, ["2024-02-27", "2024-02-28", "2024-02-29", "2024-03-01"]);
+    }
+
+    #[test]
+    fn single_day_window_splits_by_priority() {
+        let window = SearchWindow::days(date("2024-03-01"), date("2024-03-01"));
+        assert_eq!(window.query(), "created_at:>'2024-03-01' AND created_at:<'2024-03-01'");
+        let children = window.split().unwrap();
+        assert_eq!(children.iter().map(|w| w.priority).collect::<Vec<_>>(), [Some(1), Some(2), Some(3), Some(4)]);
+        assert_eq!(children[2].to_string(), "2024-03-01 (priority 3)");
+        assert_eq!(
+            children[2].query(),
+            "created_at:>'2024-03-01' AND created_at:<'2024-03-01' AND priority:3"
+        );
+        assert_eq!(children[2].split(), None);
+    }
+
+    #[test]
+    fn windows_without_priority_read_from_old_checkpoints() {
+        let window: SearchWindow = serde_json::from_str(r#"{"start":"2024-03-01","end":"2024-03-02"}"#).unwrap();
+        assert_eq!(window, SearchWindow::days(date("2024-03-01"), date("2024-03-02")));
+        assert_eq!(serde_json::to_string(&window).unwrap(), r#"{"start":"2024-03-01","end":"2024-03-02"}"#);
+    }
+
+    #[test]
+    fn range_windows_cover_range_without_overlap() {
+        let range = SyncRange { start: date("2024-01-01"), end: date("2024-01-10"), window_days: 4, open_end: false };
+        assert_eq!(
+            range.windows(),
+            [
+                (date("2024-01-01"), date("2024-01-04")),
+                (date("2024-01-05"), date("2024-01-08")),
+                (date("2024-01-09"), date("2024-01-10")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_results_keep_input_order() {
+        let started = std::time::Instant::now();
+        let results: Vec<u64> = concurrent_in_order(vec![300u64, 200, 100, 0], 4, |ms| async move {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            ms
+        })
+        .collect()
+        .await;
+        assert_eq!(results, [300, 200, 100, 0]);
+        // 并发执行：总耗时接近最长的一个而不是总和
+        assert!(started.elapsed() < Duration::from_millis(550));
+    }
+
+    #[test]
+    fn checkpoint_round_trips_through_storage() {
+        let storage = Storage::new("memory:api-checkpoint");
+        let client = FreshdeskClient::new("acme", "key");
+        let range = SyncRange { start: date("2024-01-01"), end: date("2024-01-31"), window_days: 7, open_end: false };
+        let pending = vec![
+            SearchWindow::days(date("2024-01-08"), date("2024-01-08")),
+            SearchWindow::days(date("2024-01-09"), date("2024-01-14")),
+        ];
+        let report = FullSyncReport {
+            saved: 42,
+            split_windows: 1,
+            truncated_windows: vec![TruncatedWindow { status_code: 2, window: pending[0], total: 450 }],
+            failed_windows: 1,
+        };
+        client.write_checkpoint(&storage, &range, 3, &pending, 4, &report);
+
+        let checkpoint: SyncCheckpoint = storage.read_state(SYNC_CHECKPOINT_FILE).unwrap();
+        assert_eq!(checkpoint.status_code, 3);
+        assert_eq!(checkpoint.pending_windows, pending);
+        assert_eq!(checkpoint.page, 4);
+        assert_eq!(checkpoint.report, report);
+        assert!(checkpoint.matches(&range));
+    }
+
+    #[test]
+    fn checkpoint_resumes_only_the_same_range() {
+        let range = SyncRange { start: date("2024-01-01"), end: date("2024-01-31"), window_days: 7, open_end: false };
+        let checkpoint = SyncCheckpoint {
+            range_start: range.start,
+            range_end: range.end,
+            window_days: 7,
+            open_end: false,
+            status_code: 2,
+            pending_windows: Vec::new(),
+            page: 1,
+            report: FullSyncReport::default(),
+            updated_at: String::new(),
+        };
+        assert!(checkpoint.matches(&range));
+        assert!(!checkpoint.matches(&SyncRange { window_days: 3, ..range.clone() }));
+        assert!(!checkpoint.matches(&SyncRange { end: date("2024-02-29"), ..range }));
+    }
+
+    #[test]
+    fn checkpoint_without_end_date_resumes_on_a_later_day() {
+        let range = SyncRange { start: date("2024-01-01"), end: date("2024-01-31"), window_days: 7, open_end: true };
+        let checkpoint = SyncCheckpoint {
+            range_start: range.start,
+            range_end: range.end,
+            window_days: 7,
+            open_end: true,
+            status_code: 3,
+            pending_windows: Vec::new(),
+            page: 1,
+            report: FullSyncReport::default(),
+            updated_at: String::new(),
+        };
+        let next_day = SyncRange { end: date("2024-02-01"), ..range.clone() };
+        assert!(checkpoint.matches(&next_day));
+        // 之后设置了截止日期，或断点本身有截止日期时不继续
+        assert!(!checkpoint.matches(&SyncRange { open_end: false, ..next_day.clone() }));
+        assert!(!SyncCheckpoint { open_end: false, ..checkpoint.clone() }.matches(&next_day));
+        assert!(!checkpoint.matches(&SyncRange { end: date("2024-01-30"), ..range }));
+    }
+
+    #[test]
+    fn checkpoint_without_failed_windows_still_loads() {
+        let json = r#"{"rangeStart":"2024-01-01","rangeEnd":"2024-01-31","windowDays":7,"statusCode":4,
+            "pendingWindows":[{"start":"2024-01-15","end":"2024-01-21"}],"page":2,
+            "report":{"saved":5,"splitWindows":0,"truncatedWindows":[]},"updatedAt":""}"#;
+        let checkpoint: SyncCheckpoint = serde_json::from_str(json).unwrap();
+        assert_eq!(checkpoint.report.failed_windows, 0);
+        assert_eq!(checkpoint.pending_windows[0].to_string(), "2024-01-15 ~ 2024-01-21");
     }
 }
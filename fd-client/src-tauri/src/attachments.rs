@@ -0,0 +1,216 @@
+use crate::models::Attachment;
+use crate::settings::Settings;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 附件存储目录 (位于数据目录)
+pub const ATTACHMENTS_DIR: &str = "attachments";
+/// 附件 ID 到内容哈希的索引
+const INDEX_FILE: &str = "index.json";
+
+/// 默认允许下载的类型
+pub const DEFAULT_MIME_TYPES: &str = "image/*,application/pdf,text/plain,video/*";
+
+/// 附件下载限制：单个文件大小上限与 MIME 白名单 (支持 `image/*` 形式)
+#[derive(Debug, Clone)]
+pub struct AttachmentPolicy {
+    /// 0 表示不下载附件
+    pub max_bytes: u64,
+    pub mime_types: Vec<String>,
+}
+
+impl Default for AttachmentPolicy {
+    fn default() -> Self {
+        AttachmentPolicy {
+            max_bytes: 25 * 1024 * 1024,
+            mime_types: parse_mime_types(DEFAULT_MIME_TYPES),
+        }
+    }
+}
+
+impl AttachmentPolicy {
+    pub fn from_settings(settings: &Settings) -> Self {
+        AttachmentPolicy {
+            max_bytes: settings.attachment_max_mb as u64 * 1024 * 1024,
+            mime_types: parse_mime_types(&settings.attachment_mime_types),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_bytes > 0
+    }
+
+    pub fn allows_type(&self, content_type: &str) -> bool {
+        let essence = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        self.mime_types.iter().any(|allowed| match allowed.strip_suffix("/*") {
+            Some(prefix) => essence.split('/').next() == Some(prefix),
+            None => *allowed == essence,
+        })
+    }
+
+    /// 按元数据判断是否下载；不下载时返回原因
+    pub fn check(&self, attachment: &Attachment) -> Result<(), String> {
+        let content_type = attachment.content_type.as_deref().unwrap_or("application/octet-stream");
+        if !self.allows_type(content_type) {
+            return Err(format!("type {} not allowed", content_type));
+        }
+        if attachment.size > self.max_bytes {
+            return Err(format!("{} bytes exceeds the {} byte limit", attachment.size, self.max_bytes));
+        }
+        Ok(())
+    }
+}
+
+fn parse_mime_types(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|s| s.trim().to_ascii_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// 索引中的一条附件记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredAttachment {
+    pub attachment_id: u64,
+    pub sha256: String,
+    pub name: String,
+    pub content_type: Option<String>,
+    pub size: u64,
+    pub stored_at: String,
+}
+
+/// 按内容寻址的附件存储：文件保存为 `attachments/<哈希前两位>/<哈希>.<扩展名>`，
+/// 相同内容只保存一份；`index.json` 记录附件 ID 对应的文件
+pub struct AttachmentStore {
+    root: PathBuf,
+    policy: AttachmentPolicy,
+    index: Mutex<BTreeMap<u64, StoredAttachment>>,
+}
+
+impl AttachmentStore {
+    pub fn new(data_dir: &str, policy: AttachmentPolicy) -> Self {
+        let root = Path::new(data_dir).join(ATTACHMENTS_DIR);
+        let index = fs::read_to_string(root.join(INDEX_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        AttachmentStore {
+            root,
+            policy,
+            index: Mutex::new(index),
+        }
+    }
+
+    pub fn policy(&self) -> &AttachmentPolicy {
+        &self.policy
+    }
+
+    /// 已保存且文件仍存在的附件
+    pub fn get(&self, attachment_id: u64) -> Option<StoredAttachment> {
+        let stored = self.index.lock().unwrap().get(&attachment_id).cloned()?;
+        self.blob_path(&stored).exists().then_some(stored)
+    }
+
+    /// 附件的本地文件路径
+    pub fn local_path(&self, attachment_id: u64) -> Result<PathBuf, String> {
+        let stored = self
+            .get(attachment_id)
+            .ok_or_else(|| format!("Attachment {} has not been downloaded", attachment_id))?;
+        Ok(self.blob_path(&stored))
+    }
+
+    /// 保存附件内容并登记到索引；内容已存在时只更新索引
+    pub fn store(&self, attachment: &Attachment, bytes: &[u8]) -> Result<StoredAttachment, String> {
+        let stored = StoredAttachment {
+            attachment_id: attachment.id,
+            sha256: format!("{:x}", Sha256::digest(bytes)),
+            name: attachment.name.clone(),
+            content_type: attachment.content_type.clone(),
+            size: bytes.len() as u64,
+            stored_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        let path = self.blob_path(&stored);
+        if !path.exists() {
+            let dir = path.parent().ok_or("Invalid attachment path")?;
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            // 先写临时文件再改名，中断时不会留下不完整的文件
+            let tmp = path.with_extension("part");
+            fs::write(&tmp, bytes).map_err(|e| e.to_string())?;
+            fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+        }
+
+        let mut index = self.index.lock().unwrap();
+        index.insert(attachment.id, stored.clone());
+        let json = serde_json::to_string_pretty(&*index).map_err(|e| e.to_string())?;
+        crate::fs_store::atomic_write(&self.root.join(INDEX_FILE), json.as_bytes())?;
+        Ok(stored)
+    }
+
+    fn blob_path(&self, stored: &StoredAttachment) -> PathBuf {
+        let mut file_name = stored.sha256.clone();
+        if let Some(ext) = safe_extension(&stored.name) {
+            file_name.push('.');
+            file_name.push_str(&ext);
+        }
+        self.root.join(&stored.sha256[..2]).join(file_name)
+    }
+}
+
+/// 取文件名中可安全用于路径的扩展名 (字母数字，最多 10 个字符)
+fn safe_extension(name: &str) -> Option<String> {
+    let ext = Path::new(name).extension()?.to_str()?;
+    (!ext.is_empty() && ext.len() <= 10 && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then(|| ext.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(id: u64, name: &str, content_type: &str, size: u64) -> Attachment {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "name": name, "content_type": content_type, "size": size,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn policy_filters_by_type_and_size() {
+        let policy = AttachmentPolicy { max_bytes: 100, mime_types: parse_mime_types("image/*, application/PDF") };
+        assert!(policy.check(&attachment(1, "a.png", "image/png", 10)).is_ok());
+        assert!(policy.check(&attachment(2, "a.pdf", "application/pdf; charset=binary", 10)).is_ok());
+        assert_eq!(policy.check(&attachment(3, "a.zip", "application/zip", 10)).unwrap_err(), "type application/zip not allowed");
+        assert!(policy.check(&attachment(4, "b.png", "image/png", 101)).is_err());
+    }
+
+    #[test]
+    fn stored_content_is_deduplicated_and_indexed() {
+        let dir = crate::test_util::temp_dir("attachments");
+        let data_dir = dir.to_string_lossy().into_owned();
+        let store = AttachmentStore::new(&data_dir, AttachmentPolicy::default());
+
+        let first = store.store(&attachment(1, "Report.PDF", "application/pdf", 5), b"hello").unwrap();
+        let second = store.store(&attachment(2, "copy.pdf", "application/pdf", 5), b"hello").unwrap();
+        assert_eq!(first.sha256, second.sha256);
+        assert_eq!(store.local_path(1).unwrap(), store.local_path(2).unwrap());
+        assert!(store.local_path(1).unwrap().to_string_lossy().ends_with(".pdf"));
+
+        // 索引写入后重新打开仍可找到，且没有残留的临时文件
+        let reopened = AttachmentStore::new(&data_dir, AttachmentPolicy::default());
+        assert_eq!(reopened.get(2).unwrap().name, "copy.pdf");
+        let leftovers: Vec<_> = fs::read_dir(dir.join(ATTACHMENTS_DIR))
+            .unwrap()
+            .flatten()
+            .filter(|e| e.path().is_file() && e.file_name() != INDEX_FILE)
+            .collect();
+        assert!(leftovers.is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
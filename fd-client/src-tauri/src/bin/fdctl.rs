@@ -0,0 +1,729 @@
+//! fdctl: 无界面运行同步、翻译、导出与 MQ 消费 (适用于 Linux 构建机与 cron)
+//!
+//! 用法见 `fdctl help`。未显式传入的参数从桌面端的 settings.db 读取。
+
+use fd_client::ai::{GeminiClient, TranslateFormat};
+use fd_client::api::{FreshdeskClient, SyncCheckpoint, SYNC_CHECKPOINT_FILE};
+use fd_client::attachments::{AttachmentPolicy, AttachmentStore};
+use fd_client::directory::Directory;
+use fd_client::events::{ConsoleSink, EventSink};
+use fd_client::history::HistoryRetention;
+use fd_client::jobs::SyncJobs;
+use fd_client::lang;
+use fd_client::models::TicketFilter;
+use fd_client::mq_consumer::{MqConfig, MqConsumer, MqConsumerState};
+use fd_client::query::{query_tickets, requester_tickets, SortKey, SortOrder, TicketListQuery, MAX_PAGE_LIMIT};
+use fd_client::search::{self, SearchQuery};
+use fd_client::settings::{self, Settings};
+use fd_client::sqlite_store::import_json_dir;
+use fd_client::storage::{IssueAction, Storage};
+use fd_client::sync::{SyncOptions, MAX_SYNC_CONCURRENCY};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::io::AsyncBufReadExt;
+
+const USAGE: &str = "\
+fdctl - fd-client headless CLI
+
+USAGE:
+    fdctl [GLOBAL OPTIONS] <COMMAND> [ARGS]
+
+COMMANDS:
+    sync [--full] [--from D] [--to D] [--window N] [--concurrency C]
+                                      Sync tickets from Freshdesk (incremental by default);
+                                      full sync covers --from..--to (YYYY-MM-DD or YYYY-MM)
+                                      in windows of N days; C conversation requests run
+                                      in parallel; Ctrl-C cancels (a full sync
+                                      resumes from its checkpoint next time)
+    retry-failed                      Reprocess only the tickets recorded as failed
+    failures                          List tickets that failed to sync (id, step, error)
+    checkpoint [show|discard]         Show or discard the interrupted full-sync checkpoint
+    translate <ID>... [--lang L] [--format text|html]
+                                      Translate local tickets and save the result; html
+                                      translates the HTML bodies, keeping links and images
+    export --out <FILE> [--lang L] [ID]...
+                                      Export tickets to CSV (all tickets when no ID given)
+    consume translate|reply --token T Consume the server task queue until Ctrl-C
+    list [--lang L] [--has-lang L]... [--missing-lang L] [--sort KEY[:asc|desc]]
+         [--limit N [--cursor C]] [FILTERS]
+                                      List local tickets (id, status, last activity,
+                                      subject); KEY: id (default), created_at, updated_at,
+                                      last_activity, priority, status, subject; with --limit
+                                      prints one page and the cursor of the next;
+                                      FILTERS: --tag T (repeatable), --type T, --source N,
+                                      --group ID, --product ID, --company ID, --status N,
+                                      --priority N, --from D, --to D (creation date),
+                                      --due-before DATE, --requester ID|EMAIL, --agent ID,
+                                      --field cf_name=value (repeatable)
+    search <QUERY> [--status N] [--lang L|original] [--requester ID|EMAIL]
+           [--from D] [--to D] [--days N] [--page N] [--page-size N] [--rebuild]
+                                      Full-text search over subjects, descriptions and
+                                      conversations (originals and translations); words
+                                      must all match, \"...\" is a phrase, -word excludes,
+                                      word* matches a prefix; dates filter creation time
+    history <ID> [--lang L] [--diff A..B]
+                                      Show a ticket's version timeline, or the field
+                                      changes from version A to version B
+    history prune                     Delete old versions per the retention settings
+    requester <ID|EMAIL> [--lang L]   List a requester's tickets with their contact details
+    fields                            List synced ticket fields (name, type, label)
+    attachment <ID>                   Print the local path of a downloaded attachment
+    db import                         Import the JSON ticket files (and translations) into
+                                      tickets.db in the data directory
+    migrate                           Rewrite ticket files in the current format and fix
+                                      file names that do not match the Freshdesk status
+    check [--repair]                  Check ticket files for orphan translations, status /
+                                      file name mismatches, duplicates and corrupt JSON;
+                                      --repair renames mismatched files and moves the
+                                      rest to quarantine/ in the data directory
+    delete <ID>... [--lang L]         Delete local tickets (with their translations), or
+                                      only the L translation
+    watch [--interval SECS]           Print a `ticket-updated <ID>` line whenever another
+                                      process changes a ticket file, until Ctrl-C; the
+                                      directory is polled every SECS seconds (default:
+                                      the watch interval setting)
+    langs                             List supported languages (BCP-47 code, name, aliases);
+                                      L options accept a code or an alias such as cn or jp
+    accounts                          List configured Freshdesk accounts
+    help                              Show this message
+
+GLOBAL OPTIONS:
+    --settings <PATH>   settings.db to read (default: the desktop app's one)
+    --account <NAME>    Use a named account profile (domain, key, data dir)
+    --domain <DOMAIN>   Override the Freshdesk domain
+    --data-dir <DIR>    Override output_dir: a JSON directory, `sqlite:<DIR>` or a
+                        path ending in .db for SQLite, `memory:` for an in-memory store
+    --api-key <KEY>     Override the Freshdesk API key
+    --quiet             Do not print progress lines
+
+In `consume reply` mode each reply request is printed as a `mq-reply-request`
+line with its ticketId, externalId and subject; answer it on stdin with `<ticket_id> ok` or `<ticket_id> fail`.";
+
+/// 解析后的命令行
+struct Cli {
+    settings: Settings,
+    quiet: bool,
+    command: String,
+    args: Vec<String>,
+}
+
+impl Cli {
+    fn parse() -> Result<Self, String> {
+        let mut settings_path: Option<PathBuf> = None;
+        let mut account: Option<String> = None;
+        let mut domain: Option<String> = None;
+        let mut data_dir: Option<String> = None;
+        let mut api_key: Option<String> = None;
+        let mut quiet = false;
+        let mut rest = Vec::new();
+
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--settings" => settings_path = Some(PathBuf::from(next_value(&mut iter, &arg)?)),
+                "--account" => account = Some(next_value(&mut iter, &arg)?),
+                "--domain" => domain = Some(next_value(&mut iter, &arg)?),
+                "--data-dir" => data_dir = Some(next_value(&mut iter, &arg)?),
+                "--api-key" => api_key = Some(next_value(&mut iter, &arg)?),
+                "--quiet" => quiet = true,
+                _ => rest.push(arg),
+            }
+        }
+
+        let mut settings = match settings_path.or_else(settings::default_db_path) {
+            Some(path) => settings::load_settings_from(&path),
+            None => Settings::default(),
+        };
+        if let Some(name) = account {
+            settings.select_account(&name)?;
+        }
+        if let Some(d) = domain {
+            settings.freshdesk_domain = d;
+        }
+        if let Some(dir) = data_dir {
+            settings.output_dir = dir;
+        }
+        if let Some(key) = api_key {
+            settings.api_key = key;
+        }
+
+        let mut rest = rest.into_iter();
+        let command = rest.next().unwrap_or_else(|| "help".to_string());
+        Ok(Cli {
+            settings,
+            quiet,
+            command,
+            args: rest.collect(),
+        })
+    }
+}
+
+fn next_value(iter: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    iter.next().ok_or_else(|| format!("{} requires a value", flag))
+}
+
+/// 从子命令参数中取出 `--flag value`，返回值与剩余的位置参数
+fn take_option(args: &[String], flag: &str) -> Result<(Option<String>, Vec<String>), String> {
+    let mut value = None;
+    let mut positional = Vec::new();
+    let mut iter = args.iter().cloned();
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            value = Some(next_value(&mut iter, flag)?);
+        } else {
+            positional.push(arg);
+        }
+    }
+    Ok((value, positional))
+}
+
+/// 取出可重复出现的选项的全部取值
+fn take_values(args: &[String], flag: &str) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut values = Vec::new();
+    let mut positional = Vec::new();
+    let mut iter = args.iter().cloned();
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            values.push(next_value(&mut iter, flag)?);
+        } else {
+            positional.push(arg);
+        }
+    }
+    Ok((values, positional))
+}
+
+fn parse_number<T: std::str::FromStr>(value: Option<String>, flag: &str) -> Result<Option<T>, String> {
+    value
+        .map(|v| v.parse().map_err(|_| format!("Invalid {}: {}", flag, v)))
+        .transpose()
+}
+
+/// `list` 的筛选选项；自定义字段的值按同步下来的字段类型解析
+fn parse_filter(storage: &Storage, args: &[String]) -> Result<TicketFilter, String> {
+    let (tags, rest) = take_values(args, "--tag")?;
+    let (fields, rest) = take_values(&rest, "--field")?;
+    let (ticket_type, rest) = take_option(&rest, "--type")?;
+    let (source, rest) = take_option(&rest, "--source")?;
+    let (group, rest) = take_option(&rest, "--group")?;
+    let (product, rest) = take_option(&rest, "--product")?;
+    let (company, rest) = take_option(&rest, "--company")?;
+    let (status, rest) = take_option(&rest, "--status")?;
+    let (priority, rest) = take_option(&rest, "--priority")?;
+    let (created_from, rest) = take_option(&rest, "--from")?;
+    let (created_to, rest) = take_option(&rest, "--to")?;
+    let (due_before, rest) = take_option(&rest, "--due-before")?;
+    let (requester, rest) = take_option(&rest, "--requester")?;
+    let (responder, rest) = take_option(&rest, "--agent")?;
+    if let Some(arg) = rest.first() {
+        return Err(format!("Unexpected argument: {}", arg));
+    }
+
+    let schema = storage.ticket_field_schema();
+    let mut custom_fields = BTreeMap::new();
+    for field in fields {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid --field (expected name=value): {}", field))?;
+        custom_fields.insert(name.to_string(), schema.coerce(name, value));
+    }
+
+    Ok(TicketFilter {
+        tags,
+        ticket_type,
+        source: parse_number(source, "--source")?,
+        group_id: parse_number(group, "--group")?,
+        product_id: parse_number(product, "--product")?,
+        company_id: parse_number(company, "--company")?,
+        requester_id: requester
+            .map(|r| Directory::load(storage).resolve_contact(&r))
+            .transpose()?,
+        responder_id: parse_number(responder, "--agent")?,
+        status: parse_number(status, "--status")?,
+        priority: parse_number(priority, "--priority")?,
+        created_from,
+        created_to,
+        due_before,
+        custom_fields,
+    })
+}
+
+fn parse_ids(args: &[String]) -> Result<Vec<u64>, String> {
+    args.iter()
+        .map(|a| a.parse::<u64>().map_err(|_| format!("Invalid ticket ID: {}", a)))
+        .collect()
+}
+
+async fn run(cli: Cli) -> Result<(), String> {
+    let sink = Arc::new(ConsoleSink { quiet_progress: cli.quiet });
+    let settings = &cli.settings;
+    if !matches!(cli.command.as_str(), "help" | "--help" | "-h" | "accounts" | "langs") {
+        repair_storage(sink.as_ref(), &settings.output_dir);
+    }
+
+    match cli.command.as_str() {
+        "sync" => {
+            if settings.api_key.is_empty() {
+                return Err("No API key configured (use --api-key)".to_string());
+            }
+            let (from, rest) = take_option(&cli.args, "--from")?;
+            let (to, rest) = take_option(&rest, "--to")?;
+            let (window, rest) = take_option(&rest, "--window")?;
+            let (concurrency, rest) = take_option(&rest, "--concurrency")?;
+            let window = window
+                .map(|w| w.parse::<u32>().map_err(|_| format!("Invalid --window: {}", w)))
+                .transpose()?;
+            let full_sync = rest.iter().any(|a| a == "--full");
+            let mut options = SyncOptions::from_settings(settings, full_sync, from.as_deref(), to.as_deref(), window)?;
+            if let Some(c) = concurrency {
+                let c = c.parse::<usize>().map_err(|_| format!("Invalid --concurrency: {}", c))?;
+                options.concurrency = c.clamp(1, MAX_SYNC_CONCURRENCY);
+            }
+            let client = FreshdeskClient::new(&settings.freshdesk_domain, &settings.api_key);
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            let jobs = cancellable_jobs();
+            let summary = fd_client::sync::run_sync_job(&jobs, sink.as_ref(), &client, &storage, &options).await?;
+            println!("{}", summary);
+        }
+        "retry-failed" => {
+            if settings.api_key.is_empty() {
+                return Err("No API key configured (use --api-key)".to_string());
+            }
+            let options = SyncOptions::from_settings(settings, false, None, None, None)?;
+            let client = FreshdeskClient::new(&settings.freshdesk_domain, &settings.api_key);
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            let jobs = cancellable_jobs();
+            let summary = fd_client::sync::run_retry_job(&jobs, sink.as_ref(), &client, &storage, &options).await?;
+            println!("{}", summary);
+        }
+        "failures" => {
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            for f in storage.sync_failures() {
+                println!("{}\t{}\t{}\t{}\t{}", f.ticket_id, f.step, f.attempts, f.failed_at, f.error);
+            }
+        }
+        "checkpoint" => {
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            match cli.args.first().map(String::as_str).unwrap_or("show") {
+                "show" => match storage.read_state::<SyncCheckpoint>(SYNC_CHECKPOINT_FILE) {
+                    Some(cp) => println!("{}", serde_json::to_string_pretty(&cp).map_err(|e| e.to_string())?),
+                    None => println!("No checkpoint"),
+                },
+                "discard" => {
+                    if storage.remove_state(SYNC_CHECKPOINT_FILE)? {
+                        println!("Checkpoint discarded");
+                    } else {
+                        println!("No checkpoint");
+                    }
+                }
+                other => return Err(format!("Unknown checkpoint action: {}", other)),
+            }
+        }
+        "translate" => {
+            let (lang, rest) = take_option(&cli.args, "--lang")?;
+            let (format, ids) = take_option(&rest, "--format")?;
+            let lang = lang::normalize(lang.as_deref().unwrap_or(&settings.translation_lang))?;
+            let format: TranslateFormat = format.as_deref().unwrap_or("text").parse()?;
+            let ids = parse_ids(&ids)?;
+            if ids.is_empty() {
+                return Err("translate requires at least one ticket ID".to_string());
+            }
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            let mut failed = 0;
+            for id in ids {
+                if let Err(e) = GeminiClient::translate_local_ticket(sink.as_ref(), &storage, id, &lang, format).await {
+                    sink.log(&format!("❌ #{}: {}", id, e));
+                    failed += 1;
+                }
+            }
+            if failed > 0 {
+                return Err(format!("{} ticket(s) failed to translate", failed));
+            }
+        }
+        "export" => {
+            let (out, rest) = take_option(&cli.args, "--out")?;
+            let (lang, ids) = take_option(&rest, "--lang")?;
+            let out = out.ok_or("export requires --out <FILE>")?;
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            let mut ids = parse_ids(&ids)?;
+            if ids.is_empty() {
+                ids = storage.list_tickets(lang.as_deref())?.iter().map(|t| t.id).collect();
+            }
+            let exported = fd_client::export::export_to_csv(&storage, &ids, lang.as_deref(), &out)?;
+            sink.log(&format!("✅ Exported {} tickets to {}", exported, out));
+        }
+        "consume" => {
+            let (token, rest) = take_option(&cli.args, "--token")?;
+            let token = token.ok_or("consume requires --token <AUTH_TOKEN>")?;
+            let queue_type = match rest.first().map(String::as_str) {
+                Some("translate") => "translate",
+                Some("reply") => "reply",
+                _ => return Err("consume requires `translate` or `reply`".to_string()),
+            };
+            consume(sink, settings, token, queue_type).await?;
+        }
+        "list" => {
+            let (lang, rest) = take_option(&cli.args, "--lang")?;
+            let (has_langs, rest) = take_values(&rest, "--has-lang")?;
+            let (missing_lang, rest) = take_option(&rest, "--missing-lang")?;
+            let (sort, rest) = take_option(&rest, "--sort")?;
+            let (limit, rest) = take_option(&rest, "--limit")?;
+            let (cursor, rest) = take_option(&rest, "--cursor")?;
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            let (sort, order) = match sort.as_deref().map(|s| s.split_once(':').unwrap_or((s, "desc"))) {
+                Some((key, "asc")) => (key.parse()?, SortOrder::Asc),
+                Some((key, "desc")) => (key.parse()?, SortOrder::Desc),
+                Some((_, other)) => return Err(format!("Unknown sort order: {} (expected asc or desc)", other)),
+                None => (SortKey::Id, SortOrder::Desc),
+            };
+            // 未指定 --limit 时逐页列出全部
+            let paged = limit.is_some();
+            let mut query = TicketListQuery {
+                filter: parse_filter(&storage, &rest)?,
+                lang,
+                has_langs,
+                missing_lang,
+                sort,
+                order,
+                cursor,
+                limit: Some(parse_number(limit, "--limit")?.unwrap_or(MAX_PAGE_LIMIT)),
+            };
+            loop {
+                let page = query_tickets(&storage, &query)?;
+                for t in &page.items {
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        t.id,
+                        t.status,
+                        t.last_activity_at.as_deref().unwrap_or_default(),
+                        t.subject.as_deref().unwrap_or_default()
+                    );
+                }
+                match page.next_cursor {
+                    Some(next) if paged => {
+                        sink.log(&format!("{} tickets; next page: --cursor {}", page.total, next));
+                        break;
+                    }
+                    Some(next) => query.cursor = Some(next),
+                    None => break,
+                }
+            }
+        }
+        "search" => {
+            let (status, rest) = take_option(&cli.args, "--status")?;
+            let (lang, rest) = take_option(&rest, "--lang")?;
+            let (requester, rest) = take_option(&rest, "--requester")?;
+            let (from, rest) = take_option(&rest, "--from")?;
+            let (to, rest) = take_option(&rest, "--to")?;
+            let (days, rest) = take_option(&rest, "--days")?;
+            let (page, rest) = take_option(&rest, "--page")?;
+            let (page_size, rest) = take_option(&rest, "--page-size")?;
+            let rebuild = rest.iter().any(|a| a == "--rebuild");
+            let words: Vec<String> = rest.into_iter().filter(|a| a != "--rebuild").collect();
+
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            if rebuild {
+                if let Some(index) = storage.search_index()? {
+                    let count = index.rebuild(&storage)?;
+                    sink.log(&format!("Search index rebuilt ({} documents)", count));
+                }
+                if words.is_empty() {
+                    return Ok(());
+                }
+            }
+            let from = match parse_number::<i64>(days, "--days")? {
+                Some(days) => Some((chrono::Utc::now() - chrono::Duration::days(days)).format("%Y-%m-%d").to_string()),
+                None => from,
+            };
+            let query = SearchQuery {
+                query: words.join(" "),
+                status: parse_number(status, "--status")?,
+                lang,
+                requester_id: requester
+                    .map(|r| Directory::load(&storage).resolve_contact(&r))
+                    .transpose()?,
+                from,
+                to,
+                page: parse_number(page, "--page")?,
+                page_size: parse_number(page_size, "--page-size")?,
+            };
+            let results = search::search_tickets(&storage, &query)?;
+            sink.log(&format!(
+                "{} matches (page {} of {})",
+                results.total,
+                results.page,
+                results.total.div_ceil(results.page_size).max(1)
+            ));
+            for hit in results.hits {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}",
+                    hit.ticket_id,
+                    hit.lang.as_deref().unwrap_or(search::ORIGINAL_LANG),
+                    hit.status,
+                    hit.subject.unwrap_or_default(),
+                    hit.snippet.replace("<mark>", "[").replace("</mark>", "]").replace('\n', " ")
+                );
+            }
+        }
+        "history" => {
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            let history = storage.history()?;
+            if cli.args.first().map(String::as_str) == Some("prune") {
+                let deleted = history.prune(&HistoryRetention::from_settings(settings))?;
+                println!("{} versions deleted", deleted);
+                return Ok(());
+            }
+            let (lang, rest) = take_option(&cli.args, "--lang")?;
+            let (diff, rest) = take_option(&rest, "--diff")?;
+            let id = rest.first().ok_or("history requires a ticket ID")?;
+            let id: u64 = id.parse().map_err(|_| format!("Invalid ticket ID: {}", id))?;
+            match diff {
+                Some(range) => {
+                    let (from, to) = range
+                        .split_once("..")
+                        .ok_or_else(|| format!("Invalid --diff (expected A..B): {}", range))?;
+                    let from = parse_number(Some(from.to_string()), "--diff")?.unwrap_or_default();
+                    let to = parse_number(Some(to.to_string()), "--diff")?.unwrap_or_default();
+                    for change in history.diff(id, lang.as_deref(), from, to)? {
+                        let show = |v: Option<serde_json::Value>| v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+                        println!("{}\t{}\t{}", change.field, show(change.before), show(change.after));
+                    }
+                }
+                None => {
+                    for v in history.timeline(id, lang.as_deref())? {
+                        println!(
+                            "{}\t{}\t{}\t{}\t{} conversations\t{}",
+                            v.version,
+                            v.saved_at,
+                            v.status,
+                            v.updated_at.unwrap_or_default(),
+                            v.conversations,
+                            v.changes.join(",")
+                        );
+                    }
+                }
+            }
+        }
+        "requester" => {
+            let (lang, rest) = take_option(&cli.args, "--lang")?;
+            let key = rest.first().ok_or("requester requires a contact ID or email")?;
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            let found = requester_tickets(&storage, key, lang)?;
+            if let Some(contact) = &found.contact {
+                sink.log(&format!(
+                    "{} <{}>{}{}",
+                    contact.name,
+                    contact.email.clone().unwrap_or_default(),
+                    contact.phone.as_ref().map(|p| format!(" {}", p)).unwrap_or_default(),
+                    found.company.as_ref().map(|c| format!(" - {}", c)).unwrap_or_default()
+                ));
+            }
+            for t in found.tickets {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    t.id,
+                    t.status,
+                    t.updated_at.unwrap_or_default(),
+                    t.subject.unwrap_or_default()
+                );
+            }
+        }
+        "delete" => {
+            let (lang, rest) = take_option(&cli.args, "--lang")?;
+            let ids = parse_ids(&rest)?;
+            if ids.is_empty() {
+                return Err("delete requires at least one ticket ID".to_string());
+            }
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            for id in ids {
+                let deleted = match &lang {
+                    Some(lang) => storage.delete_translation(id, lang)?,
+                    None => storage.delete_ticket(id)?,
+                };
+                if !deleted {
+                    sink.log(&format!("⚠️ #{} not found", id));
+                }
+            }
+        }
+        "attachment" => {
+            let id = cli.args.first().ok_or("attachment requires an attachment ID")?;
+            let id: u64 = id.parse().map_err(|_| format!("Invalid attachment ID: {}", id))?;
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            let store = AttachmentStore::new(storage.data_dir(), AttachmentPolicy::default());
+            println!("{}", store.local_path(id)?.display());
+        }
+        "fields" => {
+            let schema = Storage::new(&settings.output_dir).with_sink(sink.clone()).ticket_field_schema();
+            if schema.fields.is_empty() {
+                sink.log("No ticket fields synced yet (run `fdctl sync` first)");
+            }
+            for f in &schema.fields {
+                println!("{}\t{}\t{}", f.name, f.field_type, f.label);
+            }
+        }
+        "db" => match cli.args.first().map(String::as_str) {
+            Some("import") => {
+                let report = import_json_dir(&settings.output_dir)?;
+                for failure in &report.failed {
+                    sink.log(&format!("⚠️ {}", failure));
+                }
+                println!(
+                    "{} files: {} tickets, {} translations imported, {} failed",
+                    report.files,
+                    report.tickets,
+                    report.translations,
+                    report.failed.len()
+                );
+            }
+            _ => return Err("db requires `import`".to_string()),
+        },
+        "migrate" => {
+            let storage = Storage::new(&settings.output_dir).with_sink(sink.clone());
+            let report = storage.migrate_ticket_files()?;
+            for failure in &report.failed {
+                sink.log(&format!("⚠️ {}", failure));
+            }
+            println!(
+                "{} files: {} rewritten, {} renamed, {} unreadable",
+                report.total,
+                report.rewritten,
+                report.renamed,
+                report.failed.len()
+            );
+        }
+        "check" => {
+            let repair = match cli.args.as_slice() {
+                [] => false,
+                [flag] if flag == "--repair" => true,
+                _ => return Err("check accepts only --repair".to_string()),
+            };
+            let report = Storage::new(&settings.output_dir).with_sink(sink.clone()).check_data_dir(repair)?;
+            for issue in &report.issues {
+                let action = match issue.action {
+                    Some(IssueAction::Renamed) => "\trenamed",
+                    Some(IssueAction::Quarantined) => "\tquarantined",
+                    None => "",
+                };
+                println!("{}\t{}\t{}{}", issue.kind.name(), issue.path, issue.detail, action);
+            }
+            for failure in &report.failed {
+                sink.log(&format!("⚠️ {}", failure));
+            }
+            if let Some(dir) = &report.quarantine_dir {
+                println!("Quarantined files moved to {}", dir);
+            }
+            println!("{} files: {} issues, {} repaired", report.files, report.issues.len(), report.repaired());
+        }
+        "watch" => {
+            let (interval, _) = take_option(&cli.args, "--interval")?;
+            let interval = parse_number(interval, "--interval")?.unwrap_or(settings.watch_interval_secs).max(1);
+            let index = Storage::new(&settings.output_dir).with_sink(sink.clone())
+                .ticket_index()
+                .ok_or("watch requires a JSON data directory")?;
+            index.watch(sink.clone(), std::time::Duration::from_secs(interval.into()));
+            sink.log("Watching for ticket file changes (Ctrl-C to stop)");
+            tokio::signal::ctrl_c().await.map_err(|e| e.to_string())?;
+            index.unwatch();
+        }
+        "accounts" => {
+            for a in &settings.accounts {
+                let marker = if a.name == settings.active_account { "*" } else { " " };
+                println!("{} {}\t{}\t{}", marker, a.name, a.domain, a.output_dir);
+            }
+        }
+        "langs" => {
+            for l in lang::LANGUAGES {
+                println!("{}\t{}\t{}\t{}", l.code, l.name, l.native_name, l.aliases.join(","));
+            }
+        }
+        "help" | "--help" | "-h" => println!("{}", USAGE),
+        other => return Err(format!("Unknown command: {}\n\n{}", other, USAGE)),
+    }
+    Ok(())
+}
+
+/// 任务登记表；Ctrl-C 取消其中的任务 (全量同步的断点保留，下次运行时继续)
+fn cancellable_jobs() -> Arc<SyncJobs> {
+    let jobs = Arc::new(SyncJobs::default());
+    let cancel_jobs = jobs.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            for status in cancel_jobs.list() {
+                if let Ok(job) = cancel_jobs.get(&status.id) {
+                    let _ = job.cancel();
+                }
+            }
+        }
+    });
+    jobs
+}
+
+/// 运行 MQ 消费者直到 Ctrl-C；回复模式下从 stdin 读取完成信号
+async fn consume(sink: Arc<ConsoleSink>, settings: &Settings, token: String, queue_type: &str) -> Result<(), String> {
+    let state = MqConsumerState::default();
+    state.batch_size.store(settings.mq_batch_size, Ordering::SeqCst);
+    let consumer = MqConsumer::new_with_state(MqConfig::from_settings(settings), state.clone());
+
+    let stop_state = state.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            stop_state.is_running.store(false, Ordering::SeqCst);
+        }
+    });
+
+    if queue_type == "reply" {
+        let ack_state = state.clone();
+        let ack_sink = sink.clone();
+        tokio::spawn(async move {
+            let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let mut parts = line.split_whitespace();
+                let (Some(id), Some(result)) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+                let Ok(ticket_id) = id.parse::<i64>() else {
+                    ack_sink.log(&format!("⚠️ Invalid ticket ID: {}", id));
+                    continue;
+                };
+                match ack_state.pending_acks.lock().await.remove(&ticket_id) {
+                    Some(tx) => {
+                        let _ = tx.send(result == "ok");
+                    }
+                    None => ack_sink.log(&format!("⚠️ No pending reply task for ticket #{}", ticket_id)),
+                }
+            }
+        });
+    }
+
+    consumer.start_consuming(sink, token, queue_type).await
+}
+
+/// 启动时修复上次中断的工单写入
+fn repair_storage(sink: &dyn EventSink, output_dir: &str) {
+    match Storage::new(output_dir).repair_journal() {
+        Ok(report) if !report.is_empty() => sink.log(&report.summary()),
+        Ok(_) => {}
+        Err(e) => sink.log(&format!("⚠️ Storage repair failed: {}", e)),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = match Cli::parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
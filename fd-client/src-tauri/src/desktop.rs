@@ -0,0 +1,956 @@
+use crate::ai::{GeminiClient, TranslateFormat};
+use crate::api::{FreshdeskClient, SyncCheckpoint, SYNC_CHECKPOINT_FILE};
+use crate::attachments::{AttachmentPolicy, AttachmentStore};
+use crate::directory::Directory;
+use crate::events::EventSink;
+use crate::history::{FieldChange, TicketVersion};
+use crate::jobs::{SyncJobStatus, SyncJobs};
+use crate::models;
+use crate::mq_consumer::{MqConsumer, MqConfig, MqConsumerState};
+use crate::query::{RequesterTickets, TicketListQuery, TicketPage};
+use crate::search::{SearchQuery, SearchResults};
+use crate::settings::{self, Settings};
+use crate::storage::Storage;
+use crate::sync::SyncOptions;
+use crate::ticket_index::TicketIndex;
+use tauri::{AppHandle, Emitter, Manager, WebviewWindowBuilder, WebviewUrl, State};
+use tauri_plugin_dialog::DialogExt;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tokio::sync::Mutex as TokioMutex;
+
+fn log(app: &AppHandle, msg: &str) {
+    let _ = app.emit("log", msg.to_string());
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn sync_tickets(
+    app: AppHandle,
+    jobs: State<'_, SyncJobs>,
+    api_key: String,
+    output_dir: String,
+    full_sync: bool,
+    sync_start_date: String,
+    sync_end_date: Option<String>,
+    window_days: Option<u32>,
+    account: Option<String>,
+) -> Result<String, String> {
+    log(&app, "🔧 Initializing...");
+    let settings = settings::load_settings(&app);
+    let (domain, api_key, output_dir) = resolve_target(&app, &settings, account.as_deref(), api_key, output_dir)?;
+    let options = SyncOptions::from_settings(
+        &settings,
+        full_sync,
+        Some(&sync_start_date),
+        sync_end_date.as_deref(),
+        window_days,
+    )?;
+    let client = FreshdeskClient::new(&domain, &api_key);
+    let storage = open_storage(&app, &output_dir);
+    crate::sync::run_sync_job(&jobs, &app, &client, &storage, &options).await
+}
+
+/// 打开存储，索引与历史更新失败等警告发送到界面日志
+fn open_storage(app: &AppHandle, output_dir: &str) -> Storage {
+    Storage::new(output_dir).with_sink(Arc::new(app.clone()))
+}
+
+/// 指定账号时使用该账号的配置，否则沿用界面传入的 Key/目录与当前域名
+fn resolve_target(
+    app: &AppHandle,
+    settings: &Settings,
+    account: Option<&str>,
+    api_key: String,
+    output_dir: String,
+) -> Result<(String, String, String), String> {
+    match account {
+        Some(name) => {
+            let profile = settings.resolve_account(Some(name))?;
+            log(app, &format!("👤 Account: {} ({})", profile.name, profile.domain));
+            Ok((profile.domain, profile.api_key, profile.output_dir))
+        }
+        None => Ok((settings.freshdesk_domain.clone(), api_key, output_dir)),
+    }
+}
+
+/// 只重新处理失败记录中的工单
+#[tauri::command]
+async fn retry_failed_sync(
+    app: AppHandle,
+    jobs: State<'_, SyncJobs>,
+    api_key: String,
+    output_dir: String,
+    account: Option<String>,
+) -> Result<String, String> {
+    let settings = settings::load_settings(&app);
+    let (domain, api_key, output_dir) = resolve_target(&app, &settings, account.as_deref(), api_key, output_dir)?;
+    let options = SyncOptions::from_settings(&settings, false, None, None, None)?;
+    let client = FreshdeskClient::new(&domain, &api_key);
+    let storage = open_storage(&app, &output_dir);
+    crate::sync::run_retry_job(&jobs, &app, &client, &storage, &options).await
+}
+
+#[tauri::command]
+fn get_sync_failures_cmd(output_dir: String) -> Vec<models::SyncFailure> {
+    Storage::new(&output_dir).sync_failures()
+}
+
+#[tauri::command]
+fn list_local_tickets(
+    app: AppHandle,
+    output_dir: String,
+    lang: Option<String>,
+    account: Option<String>,
+    filter: Option<models::TicketFilter>,
+) -> Result<Vec<models::Ticket>, String> {
+    let output_dir = match account.as_deref() {
+        Some(name) => settings::load_settings(&app).resolve_account(Some(name))?.output_dir,
+        None => output_dir,
+    };
+    let storage = Storage::new(&output_dir);
+    let mut tickets = storage.list_tickets(lang.as_deref())?;
+    if let Some(filter) = filter {
+        tickets.retain(|t| filter.matches(t));
+    }
+    Ok(tickets)
+}
+
+/// 分页查询本地工单 (摘要，不含正文)；完整工单用 `load_ticket_cmd` 读取
+#[tauri::command]
+fn query_local_tickets(
+    app: AppHandle,
+    output_dir: String,
+    query: TicketListQuery,
+    account: Option<String>,
+) -> Result<TicketPage, String> {
+    let output_dir = match account.as_deref() {
+        Some(name) => settings::load_settings(&app).resolve_account(Some(name))?.output_dir,
+        None => output_dir,
+    };
+    crate::query::query_tickets(&Storage::new(&output_dir), &query)
+}
+
+/// 全文搜索本地工单 (原文与译文)，索引不存在时先建立；`rebuild` 为 true 时重建索引
+#[tauri::command]
+fn search_tickets(app: AppHandle, output_dir: String, query: SearchQuery, rebuild: Option<bool>) -> Result<SearchResults, String> {
+    let storage = open_storage(&app, &output_dir);
+    if rebuild.unwrap_or(false) {
+        if let Some(index) = storage.search_index()? {
+            index.rebuild(&storage)?;
+        }
+    }
+    crate::search::search_tickets(&storage, &query)
+}
+
+/// 工单 (或某个译文) 的版本时间线
+#[tauri::command]
+fn get_ticket_history_cmd(output_dir: String, ticket_id: u64, lang: Option<String>) -> Result<Vec<TicketVersion>, String> {
+    let storage = Storage::new(&output_dir);
+    let history = storage.history()?;
+    history.timeline(ticket_id, lang.as_deref())
+}
+
+/// 两个版本之间的字段级差异
+#[tauri::command]
+fn diff_ticket_versions_cmd(
+    output_dir: String,
+    ticket_id: u64,
+    lang: Option<String>,
+    from: u32,
+    to: u32,
+) -> Result<Vec<FieldChange>, String> {
+    let storage = Storage::new(&output_dir);
+    let history = storage.history()?;
+    history.diff(ticket_id, lang.as_deref(), from, to)
+}
+
+/// 支持的翻译语言 (代码、名称与别名)
+#[tauri::command]
+fn list_languages_cmd() -> Vec<crate::lang::Language> {
+    crate::lang::LANGUAGES.to_vec()
+}
+
+/// 联系人 / 公司 / 客服缓存 (界面据此显示姓名)
+#[tauri::command]
+fn get_directory_cmd(output_dir: String) -> Directory {
+    Directory::load(&Storage::new(&output_dir))
+}
+
+/// 某个联系人 (ID 或邮箱) 的全部本地工单摘要与联系人信息
+#[tauri::command]
+fn list_requester_tickets_cmd(output_dir: String, requester: String, lang: Option<String>) -> Result<RequesterTickets, String> {
+    crate::query::requester_tickets(&Storage::new(&output_dir), &requester, lang)
+}
+
+/// 删除本地工单 (连同全部译文)；指定 `lang` 时只删除该译文。返回是否存在
+#[tauri::command]
+fn delete_local_ticket_cmd(app: AppHandle, output_dir: String, ticket_id: u64, lang: Option<String>) -> Result<bool, String> {
+    let storage = open_storage(&app, &output_dir);
+    match lang {
+        Some(lang) => storage.delete_translation(ticket_id, &lang),
+        None => storage.delete_ticket(ticket_id),
+    }
+}
+
+/// 已下载附件的本地路径 (供界面打开)
+#[tauri::command]
+fn get_attachment_path_cmd(output_dir: String, attachment_id: u64) -> Result<String, String> {
+    let storage = Storage::new(&output_dir);
+    let store = AttachmentStore::new(storage.data_dir(), AttachmentPolicy::default());
+    Ok(store.local_path(attachment_id)?.to_string_lossy().into_owned())
+}
+
+/// 同步下来的工单字段定义 (自定义字段的标签、类型与选项)
+#[tauri::command]
+fn get_ticket_fields_cmd(output_dir: String) -> models::TicketFieldSchema {
+    Storage::new(&output_dir).ticket_field_schema()
+}
+
+/// 所有同步任务状态 (最新的在前)
+#[tauri::command]
+fn get_sync_jobs_cmd(jobs: State<'_, SyncJobs>) -> Vec<SyncJobStatus> {
+    jobs.list()
+}
+
+#[tauri::command]
+fn cancel_sync_job_cmd(app: AppHandle, jobs: State<'_, SyncJobs>, job_id: String) -> Result<SyncJobStatus, String> {
+    let job = jobs.get(&job_id)?;
+    job.cancel()?;
+    log(&app, &format!("⏹️ Cancelling sync job {}...", job_id));
+    let _ = app.emit("sync-job", job.status());
+    Ok(job.status())
+}
+
+#[tauri::command]
+fn pause_sync_job_cmd(app: AppHandle, jobs: State<'_, SyncJobs>, job_id: String) -> Result<SyncJobStatus, String> {
+    let job = jobs.get(&job_id)?;
+    job.pause()?;
+    log(&app, &format!("⏸️ Sync job {} paused", job_id));
+    let _ = app.emit("sync-job", job.status());
+    Ok(job.status())
+}
+
+#[tauri::command]
+fn resume_sync_job_cmd(app: AppHandle, jobs: State<'_, SyncJobs>, job_id: String) -> Result<SyncJobStatus, String> {
+    let job = jobs.get(&job_id)?;
+    job.resume()?;
+    log(&app, &format!("▶️ Sync job {} resumed", job_id));
+    let _ = app.emit("sync-job", job.status());
+    Ok(job.status())
+}
+
+/// 查看未完成的全量同步断点
+#[tauri::command]
+fn get_sync_checkpoint_cmd(output_dir: String) -> Option<SyncCheckpoint> {
+    Storage::new(&output_dir).read_state(SYNC_CHECKPOINT_FILE)
+}
+
+/// 丢弃断点，下次全量同步从头开始
+#[tauri::command]
+fn discard_sync_checkpoint_cmd(output_dir: String) -> Result<bool, String> {
+    Storage::new(&output_dir).remove_state(SYNC_CHECKPOINT_FILE)
+}
+
+#[tauri::command]
+fn list_accounts_cmd(app: AppHandle) -> Vec<settings::AccountProfile> {
+    settings::load_settings(&app).accounts
+}
+
+#[tauri::command]
+fn save_account_cmd(app: AppHandle, name: String, domain: String, api_key: String, output_dir: String) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Account name is required".to_string());
+    }
+    let mut s = settings::load_settings(&app);
+    s.upsert_account(settings::AccountProfile {
+        name: name.trim().to_string(),
+        domain: FreshdeskClient::normalize_domain(&domain),
+        api_key,
+        output_dir,
+    });
+    settings::save_settings(&app, &s)
+}
+
+#[tauri::command]
+fn delete_account_cmd(app: AppHandle, name: String) -> Result<(), String> {
+    let mut s = settings::load_settings(&app);
+    s.remove_account(&name)?;
+    settings::save_settings(&app, &s)
+}
+
+/// 切换当前账号，返回切换后的设置供界面刷新
+#[tauri::command]
+fn select_account_cmd(app: AppHandle, name: String) -> Result<Settings, String> {
+    let mut s = settings::load_settings(&app);
+    s.select_account(&name)?;
+    settings::save_settings(&app, &s)?;
+    watch_data_dir(&app, &s);
+    Ok(s)
+}
+
+#[tauri::command]
+async fn select_folder(app: AppHandle) -> Result<String, String> {
+    let (tx, rx) = mpsc::channel();
+    
+    app.dialog()
+        .file()
+        .pick_folder(move |folder| {
+            let _ = tx.send(folder);
+        });
+    
+    match rx.recv() {
+        Ok(Some(path)) => Ok(path.to_string()),
+        Ok(None) => Err("No folder selected".to_string()),
+        Err(_) => Err("Dialog error".to_string()),
+    }
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn save_settings_cmd(
+    app: AppHandle,
+    api_key: String, 
+    output_dir: String, 
+    sync_start_date: String,
+    mq_host: String,
+    mq_port: u16,
+    mq_username: String,
+    mq_password: String,
+    translation_lang: String,
+    freshdesk_domain: Option<String>,
+    sync_end_date: Option<String>,
+    sync_window_days: Option<u32>,
+    sync_concurrency: Option<u32>,
+    attachment_max_mb: Option<u32>,
+    attachment_mime_types: Option<String>,
+    history_max_versions: Option<u32>,
+    history_max_days: Option<u32>,
+    watch_interval_secs: Option<u32>,
+) -> Result<(), String> {
+    println!("[Rust] save_settings_cmd: host={}, port={}, user={}, pass_len={}", 
+        mq_host, mq_port, mq_username, mq_password.len());
+    
+    // 加载现有设置以保留MQ消费者配置
+    let existing = settings::load_settings(&app);
+    
+    let mut s = Settings { 
+        api_key, 
+        output_dir, 
+        sync_start_date,
+        sync_end_date: sync_end_date.unwrap_or(existing.sync_end_date),
+        sync_window_days: sync_window_days.unwrap_or(existing.sync_window_days).max(1),
+        sync_concurrency: sync_concurrency.unwrap_or(existing.sync_concurrency).max(1),
+        attachment_max_mb: attachment_max_mb.unwrap_or(existing.attachment_max_mb),
+        attachment_mime_types: attachment_mime_types.unwrap_or(existing.attachment_mime_types),
+        history_max_versions: history_max_versions.unwrap_or(existing.history_max_versions),
+        history_max_days: history_max_days.unwrap_or(existing.history_max_days),
+        watch_interval_secs: watch_interval_secs.unwrap_or(existing.watch_interval_secs),
+        freshdesk_domain: freshdesk_domain
+            .map(|d| FreshdeskClient::normalize_domain(&d))
+            .unwrap_or(existing.freshdesk_domain),
+        // 保留账号列表，并把顶层修改回写到当前账号
+        active_account: existing.active_account,
+        accounts: existing.accounts,
+        mq_host,
+        mq_port,
+        mq_username,
+        mq_password,
+        // 保留现有的MQ消费者配置
+        mq_consumer_enabled: existing.mq_consumer_enabled,
+        mq_batch_size: existing.mq_batch_size,
+        translation_lang: crate::lang::normalize(&translation_lang)?,
+    };
+    s.sync_active_account();
+    settings::save_settings(&app, &s)?;
+    watch_data_dir(&app, &s);
+    Ok(())
+}
+
+#[tauri::command]
+fn load_settings_cmd(app: AppHandle) -> Settings {
+    settings::load_settings(&app)
+}
+
+#[tauri::command]
+fn sync_statuses_cmd(app: AppHandle, output_dir: String) -> Result<(usize, usize), String> {
+    let storage = open_storage(&app, &output_dir);
+    storage.sync_all_statuses()
+}
+
+/// 把 JSON 工单文件 (含译文) 导入数据目录下的 tickets.db
+#[tauri::command]
+fn import_tickets_to_sqlite_cmd(output_dir: String) -> Result<crate::sqlite_store::ImportReport, String> {
+    crate::sqlite_store::import_json_dir(&output_dir)
+}
+
+/// 迁移旧格式的工单文件，返回详细结果
+#[tauri::command]
+fn migrate_ticket_files_cmd(output_dir: String) -> Result<crate::storage::MigrationReport, String> {
+    Storage::new(&output_dir).migrate_ticket_files()
+}
+
+/// 检查数据目录中的工单文件；`repair` 时修复状态不符的文件名并隔离无法处理的文件
+#[tauri::command]
+fn check_data_dir_cmd(app: AppHandle, output_dir: String, repair: Option<bool>) -> Result<crate::storage::IntegrityReport, String> {
+    open_storage(&app, &output_dir).check_data_dir(repair.unwrap_or(false))
+}
+
+/// `format` 为 "html" 时翻译 HTML 正文 (保留链接与图片)，默认翻译纯文本
+#[tauri::command]
+async fn translate_ticket_cmd(
+    app: AppHandle,
+    output_dir: String,
+    ticket_id: u64,
+    target_lang: String,
+    format: Option<TranslateFormat>,
+) -> Result<models::Ticket, String> {
+    let storage = open_storage(&app, &output_dir);
+    GeminiClient::translate_local_ticket(&app, &storage, ticket_id, &target_lang, format.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn translate_ticket_direct_cmd(
+    app: AppHandle,
+    ticket: models::Ticket,
+    target_lang: String,
+    format: Option<TranslateFormat>,
+) -> Result<models::Ticket, String> {
+    GeminiClient::translate_ticket(&app, &ticket, &target_lang, format.unwrap_or_default()).await
+}
+
+
+/// 读取完整工单 (或译文)，填充已有的译文语言
+#[tauri::command]
+fn load_ticket_cmd(output_dir: String, ticket_id: u64, lang: Option<String>) -> Result<Option<models::Ticket>, String> {
+    let storage = Storage::new(&output_dir);
+    let Some(mut ticket) = storage.load_ticket(ticket_id, lang.as_deref())? else {
+        return Ok(None);
+    };
+    ticket.available_langs = storage.translation_langs(ticket_id)?;
+    Ok(Some(ticket))
+}
+
+#[tauri::command]
+async fn export_to_csv_cmd(
+    output_dir: String,
+    ticket_ids: Vec<u64>,
+    lang: Option<String>,
+    save_path: String,
+) -> Result<(), String> {
+    let storage = Storage::new(&output_dir);
+    crate::export::export_to_csv(&storage, &ticket_ids, lang.as_deref(), &save_path)?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn open_notebook_window(app: AppHandle, notebook_id: String, notebook_url: Option<String>) -> Result<(), String> {
+    println!("[Rust] open_notebook_window called with notebook_id: {}, notebook_url: {:?}", notebook_id, notebook_url);
+    let window_label = "notebook_shadow";
+
+    let target_url = if let Some(url) = notebook_url {
+        if url.is_empty() {
+            format!("https://notebooklm.google.com/notebook/{}", notebook_id)
+        } else {
+            url
+        }
+    } else {
+        format!("https://notebooklm.google.com/notebook/{}", notebook_id)
+    };
+    
+    // 如果窗口已存在，只需根据 URL 决定是否重新加载
+    if let Some(window) = app.get_webview_window(window_label) {
+        println!("[Rust] Shadow window already exists, checking URL...");
+        let current_url = window.url().map_err(|e| e.to_string())?;
+        if current_url.as_str() != target_url {
+            println!("[Rust] URL mismatch, navigating to new URL: {}", target_url);
+            window.navigate(target_url.parse().unwrap()).map_err(|e| e.to_string())?;
+        } else {
+            println!("[Rust] URL already matches, reusing existing window");
+        }
+        return Ok(());
+    }
+
+    // 创建新窗口（默认隐藏）
+    println!("[Rust] Creating new shadow window with URL: {}", target_url);
+    let builder = WebviewWindowBuilder::new(&app, window_label, WebviewUrl::External(target_url.parse().unwrap()))
+        .title("NotebookLM Shadow")
+        .inner_size(1280.0, 1000.0) // 强制桌面尺寸
+        .visible(false) // 影子窗口默认隐藏
+        .initialization_script(r#"
+            (function() {
+                console.log('Shadow initialization script running...');
+                window.__TAURI_SHADOW__ = true;
+                
+                // 确保 __TAURI__ API 可用
+                // 这会等待 Tauri 内部初始化完成
+                function waitForTauri(callback) {
+                    if (window.__TAURI_INTERNALS__) {
+                        window.__TAURI__ = {
+                            core: {
+                                invoke: function(cmd, args) {
+                                    return window.__TAURI_INTERNALS__.invoke(cmd, args);
+                                }
+                            }
+                        };
+                        console.log('Shadow IPC bridge ready');
+                        callback();
+                    } else {
+                        setTimeout(() => waitForTauri(callback), 100);
+                    }
+                }
+                
+                waitForTauri(function() {
+                    console.log('Shadow state fully initialized');
+                });
+            })();
+        "#);
+
+    let _window = builder.build().map_err(|e| {
+        println!("[Rust] Failed to build shadow window: {}", e);
+        e.to_string()
+    })?;
+    
+    println!("[Rust] Shadow window created successfully");
+    Ok(())
+}
+
+#[tauri::command]
+async fn forward_shadow_event(app: AppHandle, event: String, payload: String) -> Result<(), String> {
+    println!("[Rust] forward_shadow_event: event={}, payload_len={}", event, payload.len());
+    app.emit(&event, payload).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn execute_notebook_js(app: AppHandle, script: String) -> Result<(), String> {
+    println!("[Rust] execute_notebook_js called, script_len={}", script.len());
+    if let Some(window) = app.get_webview_window("notebook_shadow") {
+        println!("[Rust] Found shadow window, executing script...");
+        window.eval(&script).map_err(|e| {
+            println!("[Rust] Script eval failed: {}", e);
+            e.to_string()
+        })?;
+        println!("[Rust] Script executed successfully");
+        Ok(())
+    } else {
+        println!("[Rust] ERROR: Shadow window not found!");
+        Err("Shadow window not found".to_string())
+    }
+}
+
+#[tauri::command]
+async fn get_shadow_result(app: AppHandle) -> Result<String, String> {
+    println!("[Rust] get_shadow_result called");
+    if let Some(window) = app.get_webview_window("notebook_shadow") {
+        // 注入脚本：提取内容并通过 invoke 发送回 Rust
+        let extract_script = r#"
+            (function() {
+                try {
+                    // AI 回复在 .to-user-container .message-text-content 中
+                    const responses = document.querySelectorAll('.to-user-container .message-text-content');
+                    const lastResponse = responses[responses.length - 1];
+                    const text = lastResponse ? (lastResponse.innerText || lastResponse.textContent || "").trim() : "";
+                    
+                    // 检测是否完成：存在复制按钮说明生成完毕
+                    const isFinished = !!document.querySelector('.chat-message-pair:last-child .xap-copy-to-clipboard');
+                    
+                    const result = JSON.stringify({ text: text, finished: isFinished });
+                    console.log('[Shadow] Extraction done, length:', text.length, 'finished:', isFinished);
+                    
+                    // 通过 invoke 发送回 Rust
+                    if (window.__TAURI__ && window.__TAURI__.core && window.__TAURI__.core.invoke) {
+                        window.__TAURI__.core.invoke('forward_shadow_event', { 
+                            event: 'shadow-result', 
+                            payload: result 
+                        }).then(() => {
+                            console.log('[Shadow] Result sent via invoke');
+                        }).catch(e => {
+                            console.error('[Shadow] invoke error:', e);
+                        });
+                    } else {
+                        console.error('[Shadow] __TAURI__.core.invoke not available');
+                    }
+                } catch (e) {
+                    console.error('[Shadow] Extraction error:', e);
+                }
+            })();
+        "#;
+        
+        window.eval(extract_script).map_err(|e| e.to_string())?;
+        
+        // 返回占位符，实际结果通过事件传回
+        Ok("__PENDING__".to_string())
+    } else {
+        Err("Shadow window not found".to_string())
+    }
+}
+
+#[tauri::command]
+async fn get_notebook_window_visibility(app: AppHandle) -> Result<bool, String> {
+    if let Some(window) = app.get_webview_window("notebook_shadow") {
+        window.is_visible().map_err(|e| e.to_string())
+    } else {
+        Ok(false)
+    }
+}
+
+#[tauri::command]
+async fn toggle_notebook_window(app: AppHandle, visible: bool) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("notebook_shadow") {
+        if visible {
+            window.show().map_err(|e| e.to_string())?;
+            window.set_focus().map_err(|e| e.to_string())?;
+        } else {
+            window.hide().map_err(|e| e.to_string())?;
+        }
+        // 发送全局事件通知前端所有组件更新按钮状态
+        app.emit("notebook-window-visibility-changed", visible).map_err(|e| e.to_string())?;
+        Ok(())
+    } else {
+        Err("Shadow window not found".to_string())
+    }
+}
+
+// =========== MQ Consumer Commands ===========
+
+/// 翻译 MQ 消费者状态
+pub struct MqTranslateState {
+    consumer: Arc<TokioMutex<Option<MqConsumer>>>,
+    state: MqConsumerState,
+}
+
+impl Default for MqTranslateState {
+    fn default() -> Self {
+        let state = MqConsumerState::default();
+        state.batch_size.store(1, Ordering::SeqCst);
+        Self {
+            consumer: Arc::new(TokioMutex::new(None)),
+            state,
+        }
+    }
+}
+
+/// 回复 MQ 消费者状态
+pub struct MqReplyState {
+    consumer: Arc<TokioMutex<Option<MqConsumer>>>,
+    state: MqConsumerState,
+}
+
+impl Default for MqReplyState {
+    fn default() -> Self {
+        let state = MqConsumerState::default();
+        state.batch_size.store(1, Ordering::SeqCst);
+        Self {
+            consumer: Arc::new(TokioMutex::new(None)),
+            state,
+        }
+    }
+}
+
+#[tauri::command]
+async fn start_mq_consumer(
+    app: AppHandle,
+    auth_token: String,
+    mq_state: State<'_, MqTranslateState>,
+) -> Result<String, String> {
+    // 检查是否已在运行
+    if mq_state.state.is_running.load(std::sync::atomic::Ordering::SeqCst) {
+        return Err("Consumer already running".to_string());
+    }
+
+    // 从设置加载配置
+    let mut settings = settings::load_settings(&app);
+    let config = MqConfig::from_settings(&settings);
+    
+    // 设置 batch_size 到状态
+    mq_state.state.batch_size.store(settings.mq_batch_size, std::sync::atomic::Ordering::SeqCst);
+    
+    log(&app, &format!("🐰 Starting MQ consumer, connecting to {}:{}", config.host, config.port));
+
+    // 使用共享状态创建消费者
+    let consumer = MqConsumer::new_with_state(config, mq_state.state.clone());
+    
+    // 保存消费者实例
+    {
+        let mut lock = mq_state.consumer.lock().await;
+        *lock = Some(consumer);
+    }
+    
+    // 保存启动状态到设置
+    settings.mq_consumer_enabled = true;
+    let _ = settings::save_settings(&app, &settings);
+
+    // 启动消费（在后台任务中）
+    let sink: Arc<dyn EventSink> = Arc::new(app.clone());
+    let consumer_arc = mq_state.consumer.clone();
+    
+    tokio::spawn(async move {
+        let lock = consumer_arc.lock().await;
+        if let Some(ref consumer) = *lock {
+            if let Err(e) = consumer.start_consuming(sink.clone(), auth_token, "translate").await {
+                GeminiClient::log(sink.as_ref(), &format!("❌ Translation MQ Consumer error: {}", e));
+            }
+        }
+    });
+
+    Ok("MQ Consumer started".to_string())
+}
+
+#[tauri::command]
+async fn stop_mq_consumer(
+    app: AppHandle,
+    mq_state: State<'_, MqTranslateState>,
+) -> Result<String, String> {
+    mq_state.state.is_running.store(false, std::sync::atomic::Ordering::SeqCst);
+    
+    // 保存停止状态到设置
+    let mut settings = settings::load_settings(&app);
+    settings.mq_consumer_enabled = false;
+    let _ = settings::save_settings(&app, &settings);
+    
+    log(&app, "🛑 Stopping MQ consumer...");
+    Ok("MQ Consumer stopping".to_string())
+}
+
+#[tauri::command]
+async fn get_mq_consumer_status(
+    mq_state: State<'_, MqTranslateState>,
+) -> Result<serde_json::Value, String> {
+    let is_running = mq_state.state.is_running.load(std::sync::atomic::Ordering::SeqCst);
+    let batch_size = mq_state.state.batch_size.load(std::sync::atomic::Ordering::SeqCst);
+    let current_task = mq_state.state.current_task.lock().await.clone();
+    let translating = mq_state.state.translating_tickets.lock().await.clone();
+    let completed = mq_state.state.completed_tickets.lock().await.clone();
+    
+    Ok(serde_json::json!({
+        "isRunning": is_running,
+        "batchSize": batch_size,
+        "currentTask": current_task,
+        "translatingTickets": translating,
+        "completedTickets": completed
+    }))
+}
+
+#[tauri::command]
+async fn update_mq_batch_size(
+    app: AppHandle,
+    batch_size: u32,
+    mq_state: State<'_, MqTranslateState>,
+) -> Result<(), String> {
+    // 更新内存状态
+    mq_state.state.batch_size.store(batch_size, std::sync::atomic::Ordering::SeqCst);
+    
+    // 同步保存到设置
+    let mut settings = settings::load_settings(&app);
+    settings.mq_batch_size = batch_size;
+    settings::save_settings(&app, &settings)?;
+    
+    log(&app, &format!("⚙️ MQ batch size updated to {}", batch_size));
+    Ok(())
+}
+
+// =========== Reply MQ Commands ===========
+
+#[tauri::command]
+async fn start_reply_mq_consumer(
+    app: AppHandle,
+    auth_token: String,
+    mq_state: State<'_, MqReplyState>,
+) -> Result<String, String> {
+    if mq_state.state.is_running.load(Ordering::SeqCst) {
+        return Err("Reply Consumer already running".to_string());
+    }
+
+    let settings = settings::load_settings(&app);
+    let config = MqConfig::from_settings(&settings);
+    
+    mq_state.state.batch_size.store(settings.mq_batch_size, Ordering::SeqCst);
+    
+    log(&app, &format!("🐰 Starting Reply MQ consumer, connecting to {}:{}", config.host, config.port));
+
+    let consumer = MqConsumer::new_with_state(config, mq_state.state.clone());
+    
+    {
+        let mut lock = mq_state.consumer.lock().await;
+        *lock = Some(consumer);
+    }
+    
+    let sink: Arc<dyn EventSink> = Arc::new(app.clone());
+    let consumer_arc = mq_state.consumer.clone();
+    
+    tokio::spawn(async move {
+        let lock = consumer_arc.lock().await;
+        if let Some(ref consumer) = *lock {
+            if let Err(e) = consumer.start_consuming(sink.clone(), auth_token, "reply").await {
+                GeminiClient::log(sink.as_ref(), &format!("❌ Reply MQ Consumer error: {}", e));
+            }
+        }
+    });
+
+    Ok("Reply MQ Consumer started".to_string())
+}
+
+#[tauri::command]
+async fn stop_reply_mq_consumer(
+    app: AppHandle,
+    mq_state: State<'_, MqReplyState>,
+) -> Result<String, String> {
+    mq_state.state.is_running.store(false, Ordering::SeqCst);
+    log(&app, "🛑 Stopping Reply MQ consumer...");
+    Ok("Reply MQ Consumer stopping".to_string())
+}
+
+#[tauri::command]
+async fn get_reply_mq_consumer_status(
+    mq_state: State<'_, MqReplyState>,
+) -> Result<serde_json::Value, String> {
+    let is_running = mq_state.state.is_running.load(Ordering::SeqCst);
+    let batch_size = mq_state.state.batch_size.load(Ordering::SeqCst);
+    let current_task = mq_state.state.current_task.lock().await.clone();
+    let translating = mq_state.state.translating_tickets.lock().await.clone();
+    let completed = mq_state.state.completed_tickets.lock().await.clone();
+    
+    Ok(serde_json::json!({
+        "isRunning": is_running,
+        "batchSize": batch_size,
+        "currentTask": current_task,
+        "translatingTickets": translating, // 虽然变量名是 translating，但在 UI 上会对应“回复中”
+        "completedTickets": completed
+    }))
+}
+
+
+#[tauri::command]
+async fn complete_reply_task(
+    ticket_id: i64,
+    success: bool,
+    mq_state: State<'_, MqReplyState>,
+) -> Result<(), String> {
+    let mut p_acks = mq_state.state.pending_acks.lock().await;
+    if let Some(tx) = p_acks.remove(&ticket_id) {
+        let _ = tx.send(success);
+        Ok(())
+    } else {
+        Err(format!("No pending reply task found for ticket #{}", ticket_id))
+    }
+}
+
+#[tauri::command]
+async fn complete_translate_task(
+    ticket_id: i64,
+    success: bool,
+    mq_state: State<'_, MqTranslateState>,
+) -> Result<(), String> {
+    let mut p_acks = mq_state.state.pending_acks.lock().await;
+    if let Some(tx) = p_acks.remove(&ticket_id) {
+        let _ = tx.send(success);
+        Ok(())
+    } else {
+        Err(format!("No pending translation task found for ticket #{}", ticket_id))
+    }
+}
+
+
+/// 当前监视外部修改的工单索引
+#[derive(Default)]
+struct WatchedIndex(std::sync::Mutex<Option<Arc<TicketIndex>>>);
+
+/// 监视数据目录中其他进程对工单文件的修改 (发送 `ticket-updated`)，并停止监视之前的目录；
+/// 检查间隔为 0 时不监视
+fn watch_data_dir(app: &AppHandle, settings: &Settings) {
+    let watched = app.state::<WatchedIndex>();
+    let mut current = watched.0.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(index) = current.take() {
+        index.unwatch();
+    }
+    if settings.watch_interval_secs == 0 {
+        return;
+    }
+    if let Some(index) = Storage::new(&settings.output_dir).ticket_index() {
+        index.watch(Arc::new(app.clone()), std::time::Duration::from_secs(settings.watch_interval_secs.into()));
+        *current = Some(index);
+    }
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .manage(MqTranslateState::default())
+        .manage(MqReplyState::default())
+        .manage(SyncJobs::default())
+        .manage(WatchedIndex::default())
+        .setup(|app| {
+            let settings = settings::load_settings(app.handle());
+            // 修复上次中断的工单写入
+            match Storage::new(&settings.output_dir).repair_journal() {
+                Ok(report) if !report.is_empty() => app.handle().log(&report.summary()),
+                Ok(_) => {}
+                Err(e) => app.handle().log(&format!("⚠️ Storage repair failed: {}", e)),
+            }
+            // 建立工单索引并监视外部修改
+            watch_data_dir(app.handle(), &settings);
+            let mq_translate_state = app.state::<MqTranslateState>();
+            mq_translate_state.state.batch_size.store(settings.mq_batch_size, Ordering::SeqCst);
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            sync_tickets, 
+            list_local_tickets,
+            query_local_tickets,
+            search_tickets,
+            get_ticket_history_cmd,
+            diff_ticket_versions_cmd,
+            get_ticket_fields_cmd,
+            get_attachment_path_cmd,
+            delete_local_ticket_cmd,
+            get_directory_cmd,
+            list_languages_cmd,
+            list_requester_tickets_cmd,
+            retry_failed_sync,
+            get_sync_failures_cmd,
+            get_sync_jobs_cmd,
+            cancel_sync_job_cmd,
+            pause_sync_job_cmd,
+            resume_sync_job_cmd,
+            get_sync_checkpoint_cmd,
+            discard_sync_checkpoint_cmd,
+            list_accounts_cmd,
+            save_account_cmd,
+            delete_account_cmd,
+            select_account_cmd,
+            select_folder,
+            save_settings_cmd,
+            load_settings_cmd,
+            sync_statuses_cmd,
+            migrate_ticket_files_cmd,
+            check_data_dir_cmd,
+            import_tickets_to_sqlite_cmd,
+            translate_ticket_cmd,
+            translate_ticket_direct_cmd,
+            load_ticket_cmd,
+            export_to_csv_cmd,
+            open_notebook_window,
+            execute_notebook_js,
+            get_shadow_result,
+            forward_shadow_event,
+            toggle_notebook_window,
+            get_notebook_window_visibility,
+            // MQ 消费者命令
+            start_mq_consumer,
+            stop_mq_consumer,
+            get_mq_consumer_status,
+            update_mq_batch_size,
+            // Reply MQ
+            start_reply_mq_consumer,
+            stop_reply_mq_consumer,
+            get_reply_mq_consumer_status,
+            complete_reply_task,
+            complete_translate_task
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
@@ -0,0 +1,116 @@
+use crate::models::{Agent, Company, Contact};
+use crate::storage::Storage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 联系人 / 公司 / 客服缓存 (位于数据目录)
+const DIRECTORY_FILE: &str = "directory.json";
+
+/// 联系人、公司与客服的本地缓存，用于把工单中的 ID 显示为姓名、邮箱与公司
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Directory {
+    pub contacts: BTreeMap<u64, Contact>,
+    pub companies: BTreeMap<u64, Company>,
+    pub agents: BTreeMap<u64, Agent>,
+    /// 联系人增量刷新的游标 (已缓存联系人中最新的 updated_at)
+    pub contacts_updated_at: Option<String>,
+    pub refreshed_at: Option<String>,
+}
+
+/// 工单或会话中一个用户的显示信息
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Person {
+    pub id: u64,
+    pub name: String,
+    pub email: Option<String>,
+    pub company: Option<String>,
+    pub is_agent: bool,
+}
+
+impl Directory {
+    pub fn load(storage: &Storage) -> Self {
+        storage.read_state(DIRECTORY_FILE).unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &Storage) -> Result<(), String> {
+        storage.write_state(DIRECTORY_FILE, self)
+    }
+
+    /// 合并新拉取的联系人，并推进增量游标
+    pub fn merge_contacts(&mut self, contacts: Vec<Contact>) {
+        for contact in contacts {
+            if contact.updated_at > self.contacts_updated_at {
+                self.contacts_updated_at = contact.updated_at.clone();
+            }
+            self.contacts.insert(contact.id, contact);
+        }
+    }
+
+    /// 公司与客服数量不多，每次整体替换 (同时去掉已删除的)
+    pub fn replace_companies(&mut self, companies: Vec<Company>) {
+        self.companies = companies.into_iter().map(|c| (c.id, c)).collect();
+    }
+
+    pub fn replace_agents(&mut self, agents: Vec<Agent>) {
+        self.agents = agents.into_iter().map(|a| (a.id, a)).collect();
+    }
+
+    /// 按用户 ID 查找 (客服优先，其次联系人)
+    pub fn person(&self, user_id: u64) -> Option<Person> {
+        if let Some(agent) = self.agents.get(&user_id) {
+            return Some(Person {
+                id: user_id,
+                name: agent.contact.name.clone(),
+                email: agent.contact.email.clone(),
+                company: None,
+                is_agent: true,
+            });
+        }
+        let contact = self.contacts.get(&user_id)?;
+        Some(Person {
+            id: user_id,
+            name: contact.name.clone(),
+            email: contact.email.clone(),
+            company: contact.company_id.and_then(|id| self.company_name(id)),
+            is_agent: false,
+        })
+    }
+
+    pub fn company_name(&self, company_id: u64) -> Option<String> {
+        self.companies.get(&company_id).map(|c| c.name.clone())
+    }
+
+    /// 按邮箱查找联系人 (不区分大小写)
+    pub fn contact_by_email(&self, email: &str) -> Option<&Contact> {
+        self.contacts
+            .values()
+            .find(|c| c.email.as_deref().is_some_and(|e| e.eq_ignore_ascii_case(email)))
+    }
+
+    /// 联系人 ID 或邮箱解析为联系人 ID
+    pub fn resolve_contact(&self, id_or_email: &str) -> Result<u64, String> {
+        let key = id_or_email.trim();
+        if let Ok(id) = key.parse::<u64>() {
+            return Ok(id);
+        }
+        self.contact_by_email(key)
+            .map(|c| c.id)
+            .ok_or_else(|| format!("Unknown requester: {} (not in the contact cache)", key))
+    }
+
+    /// "姓名 <邮箱>"，未缓存时为 "#ID"
+    pub fn display_name(&self, user_id: Option<u64>) -> String {
+        let Some(id) = user_id else {
+            return String::new();
+        };
+        match self.person(id) {
+            Some(p) => match p.email {
+                Some(email) if !email.is_empty() => format!("{} <{}>", p.name, email),
+                _ => p.name,
+            },
+            None => format!("#{}", id),
+        }
+    }
+}
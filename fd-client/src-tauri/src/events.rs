@@ -0,0 +1,90 @@
+use serde_json::Value;
+
+/// 事件出口：核心逻辑只通过它上报日志与进度，不直接依赖 Tauri
+pub trait EventSink: Send + Sync {
+    /// 发送任意事件 (桌面端对应 `app.emit(name, payload)`)
+    fn event(&self, name: &str, payload: Value);
+
+    /// 发送 `log` 事件
+    fn log(&self, msg: &str) {
+        self.event("log", Value::String(msg.to_string()));
+    }
+
+    /// 发送 `progress` 事件
+    fn progress(&self, payload: Value) {
+        self.event("progress", payload);
+    }
+}
+
+#[cfg(feature = "desktop")]
+impl EventSink for tauri::AppHandle {
+    fn event(&self, name: &str, payload: Value) {
+        use tauri::Emitter;
+        let _ = self.emit(name, payload);
+    }
+}
+
+/// 终端输出：日志打到 stdout，进度打到 stderr，其余事件按 `name payload` 打印 (回复请求只打印工单信息)
+#[derive(Default)]
+pub struct ConsoleSink {
+    /// 为 true 时不打印进度 (例如输出被重定向到文件)
+    pub quiet_progress: bool,
+}
+
+impl EventSink for ConsoleSink {
+    fn event(&self, name: &str, payload: Value) {
+        match name {
+            "log" => match payload {
+                Value::String(s) => println!("{}", s),
+                other => println!("{}", other),
+            },
+            "progress" => {
+                if self.quiet_progress {
+                    return;
+                }
+                let phase = payload.get("phase").and_then(|v| v.as_str()).unwrap_or("");
+                let current = payload.get("current").and_then(|v| v.as_i64()).unwrap_or(0);
+                let total = payload.get("total").and_then(|v| v.as_i64()).unwrap_or(100);
+                match (payload.get("processed"), payload.get("totalTickets")) {
+                    (Some(p), Some(t)) => eprintln!("[{}] {}/{} ({}/{})", phase, current, total, p, t),
+                    _ => eprintln!("[{}] {}/{}", phase, current, total),
+                }
+            }
+            // 回复请求里带着服务端的 authToken，终端只打印工单信息
+            "mq-reply-request" => println!("{} {}", name, reply_request_summary(&payload)),
+            _ => println!("{} {}", name, payload),
+        }
+    }
+}
+
+/// 回复请求 (JSON 字符串或对象) 中可以打印的字段
+fn reply_request_summary(payload: &Value) -> Value {
+    let request = match payload {
+        Value::String(s) => serde_json::from_str(s).unwrap_or(Value::Null),
+        other => other.clone(),
+    };
+    serde_json::json!({
+        "ticketId": request.get("ticketId"),
+        "externalId": request.get("externalId"),
+        "subject": request.get("subject"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reply_request_summary_drops_the_auth_token() {
+        let payload = serde_json::json!({
+            "ticketId": 7,
+            "externalId": "ext-7",
+            "subject": "Printer",
+            "description": "offline",
+            "authToken": "secret",
+        });
+        let expected = serde_json::json!({"ticketId": 7, "externalId": "ext-7", "subject": "Printer"});
+        assert_eq!(reply_request_summary(&Value::String(payload.to_string())), expected);
+        assert_eq!(reply_request_summary(&payload), expected);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::directory::Directory;
+use crate::models::{source_name, Ticket};
+use crate::storage::Storage;
+
+/// 将指定工单导出为 CSV (lang 为 None 时导出原文)；
+/// 自定义字段各占一列，列名取同步下来的字段标签；用户 ID 按联系人缓存显示为姓名与邮箱
+pub fn export_to_csv(
+    storage: &Storage,
+    ticket_ids: &[u64],
+    lang: Option<&str>,
+    save_path: &str,
+) -> Result<usize, String> {
+    let mut tickets = Vec::new();
+    for &id in ticket_ids {
+        if let Some(ticket) = storage.load_ticket(id, lang)? {
+            tickets.push(ticket);
+        }
+    }
+
+    // 自定义字段列：先按字段定义的顺序，再补上定义中没有的字段
+    let schema = storage.ticket_field_schema();
+    let mut custom_columns: Vec<(String, String)> = schema
+        .custom_fields()
+        .map(|f| (f.name.clone(), f.label.clone()))
+        .collect();
+    let mut extra: Vec<&String> = tickets
+        .iter()
+        .flat_map(|t| t.custom_fields.keys())
+        .filter(|k| !custom_columns.iter().any(|(name, _)| name == *k))
+        .collect();
+    extra.sort();
+    extra.dedup();
+    custom_columns.extend(extra.into_iter().map(|k| (k.clone(), k.clone())));
+
+    let directory = Directory::load(storage);
+    let mut writer = csv::Writer::from_path(save_path).map_err(|e| e.to_string())?;
+
+    // Headers
+    let mut headers: Vec<String> = [
+        "ID",
+        "Subject",
+        "Status",
+        "Priority",
+        "Requester",
+        "Requester Email",
+        "Company",
+        "Agent",
+        "Type",
+        "Source",
+        "Tags",
+        "Group ID",
+        "Product ID",
+        "Company ID",
+        "Due By",
+        "FR Due By",
+        "Created At",
+        "Updated At",
+        "Description",
+        "Conversations",
+        "Attachments",
+    ]
+    .iter()
+    .map(|h| h.to_string())
+    .collect();
+    headers.extend(custom_columns.iter().map(|(_, label)| label.clone()));
+    writer.write_record(&headers).map_err(|e| e.to_string())?;
+
+    // Data
+    let mut exported = 0;
+    for ticket in tickets {
+        let mut conv_text = String::new();
+        for conv in &ticket.conversations {
+            let role = if conv.incoming { "Customer" } else { "Agent" };
+            let author = match conv.user_id.and_then(|id| directory.person(id)) {
+                Some(person) => format!("{} ({})", person.name, role),
+                None => role.to_string(),
+            };
+            conv_text.push_str(&format!(
+                "[{:?}] {}: {}\n",
+                conv.created_at,
+                author,
+                conv.body_text
+            ));
+        }
+        let requester = ticket.requester_id.and_then(|id| directory.person(id));
+        // 工单未带公司时取联系人所属公司
+        let company = ticket
+            .company_id
+            .and_then(|id| directory.company_name(id))
+            .or_else(|| requester.as_ref().and_then(|p| p.company.clone()));
+
+        let mut record = vec![
+            ticket.id.to_string(),
+            ticket.subject.clone().unwrap_or_default(),
+            ticket.status.to_string(),
+            ticket.priority.to_string(),
+            requester.as_ref().map(|p| p.name.clone()).unwrap_or_else(|| optional_id(ticket.requester_id)),
+            requester.and_then(|p| p.email).unwrap_or_default(),
+            company.unwrap_or_default(),
+            ticket.responder_id.map(|id| directory.display_name(Some(id))).unwrap_or_default(),
+            ticket.ticket_type.clone().unwrap_or_default(),
+            ticket.source.map(source_name).unwrap_or_default(),
+            ticket.tags.join(", "),
+            optional_id(ticket.group_id),
+            optional_id(ticket.product_id),
+            optional_id(ticket.company_id),
+            ticket.due_by.clone().unwrap_or_default(),
+            ticket.fr_due_by.clone().unwrap_or_default(),
+            ticket.created_at.clone().unwrap_or_default(),
+            ticket.updated_at.clone().unwrap_or_default(),
+            ticket.description_text.clone().unwrap_or_default(),
+            conv_text,
+            attachment_names(&ticket),
+        ];
+        record.extend(custom_columns.iter().map(|(name, _)| {
+            let value = ticket.custom_fields.get(name);
+            value.map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string())).unwrap_or_default()
+        }));
+
+        writer.write_record(&record).map_err(|e| e.to_string())?;
+        exported += 1;
+    }
+
+    writer.flush().map_err(|e| e.to_string())?;
+
+    Ok(exported)
+}
+
+fn optional_id(id: Option<u64>) -> String {
+    id.map(|id| id.to_string()).unwrap_or_default()
+}
+
+/// 工单及其会话的附件，每行一个 "名称 (大小)"
+fn attachment_names(ticket: &Ticket) -> String {
+    ticket
+        .attachments
+        .iter()
+        .chain(ticket.conversations.iter().flat_map(|c| c.attachments.iter()))
+        .map(|a| format!("{} ({} bytes)", a.name, a.size))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CustomFieldValue, FreshdeskStatus};
+    use crate::test_util::{temp_dir, ticket};
+
+    #[test]
+    fn custom_field_text_is_exported_without_json_quotes() {
+        let storage = Storage::new("memory:export-custom-fields");
+        let mut ticket = ticket(1, FreshdeskStatus::Open);
+        ticket.custom_fields.insert("cf_plan".to_string(), CustomFieldValue::Text("Gold, annual".to_string()));
+        ticket.custom_fields.insert("cf_note".to_string(), CustomFieldValue::Other(serde_json::json!("raw")));
+        ticket.custom_fields.insert("cf_seats".to_string(), CustomFieldValue::Integer(3));
+        ticket.custom_fields.insert("cf_vip".to_string(), CustomFieldValue::Null);
+        storage.save_ticket(&ticket, None).unwrap();
+
+        let dir = temp_dir("export");
+        let path = dir.join("tickets.csv");
+        assert_eq!(export_to_csv(&storage, &[1], None, &path.to_string_lossy()).unwrap(), 1);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].ends_with(",cf_note,cf_plan,cf_seats,cf_vip"), "{}", lines[0]);
+        assert!(lines[1].ends_with(",raw,\"Gold, annual\",3,"), "{}", lines[1]);
+    }
+}
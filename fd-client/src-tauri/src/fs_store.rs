@@ -0,0 +1,1008 @@
+use crate::lang;
+use crate::models::{FreshdeskStatus, Ticket, TicketFilter};
+use crate::store::TicketStore;
+use crate::ticket_index::TicketIndex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// 未完成写入的日志目录 (位于 tickets 目录下)
+const JOURNAL_DIR: &str = ".journal";
+/// 写入中的临时文件后缀 (不是 .json，列表时会被忽略)
+const TEMP_SUFFIX: &str = ".tmp";
+/// 隔离目录 (位于数据目录下)：修复时无法安全处理的文件按批次移入 `quarantine/{时间}/`
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// 按 (目录, 工单 ID) 分片的写锁：同一工单的写入 (同步、翻译消费者等) 串行执行
+static TICKET_LOCKS: Lazy<Vec<Mutex<()>>> = Lazy::new(|| (0..64).map(|_| Mutex::new(())).collect());
+
+/// `migrate_ticket_files` 的结果
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MigrationReport {
+    pub total: usize,
+    /// 按当前格式重写的文件数
+    pub rewritten: usize,
+    /// 因状态变化重命名的文件数
+    pub renamed: usize,
+    /// 无法解析的文件及原因
+    pub failed: Vec<String>,
+}
+
+/// `repair_journal` 的结果
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct RepairReport {
+    /// 发现的未完成操作数
+    pub pending: usize,
+    /// 新文件已写好、补删旧文件的操作数
+    pub completed: usize,
+    /// 新文件未写完、保留旧文件的操作数
+    pub rolled_back: usize,
+    /// 清理的残留临时文件数
+    pub temp_files_removed: usize,
+    pub failed: Vec<String>,
+}
+
+impl RepairReport {
+    pub fn is_empty(&self) -> bool {
+        self.pending == 0 && self.temp_files_removed == 0 && self.failed.is_empty()
+    }
+
+    /// 一行摘要 (启动日志)
+    pub fn summary(&self) -> String {
+        format!(
+            "Repaired interrupted writes: {} completed, {} rolled back, {} temp files removed, {} failed",
+            self.completed,
+            self.rolled_back,
+            self.temp_files_removed,
+            self.failed.len()
+        )
+    }
+}
+
+/// `check_data_dir` 发现的问题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// 译文没有对应的原文，或原文已损坏
+    OrphanTranslation,
+    /// 文件名中的状态与内容不符
+    StatusMismatch,
+    /// 同一工单 (同一语言) 有多个状态的文件；保留 updated_at 最新的一个
+    Duplicate,
+    /// 无法解析，或内容中的工单 ID 与文件名不符
+    Corrupt,
+    /// 文件名不是 `{id}_{status}[_{lang}].json`，列表时被忽略；修复时不处理
+    Unrecognized,
+}
+
+impl IssueKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::OrphanTranslation => "orphan_translation",
+            Self::StatusMismatch => "status_mismatch",
+            Self::Duplicate => "duplicate",
+            Self::Corrupt => "corrupt",
+            Self::Unrecognized => "unrecognized",
+        }
+    }
+}
+
+/// 修复时对问题文件的处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueAction {
+    /// 按内容中的状态重命名
+    Renamed,
+    /// 移入隔离目录
+    Quarantined,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityIssue {
+    pub kind: IssueKind,
+    pub path: String,
+    pub ticket_id: Option<u64>,
+    pub lang: Option<String>,
+    pub detail: String,
+    /// 修复模式下的处理结果；只检查、未处理或处理失败时为 None
+    pub action: Option<IssueAction>,
+}
+
+/// `check_data_dir` 的结果
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct IntegrityReport {
+    /// 检查的工单文件数
+    pub files: usize,
+    pub issues: Vec<IntegrityIssue>,
+    /// 本次修复隔离文件的目录
+    pub quarantine_dir: Option<String>,
+    /// 修复失败的文件及原因
+    pub failed: Vec<String>,
+}
+
+impl IntegrityReport {
+    /// 已修复的问题数
+    pub fn repaired(&self) -> usize {
+        self.issues.iter().filter(|i| i.action.is_some()).count()
+    }
+}
+
+/// 一次工单写入的日志：先记录再写新文件，旧文件在新文件就位后才删除
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalEntry {
+    ticket_id: u64,
+    lang: Option<String>,
+    /// 新文件名
+    target: String,
+    /// 新文件就位后要删除的旧文件名 (状态变化时文件名不同)
+    replaces: Vec<String>,
+    started_at: String,
+}
+
+/// 原子写入：写临时文件并 fsync，再改名覆盖目标；中断时目标文件保持原样
+pub fn atomic_write(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let file_name = path.file_name().and_then(|s| s.to_str()).ok_or("Invalid file path")?;
+    let tmp = path.with_file_name(format!("{}{}", file_name, TEMP_SUFFIX));
+    let result = (|| {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        sync_dir(path.parent().unwrap_or(Path::new(".")))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// 持久化目录项 (改名、删除)；Windows 上无法打开目录，跳过
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// 已解析的工单文件：(路径, 内容)
+type TicketFile = (PathBuf, Ticket);
+
+/// 文件存储：每张工单的原文与每种译文各一个 JSON 文件
+/// (`tickets/{id}_{status}[_{lang}].json`)，状态文件直接放在数据目录下
+pub struct FsStore {
+    data_dir: String,
+    /// 进程内共享的文件索引，读取单张工单时无需扫描目录
+    index: Arc<TicketIndex>,
+}
+
+impl FsStore {
+    pub fn new(data_dir: &str) -> Self {
+        let tickets_path = Path::new(data_dir).join("tickets");
+        fs::create_dir_all(&tickets_path).unwrap_or_default();
+        FsStore {
+            data_dir: data_dir.to_string(),
+            index: TicketIndex::shared(data_dir),
+        }
+    }
+
+    fn tickets_dir(&self) -> PathBuf {
+        Path::new(&self.data_dir).join("tickets")
+    }
+
+    /// 持有该工单的写锁
+    fn lock_ticket(&self, ticket_id: u64) -> MutexGuard<'static, ()> {
+        let mut hasher = DefaultHasher::new();
+        self.data_dir.trim_end_matches(['/', '\\']).hash(&mut hasher);
+        ticket_id.hash(&mut hasher);
+        let lock = &TICKET_LOCKS[(hasher.finish() % TICKET_LOCKS.len() as u64) as usize];
+        lock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn journal_path(&self, ticket_id: u64, lang: Option<&str>) -> PathBuf {
+        let name = match lang {
+            Some(l) => format!("{}_{}.json", ticket_id, l),
+            None => format!("{}.json", ticket_id),
+        };
+        self.tickets_dir().join(JOURNAL_DIR).join(name)
+    }
+
+    /// 检查日志中未完成的写入并修复：新文件已就位时补删旧文件，否则保留旧文件；
+    /// 同时清理残留的临时文件。应在启动时 (尚无写入前) 调用
+    pub fn repair_journal(&self) -> Result<RepairReport, String> {
+        let tickets_dir = self.tickets_dir();
+        let mut report = RepairReport::default();
+
+        if let Ok(entries) = fs::read_dir(tickets_dir.join(JOURNAL_DIR)) {
+            for path in entries.flatten().map(|e| e.path()) {
+                if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
+                report.pending += 1;
+                let entry: JournalEntry = match fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+                {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        // 日志本身没写完：写入还没开始，旧文件未动
+                        report.failed.push(format!("{}: {}", path.display(), e));
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                };
+
+                let _guard = self.lock_ticket(entry.ticket_id);
+                let target = tickets_dir.join(&entry.target);
+                if Self::read_ticket_file(&target).is_ok() {
+                    for old in &entry.replaces {
+                        let old = tickets_dir.join(old);
+                        if let Err(e) = fs::remove_file(&old) {
+                            if e.kind() != std::io::ErrorKind::NotFound {
+                                report.failed.push(format!("{}: {}", old.display(), e));
+                            }
+                        }
+                    }
+                    report.completed += 1;
+                } else {
+                    // 改名前中断：目标文件不存在或仍是旧内容，临时文件在下面清理
+                    report.rolled_back += 1;
+                }
+                fs::remove_file(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            }
+        }
+        if report.pending > 0 {
+            self.index.invalidate();
+        }
+
+        for dir in [tickets_dir.clone(), tickets_dir.join(JOURNAL_DIR), PathBuf::from(&self.data_dir)] {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|e| e.path()) {
+                let is_temp = path.file_name().and_then(|s| s.to_str()).is_some_and(|n| n.ends_with(TEMP_SUFFIX));
+                if is_temp && path.is_file() && fs::remove_file(&path).is_ok() {
+                    report.temp_files_removed += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Get filename for a ticket: {id}_{status}_{lang}.json or {id}_{status}.json
+    /// (语言为 BCP-47 代码，如 `12_open_zh-CN.json`)
+    fn ticket_filename(ticket: &Ticket, lang: Option<&str>) -> String {
+        match lang {
+            Some(l) => format!("{}_{}_{}.json", ticket.id, ticket.status.name(), lang::file_code(l)),
+            None => format!("{}_{}.json", ticket.id, ticket.status.name()),
+        }
+    }
+
+    /// 文件名 (不含扩展名) 中的语言：第二个 `_` 之后的全部内容，旧代码 (`cn`、`zh_CN`) 规范化
+    fn file_lang(stem: &str) -> Option<String> {
+        stem.splitn(3, '_').nth(2).map(lang::normalize_lenient)
+    }
+
+    /// 读取工单文件，兼容旧格式；文件名中的状态作为旧文件缺失 Freshdesk 状态时的回退
+    fn read_ticket_file(path: &Path) -> Result<Ticket, String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let fallback_status = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|stem| stem.split('_').nth(1))
+            .and_then(FreshdeskStatus::from_name);
+        Ticket::from_legacy_json(&content, fallback_status)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// 逐个读取 tickets 目录下的工单文件：(路径, 语言, 解析结果)
+    pub fn ticket_files(&self) -> Vec<(PathBuf, Option<String>, Result<Ticket, String>)> {
+        let tickets_dir = Path::new(&self.data_dir).join("tickets");
+        let Ok(entries) = fs::read_dir(&tickets_dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+            .collect();
+        files.sort();
+        files
+            .into_iter()
+            .map(|path| {
+                let lang = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(Self::file_lang);
+                let ticket = Self::read_ticket_file(&path);
+                (path, lang, ticket)
+            })
+            .collect()
+    }
+
+    /// 扫描目录得到某工单某语言除 `target` 外的文件名；只用于迁移与修复 (需要清理索引不收录的重复文件)
+    fn scan_replaced_files(&self, ticket_id: u64, lang: Option<&str>, target: &str) -> Vec<String> {
+        let prefix = format!("{}_", ticket_id);
+        let Ok(entries) = fs::read_dir(self.tickets_dir()) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|name| name.starts_with(&prefix) && name.ends_with(".json") && name != target)
+            .filter(|name| Self::file_lang(name.trim_end_matches(".json")).as_deref() == lang)
+            .collect()
+    }
+
+    /// 不经过索引查找某工单某语言的文件：按标准状态逐个尝试文件名，有多个时取最新修改的。
+    /// 不扫描目录；自定义状态的文件要等索引刷新后才能找到
+    fn find_ticket_file(&self, ticket_id: u64, lang: Option<&str>) -> Option<PathBuf> {
+        let tickets_dir = self.tickets_dir();
+        [FreshdeskStatus::Open, FreshdeskStatus::Pending, FreshdeskStatus::Resolved, FreshdeskStatus::Closed]
+            .into_iter()
+            .map(|status| {
+                let ticket = Ticket { id: ticket_id, status, ..Default::default() };
+                tickets_dir.join(Self::ticket_filename(&ticket, lang))
+            })
+            .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok(), path)))
+            .max_by_key(|(modified, _)| *modified)
+            .map(|(_, path)| path)
+    }
+
+    /// 写入工单文件 (调用方持有该工单的写锁)，替换索引中同语言的旧文件 (状态变化时文件名不同)
+    fn replace_ticket_file(&self, ticket_id: u64, lang: Option<&str>, target: &str, json: &str) -> Result<(), String> {
+        let replaces: Vec<String> = self
+            .index
+            .get(ticket_id, lang)
+            .and_then(|entry| entry.path.file_name()?.to_str().map(str::to_string))
+            .filter(|name| name != target)
+            .into_iter()
+            .collect();
+        self.write_ticket_file(ticket_id, lang, target, json, replaces)
+    }
+
+    /// 记录日志 → 原子写入新文件 → 删除 `replaces` 中的旧文件 → 删除日志。
+    /// 任一步中断都可由 `repair_journal` 修复
+    fn write_ticket_file(&self, ticket_id: u64, lang: Option<&str>, target: &str, json: &str, replaces: Vec<String>) -> Result<(), String> {
+        let tickets_dir = self.tickets_dir();
+        if replaces.is_empty() {
+            // 只是覆盖同名文件，原子改名即可，无需日志
+            atomic_write(&tickets_dir.join(target), json.as_bytes())?;
+            self.index.record(&tickets_dir.join(target));
+            return Ok(());
+        }
+
+        let entry = JournalEntry {
+            ticket_id,
+            lang: lang.map(str::to_string),
+            target: target.to_string(),
+            replaces,
+            started_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let journal = self.journal_path(ticket_id, lang);
+        fs::create_dir_all(journal.parent().ok_or("Invalid journal path")?).map_err(|e| e.to_string())?;
+        atomic_write(&journal, serde_json::to_string(&entry).map_err(|e| e.to_string())?.as_bytes())?;
+
+        atomic_write(&tickets_dir.join(target), json.as_bytes())?;
+        self.index.record(&tickets_dir.join(target));
+        for old in &entry.replaces {
+            let old = tickets_dir.join(old);
+            if let Err(e) = fs::remove_file(&old) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    return Err(format!("{}: {}", old.display(), e));
+                }
+            }
+            self.index.forget(&old);
+        }
+        sync_dir(&tickets_dir).map_err(|e| e.to_string())?;
+        fs::remove_file(&journal).map_err(|e| format!("{}: {}", journal.display(), e))
+    }
+
+    /// 重新读取所有工单文件 (含译文)：旧格式按当前格式重写，文件名与 Freshdesk 状态不符的重命名
+    pub fn migrate_ticket_files(&self) -> Result<MigrationReport, String> {
+        let tickets_dir = self.tickets_dir();
+        let mut report = MigrationReport::default();
+
+        let entries = match fs::read_dir(&tickets_dir) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(report),
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // 同一工单的重复文件可能已在前面被替换掉
+            if path.extension().and_then(|s| s.to_str()) != Some("json") || !path.exists() {
+                continue;
+            }
+            report.total += 1;
+
+            let current_filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("").to_string();
+            let lang = Self::file_lang(current_filename.trim_end_matches(".json"));
+
+            let ticket = match Self::read_ticket_file(&path) {
+                Ok(t) => t,
+                Err(e) => {
+                    report.failed.push(e);
+                    continue;
+                }
+            };
+
+            let json = serde_json::to_string_pretty(&ticket).map_err(|e| e.to_string())?;
+            let expected_filename = Self::ticket_filename(&ticket, lang.as_deref());
+            let content_changed = fs::read_to_string(&path).map(|c| c != json).unwrap_or(true);
+            let renamed = current_filename != expected_filename;
+
+            if content_changed || renamed {
+                let _guard = self.lock_ticket(ticket.id);
+                let replaces = self.scan_replaced_files(ticket.id, lang.as_deref(), &expected_filename);
+                if let Err(e) = self.write_ticket_file(ticket.id, lang.as_deref(), &expected_filename, &json, replaces) {
+                    report.failed.push(e);
+                    continue;
+                }
+                if renamed {
+                    report.renamed += 1;
+                }
+                if content_changed {
+                    report.rewritten += 1;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// 检查 tickets 目录：没有可解析原文的译文、文件名状态与内容不符、同一工单的重复文件、
+    /// 无法解析的文件。`repair` 时把状态不符的文件重命名，重复 (较旧的)、损坏与孤立的文件移入隔离目录
+    pub fn check_data_dir(&self, repair: bool) -> Result<IntegrityReport, String> {
+        let mut report = IntegrityReport::default();
+        // (工单 ID, 语言) → 可解析的文件
+        let mut groups: BTreeMap<(u64, Option<String>), Vec<TicketFile>> = BTreeMap::new();
+        // 原文文件损坏的工单；修复时原文被隔离，其译文也按孤立处理
+        let mut corrupt_originals: HashSet<u64> = HashSet::new();
+        let issue = |kind, path: &Path, ticket_id, lang: &Option<String>, detail: String| IntegrityIssue {
+            kind,
+            path: path.display().to_string(),
+            ticket_id,
+            lang: lang.clone(),
+            detail,
+            action: None,
+        };
+
+        for (path, lang, ticket) in self.ticket_files() {
+            report.files += 1;
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            let id = match stem.split_once('_') {
+                Some((id, _)) => id.parse::<u64>().ok(),
+                None => None,
+            };
+            let Some(id) = id else {
+                let detail = "File name is not {id}_{status}[_{lang}].json".to_string();
+                report.issues.push(issue(IssueKind::Unrecognized, &path, None, &lang, detail));
+                continue;
+            };
+            let detail = match ticket {
+                Ok(ticket) if ticket.id == id => {
+                    groups.entry((id, lang)).or_default().push((path, ticket));
+                    continue;
+                }
+                Ok(ticket) => format!("File contains ticket #{}", ticket.id),
+                Err(e) => {
+                    let prefix = format!("{}: ", path.display());
+                    e.strip_prefix(&prefix).unwrap_or(&e).to_string()
+                }
+            };
+            if lang.is_none() {
+                corrupt_originals.insert(id);
+            }
+            report.issues.push(issue(IssueKind::Corrupt, &path, Some(id), &lang, detail));
+        }
+        // 有可解析原文的工单
+        let originals: HashSet<u64> = groups.keys().filter(|(_, lang)| lang.is_none()).map(|(id, _)| *id).collect();
+
+        // 状态不符的文件在隔离完成后再重命名 (重命名会替换同语言的其他文件)
+        let mut renames: Vec<(usize, Ticket)> = Vec::new();
+        for ((id, lang), mut files) in groups {
+            let name_ok = |(path, ticket): &TicketFile| {
+                path.file_name().and_then(|s| s.to_str()) == Some(Self::ticket_filename(ticket, lang.as_deref()).as_str())
+            };
+            // 最新的在前；同样新时优先文件名正确的
+            files.sort_by(|a, b| b.1.updated_at.cmp(&a.1.updated_at).then_with(|| name_ok(b).cmp(&name_ok(a))));
+            let kept = files.remove(0);
+            let kept_name = kept.0.file_name().and_then(|s| s.to_str()).unwrap_or("").to_string();
+            for (path, ticket) in &files {
+                let detail = format!(
+                    "Duplicate of {} (updated_at {} vs {})",
+                    kept_name,
+                    ticket.updated_at.as_deref().unwrap_or("-"),
+                    kept.1.updated_at.as_deref().unwrap_or("-")
+                );
+                report.issues.push(issue(IssueKind::Duplicate, path, Some(id), &lang, detail));
+            }
+
+            if lang.is_some() && !originals.contains(&id) {
+                let detail = match corrupt_originals.contains(&id) {
+                    true => format!("Original file of ticket #{} is corrupt", id),
+                    false => format!("No original file for ticket #{}", id),
+                };
+                report.issues.push(issue(IssueKind::OrphanTranslation, &kept.0, Some(id), &lang, detail));
+            } else if !name_ok(&kept) {
+                let expected = Self::ticket_filename(&kept.1, lang.as_deref());
+                let detail = format!("Ticket status is {}, expected {}", kept.1.status.name(), expected);
+                report.issues.push(issue(IssueKind::StatusMismatch, &kept.0, Some(id), &lang, detail));
+                renames.push((report.issues.len() - 1, kept.1));
+            }
+        }
+
+        if !repair {
+            return Ok(report);
+        }
+
+        let batch = Path::new(&self.data_dir)
+            .join(QUARANTINE_DIR)
+            .join(chrono::Local::now().format("%Y%m%d-%H%M%S").to_string());
+        // 损坏的原文未能隔离时保留其译文 (损坏的问题排在孤立译文之前)
+        let mut unmoved_originals: HashSet<u64> = HashSet::new();
+        // 有文件未能隔离的 (工单 ID, 语言)；重命名会替换同语言的其他文件，这些工单不重命名
+        let mut unmoved: HashSet<(u64, Option<String>)> = HashSet::new();
+        for issue in report.issues.iter_mut() {
+            if matches!(issue.kind, IssueKind::Unrecognized | IssueKind::StatusMismatch) {
+                continue;
+            }
+            if issue.kind == IssueKind::OrphanTranslation
+                && issue.ticket_id.is_some_and(|id| unmoved_originals.contains(&id))
+            {
+                continue;
+            }
+            let path = PathBuf::from(&issue.path);
+            let _guard = issue.ticket_id.map(|id| self.lock_ticket(id));
+            let moved = fs::create_dir_all(&batch)
+                .and_then(|_| fs::rename(&path, batch.join(path.file_name().unwrap_or_default())));
+            match moved {
+                Ok(()) => {
+                    issue.action = Some(IssueAction::Quarantined);
+                    report.quarantine_dir = Some(batch.display().to_string());
+                }
+                Err(e) => {
+                    if issue.kind == IssueKind::Corrupt && issue.lang.is_none() {
+                        unmoved_originals.extend(issue.ticket_id);
+                    }
+                    if let Some(id) = issue.ticket_id {
+                        unmoved.insert((id, issue.lang.clone()));
+                    }
+                    report.failed.push(format!("{}: {}", issue.path, e));
+                }
+            }
+        }
+        for (index, ticket) in renames {
+            let issue = &mut report.issues[index];
+            let lang = issue.lang.clone();
+            if unmoved.contains(&(ticket.id, lang.clone())) {
+                report.failed.push(format!("{}: not renamed, other files of the ticket could not be quarantined", issue.path));
+                continue;
+            }
+            let result = serde_json::to_string_pretty(&ticket).map_err(|e| e.to_string()).and_then(|json| {
+                let _guard = self.lock_ticket(ticket.id);
+                let target = Self::ticket_filename(&ticket, lang.as_deref());
+                let replaces = self.scan_replaced_files(ticket.id, lang.as_deref(), &target);
+                self.write_ticket_file(ticket.id, lang.as_deref(), &target, &json, replaces)
+            });
+            match result {
+                Ok(()) => issue.action = Some(IssueAction::Renamed),
+                Err(e) => report.failed.push(e),
+            }
+        }
+        if report.quarantine_dir.is_some() {
+            sync_dir(&self.tickets_dir()).map_err(|e| e.to_string())?;
+            self.index.invalidate();
+        }
+        Ok(report)
+    }
+}
+
+impl TicketStore for FsStore {
+    /// Save ticket with status and optional language in filename
+    fn save_ticket(&self, ticket: &Ticket, lang: Option<&str>) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(ticket).map_err(|e| e.to_string())?;
+        let _guard = self.lock_ticket(ticket.id);
+        // 新文件就位后才删除同语言的旧文件 (不同状态)
+        self.replace_ticket_file(ticket.id, lang, &Self::ticket_filename(ticket, lang), &json)
+    }
+
+    /// Load a specific ticket by ID and language (路径来自文件索引，索引未收录时查找磁盘)
+    fn load_ticket(
+        &self,
+        ticket_id: u64,
+        lang: Option<&str>,
+    ) -> Result<Option<Ticket>, String> {
+        if let Some(entry) = self.index.get(ticket_id, lang) {
+            match Self::read_ticket_file(&entry.path) {
+                Ok(ticket) => return Ok(Some(ticket)),
+                // 文件已被外部改名或删除，索引尚未刷新
+                Err(_) if !entry.path.exists() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        // 索引中没有 (外部新建或改名) 时直接查找磁盘；索引留给 `refresh` 更新，以便发出变化事件
+        match self.find_ticket_file(ticket_id, lang) {
+            Some(path) => Self::read_ticket_file(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 文件来自索引，只读取所需语言的文件；无法读取的文件跳过
+    fn list_tickets(&self, preferred_lang: Option<&str>) -> Result<Vec<Ticket>, String> {
+        let langs = self.index.all_translation_langs();
+        let mut tickets: Vec<Ticket> = self
+            .index
+            .files(preferred_lang)
+            .into_iter()
+            .filter_map(|(id, entry)| {
+                let mut ticket = Self::read_ticket_file(&entry.path).ok()?;
+                ticket.available_langs = langs.get(&id).cloned().unwrap_or_default();
+                Some(ticket)
+            })
+            .collect();
+        tickets.sort_by_key(|t| std::cmp::Reverse(t.id));
+        Ok(tickets)
+    }
+
+    /// 文件来自索引，头部按文件缓存 (文件未变化时不再解析)；无法读取的文件同 `list_tickets` 一样跳过
+    fn list_headers(&self, preferred_lang: Option<&str>, _: &TicketFilter) -> Result<Vec<Ticket>, String> {
+        let langs = self.index.all_translation_langs();
+        let mut tickets: Vec<Ticket> = self
+            .index
+            .files(preferred_lang)
+            .into_iter()
+            .filter_map(|(id, entry)| {
+                let mut ticket = self.index.header(&entry, Self::read_ticket_file).ok()?;
+                ticket.available_langs = langs.get(&id).cloned().unwrap_or_default();
+                Some(ticket)
+            })
+            .collect();
+        tickets.sort_by_key(|t| std::cmp::Reverse(t.id));
+        Ok(tickets)
+    }
+
+    fn delete_ticket(&self, ticket_id: u64) -> Result<bool, String> {
+        let _guard = self.lock_ticket(ticket_id);
+        let paths = self.index.paths(ticket_id);
+        for (_, path) in &paths {
+            fs::remove_file(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            self.index.forget(path);
+        }
+        Ok(!paths.is_empty())
+    }
+
+    fn translation_langs(&self, ticket_id: u64) -> Result<Vec<String>, String> {
+        Ok(self.index.translation_langs(ticket_id))
+    }
+
+    fn delete_translation(&self, ticket_id: u64, lang: &str) -> Result<bool, String> {
+        let _guard = self.lock_ticket(ticket_id);
+        let Some(entry) = self.index.get(ticket_id, Some(lang)) else {
+            return Ok(false);
+        };
+        fs::remove_file(&entry.path).map_err(|e| format!("{}: {}", entry.path.display(), e))?;
+        self.index.forget(&entry.path);
+        Ok(true)
+    }
+
+    fn read_state(&self, name: &str) -> Option<String> {
+        fs::read_to_string(Path::new(&self.data_dir).join(name)).ok()
+    }
+
+    fn write_state(&self, name: &str, json: &str) -> Result<(), String> {
+        atomic_write(&Path::new(&self.data_dir).join(name), json.as_bytes())
+    }
+
+    /// 删除状态文件，返回文件是否存在
+    fn remove_state(&self, name: &str) -> Result<bool, String> {
+        let path = Path::new(&self.data_dir).join(name);
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WorkflowStatus;
+    use crate::test_util::{temp_dir, ticket};
+
+    /// 每个测试独立的空数据目录
+    fn data_dir(name: &str) -> PathBuf {
+        let dir = temp_dir(&format!("fs-store-{}", name));
+        fs::create_dir_all(dir.join("tickets").join(JOURNAL_DIR)).unwrap();
+        dir
+    }
+
+    fn write_ticket(dir: &Path, name: &str, ticket: &Ticket) {
+        fs::write(dir.join("tickets").join(name), serde_json::to_string(ticket).unwrap()).unwrap();
+    }
+
+    fn write_journal(dir: &Path, ticket_id: u64, target: &str, replaces: &[&str]) {
+        let entry = JournalEntry {
+            ticket_id,
+            lang: None,
+            target: target.to_string(),
+            replaces: replaces.iter().map(|s| s.to_string()).collect(),
+            started_at: String::new(),
+        };
+        let path = dir.join("tickets").join(JOURNAL_DIR).join(format!("{}.json", ticket_id));
+        fs::write(path, serde_json::to_string(&entry).unwrap()).unwrap();
+    }
+
+    fn ticket_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir.join("tickets"))
+            .unwrap()
+            .flatten()
+            .filter(|e| e.path().is_file())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn repair_completes_writes_whose_new_file_is_in_place() {
+        let dir = data_dir("repair-complete");
+        write_ticket(&dir, "1_open.json", &ticket(1, FreshdeskStatus::Open));
+        write_ticket(&dir, "1_closed.json", &ticket(1, FreshdeskStatus::Closed));
+        write_journal(&dir, 1, "1_closed.json", &["1_open.json"]);
+
+        let report = FsStore::new(&dir.to_string_lossy()).repair_journal().unwrap();
+        assert_eq!((report.pending, report.completed, report.rolled_back), (1, 1, 0));
+        assert_eq!(ticket_files(&dir), ["1_closed.json"]);
+        assert!(fs::read_dir(dir.join("tickets").join(JOURNAL_DIR)).unwrap().next().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn repair_rolls_back_writes_interrupted_before_the_rename() {
+        let dir = data_dir("repair-rollback");
+        write_ticket(&dir, "2_open.json", &ticket(2, FreshdeskStatus::Open));
+        fs::write(dir.join("tickets").join(format!("2_pending.json{}", TEMP_SUFFIX)), "{\"id\": 2").unwrap();
+        write_journal(&dir, 2, "2_pending.json", &["2_open.json"]);
+
+        let store = FsStore::new(&dir.to_string_lossy());
+        let report = store.repair_journal().unwrap();
+        assert_eq!((report.pending, report.completed, report.rolled_back), (1, 0, 1));
+        assert_eq!(report.temp_files_removed, 1);
+        assert_eq!(ticket_files(&dir), ["2_open.json"]);
+        assert_eq!(store.load_ticket(2, None).unwrap().unwrap().status, FreshdeskStatus::Open);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn repair_discards_unreadable_journal_entries() {
+        let dir = data_dir("repair-corrupt");
+        write_ticket(&dir, "3_open.json", &ticket(3, FreshdeskStatus::Open));
+        fs::write(dir.join("tickets").join(JOURNAL_DIR).join("3.json"), "{\"ticketId\": 3, \"tar").unwrap();
+
+        let report = FsStore::new(&dir.to_string_lossy()).repair_journal().unwrap();
+        assert_eq!(report.pending, 1);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(ticket_files(&dir), ["3_open.json"]);
+        assert!(!dir.join("tickets").join(JOURNAL_DIR).join("3.json").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn status_change_replaces_the_old_file_without_leaving_a_journal() {
+        let dir = data_dir("save-rename");
+        let store = FsStore::new(&dir.to_string_lossy());
+        store.save_ticket(&ticket(4, FreshdeskStatus::Open), None).unwrap();
+        store.save_ticket(&ticket(4, FreshdeskStatus::Resolved), None).unwrap();
+
+        assert_eq!(ticket_files(&dir), ["4_resolved.json"]);
+        assert!(store.repair_journal().unwrap().is_empty());
+        assert_eq!(store.load_ticket(4, None).unwrap().unwrap().status, FreshdeskStatus::Resolved);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// 各类问题各一例的 tickets 目录
+    fn write_inconsistent_tickets(dir: &Path) {
+        let updated = |mut t: Ticket, at: &str| {
+            t.updated_at = Some(at.to_string());
+            t
+        };
+        write_ticket(dir, "10_open.json", &ticket(10, FreshdeskStatus::Open));
+        write_ticket(dir, "10_open_zh-CN.json", &ticket(10, FreshdeskStatus::Open));
+        write_ticket(dir, "11_open_de.json", &ticket(11, FreshdeskStatus::Open));
+        write_ticket(dir, "12_closed.json", &ticket(12, FreshdeskStatus::Open));
+        write_ticket(dir, "13_open.json", &updated(ticket(13, FreshdeskStatus::Open), "2024-01-01T00:00:00Z"));
+        write_ticket(dir, "13_pending.json", &updated(ticket(13, FreshdeskStatus::Pending), "2024-02-01T00:00:00Z"));
+        fs::write(dir.join("tickets").join("14_open.json"), "{\"id\": 14,").unwrap();
+        write_ticket(dir, "14_open_ja.json", &ticket(14, FreshdeskStatus::Open));
+        write_ticket(dir, "15_open.json", &ticket(99, FreshdeskStatus::Open));
+        fs::write(dir.join("tickets").join("notes.json"), "{}").unwrap();
+    }
+
+    fn issues(report: &IntegrityReport) -> Vec<(IssueKind, String, Option<IssueAction>)> {
+        let mut issues: Vec<_> = report
+            .issues
+            .iter()
+            .map(|i| {
+                let name = Path::new(&i.path).file_name().unwrap().to_string_lossy().into_owned();
+                (i.kind, name, i.action)
+            })
+            .collect();
+        issues.sort_by(|a, b| a.1.cmp(&b.1));
+        issues
+    }
+
+    #[test]
+    fn check_classifies_issues_without_touching_files() {
+        let dir = data_dir("check");
+        write_inconsistent_tickets(&dir);
+        let before = ticket_files(&dir);
+
+        let report = FsStore::new(&dir.to_string_lossy()).check_data_dir(false).unwrap();
+        assert_eq!(report.files, 10);
+        assert_eq!(
+            issues(&report),
+            vec![
+                (IssueKind::OrphanTranslation, "11_open_de.json".to_string(), None),
+                (IssueKind::StatusMismatch, "12_closed.json".to_string(), None),
+                (IssueKind::Duplicate, "13_open.json".to_string(), None),
+                (IssueKind::Corrupt, "14_open.json".to_string(), None),
+                (IssueKind::OrphanTranslation, "14_open_ja.json".to_string(), None),
+                (IssueKind::Corrupt, "15_open.json".to_string(), None),
+                (IssueKind::Unrecognized, "notes.json".to_string(), None),
+            ]
+        );
+        let orphan = report.issues.iter().find(|i| i.path.ends_with("14_open_ja.json")).unwrap();
+        assert_eq!(orphan.detail, "Original file of ticket #14 is corrupt");
+        assert_eq!(report.repaired(), 0);
+        assert!(report.quarantine_dir.is_none());
+        assert_eq!(ticket_files(&dir), before);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn repair_quarantines_corrupt_originals_with_their_translations() {
+        let dir = data_dir("check-repair");
+        write_inconsistent_tickets(&dir);
+        let store = FsStore::new(&dir.to_string_lossy());
+
+        let report = store.check_data_dir(true).unwrap();
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+        assert_eq!(report.repaired(), 6);
+        let quarantined: Vec<String> = issues(&report)
+            .into_iter()
+            .filter(|(_, _, action)| *action == Some(IssueAction::Quarantined))
+            .map(|(_, name, _)| name)
+            .collect();
+        assert_eq!(quarantined, ["11_open_de.json", "13_open.json", "14_open.json", "14_open_ja.json", "15_open.json"]);
+        let mut moved: Vec<String> = fs::read_dir(report.quarantine_dir.as_ref().unwrap())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        moved.sort();
+        assert_eq!(moved, quarantined);
+        assert_eq!(ticket_files(&dir), ["10_open.json", "10_open_zh-CN.json", "12_open.json", "13_pending.json", "notes.json"]);
+        assert_eq!(store.load_ticket(12, None).unwrap().unwrap().status, FreshdeskStatus::Open);
+
+        // 一次修复后只剩无法处理的文件
+        let again = store.check_data_dir(true).unwrap();
+        assert_eq!(issues(&again), vec![(IssueKind::Unrecognized, "notes.json".to_string(), None)]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failed_quarantine_keeps_duplicates_from_being_replaced_by_renames() {
+        let dir = data_dir("check-quarantine-fails");
+        let updated = |mut t: Ticket, at: &str| {
+            t.updated_at = Some(at.to_string());
+            t
+        };
+        write_ticket(&dir, "17_pending.json", &updated(ticket(17, FreshdeskStatus::Open), "2024-02-01T00:00:00Z"));
+        write_ticket(&dir, "17_resolved.json", &updated(ticket(17, FreshdeskStatus::Resolved), "2024-01-01T00:00:00Z"));
+        write_ticket(&dir, "18_closed.json", &ticket(18, FreshdeskStatus::Open));
+        // 隔离目录的位置被文件占用，无法创建
+        fs::write(dir.join(QUARANTINE_DIR), "").unwrap();
+        let store = FsStore::new(&dir.to_string_lossy());
+
+        let report = store.check_data_dir(true).unwrap();
+        assert_eq!(report.failed.len(), 2, "{:?}", report.failed);
+        assert!(report.failed[1].contains("17_pending.json: not renamed"));
+        assert_eq!(
+            issues(&report),
+            vec![
+                (IssueKind::StatusMismatch, "17_pending.json".to_string(), None),
+                (IssueKind::Duplicate, "17_resolved.json".to_string(), None),
+                (IssueKind::StatusMismatch, "18_closed.json".to_string(), Some(IssueAction::Renamed)),
+            ]
+        );
+        assert_eq!(ticket_files(&dir), ["17_pending.json", "17_resolved.json", "18_open.json"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn load_finds_files_the_index_has_not_seen_yet() {
+        let dir = data_dir("load-unindexed");
+        write_ticket(&dir, "20_open.json", &ticket(20, FreshdeskStatus::Open));
+        let store = FsStore::new(&dir.to_string_lossy());
+        assert!(store.load_ticket(21, None).unwrap().is_none());
+
+        // 其他进程新建、改名的文件
+        write_ticket(&dir, "21_pending.json", &ticket(21, FreshdeskStatus::Pending));
+        write_ticket(&dir, "21_pending_de.json", &ticket(21, FreshdeskStatus::Pending));
+        fs::rename(dir.join("tickets").join("20_open.json"), dir.join("tickets").join("20_closed.json")).unwrap();
+        assert_eq!(store.load_ticket(21, None).unwrap().unwrap().status, FreshdeskStatus::Pending);
+        assert!(store.load_ticket(21, Some("de")).unwrap().is_some());
+        assert!(store.load_ticket(21, Some("fr")).unwrap().is_none());
+        assert_eq!(store.load_ticket(20, None).unwrap().unwrap().id, 20);
+
+        // 索引留给 refresh 更新，变化照常报告
+        assert_eq!(TicketIndex::shared(&dir.to_string_lossy()).refresh(), [20, 21]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn list_and_delete_resolve_files_through_the_index() {
+        let dir = data_dir("index-paths");
+        write_ticket(&dir, "30_open.json", &ticket(30, FreshdeskStatus::Open));
+        write_ticket(&dir, "30_open_de.json", &ticket(30, FreshdeskStatus::Open));
+        write_ticket(&dir, "31_closed.json", &ticket(31, FreshdeskStatus::Closed));
+        let store = FsStore::new(&dir.to_string_lossy());
+
+        let listed = store.list_tickets(None).unwrap();
+        assert_eq!(listed.iter().map(|t| t.id).collect::<Vec<_>>(), [31, 30]);
+        assert_eq!(listed[1].available_langs, ["de"]);
+        assert_eq!(store.list_tickets(Some("de")).unwrap().len(), 1);
+
+        assert!(store.delete_translation(30, "de").unwrap());
+        assert!(!store.delete_translation(30, "de").unwrap());
+        assert!(store.delete_ticket(31).unwrap());
+        assert_eq!(ticket_files(&dir), ["30_open.json"]);
+        assert_eq!(store.list_tickets(None).unwrap()[0].available_langs, Vec::<String>::new());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn migration_rewrites_legacy_camel_case_files() {
+        let dir = data_dir("migrate-legacy");
+        let legacy = |id: u64| {
+            format!(
+                r#"{{"id": {id}, "externalId": "12162", "subject": "亚马逊订单", "descriptionText": null,
+                    "status": "PENDING_TRANS", "priority": 0, "createdAt": "2026-01-30T13:05:41", "updatedAt": null}}"#
+            )
+        };
+        fs::write(dir.join("tickets").join("125_pending_cn.json"), legacy(125)).unwrap();
+        fs::write(dir.join("tickets").join("126_open.json"), legacy(126)).unwrap();
+
+        let parsed = Ticket::from_legacy_json(&legacy(125), Some(FreshdeskStatus::Pending)).unwrap();
+        assert_eq!(parsed.status, FreshdeskStatus::Pending);
+        assert_eq!(parsed.workflow_status, Some(WorkflowStatus::PendingTrans));
+        assert_eq!(parsed.external_id.as_deref(), Some("12162"));
+        assert_eq!(parsed.created_at.as_deref(), Some("2026-01-30T13:05:41"));
+
+        let store = FsStore::new(&dir.to_string_lossy());
+        let report = store.migrate_ticket_files().unwrap();
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+        assert_eq!((report.total, report.rewritten, report.renamed), (2, 2, 1));
+        assert_eq!(ticket_files(&dir), ["125_pending_zh-CN.json", "126_open.json"]);
+
+        // 重写后是当前格式：snake_case、数字状态、流转状态单独一个字段
+        let rewritten: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("tickets").join("125_pending_zh-CN.json")).unwrap()).unwrap();
+        assert_eq!(rewritten["status"], 3);
+        assert_eq!(rewritten["workflow_status"], "PENDING_TRANS");
+        assert_eq!(rewritten["external_id"], "12162");
+        assert!(rewritten.get("externalId").is_none());
+        let original = store.load_ticket(126, None).unwrap().unwrap();
+        assert_eq!((original.status, original.workflow_status), (FreshdeskStatus::Open, Some(WorkflowStatus::PendingTrans)));
+        assert_eq!(store.migrate_ticket_files().unwrap().rewritten, 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,458 @@
+use crate::models::{FreshdeskStatus, Ticket};
+use crate::settings::Settings;
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// 工单版本库 (位于数据目录)
+pub const HISTORY_DB_FILE: &str = "history.db";
+
+/// 进程内每个数据目录 (或内存存储) 一个版本库
+static STORES: Lazy<Mutex<HashMap<String, Arc<HistoryStore>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS versions (
+    id INTEGER PRIMARY KEY,
+    ticket_id INTEGER NOT NULL,
+    lang TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    saved_at TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    updated_at TEXT,
+    conversations INTEGER NOT NULL,
+    changes TEXT NOT NULL,
+    data TEXT NOT NULL,
+    UNIQUE (ticket_id, lang, version)
+);
+CREATE INDEX IF NOT EXISTS idx_versions_saved_at ON versions(saved_at);
+";
+
+/// 版本保留策略；每张工单 (每种语言) 的最新版本始终保留
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HistoryRetention {
+    /// 每张工单最多保留的版本数，0 表示不限
+    pub max_versions: u32,
+    /// 超过天数的旧版本被删除，0 表示不限
+    pub max_days: u32,
+}
+
+impl HistoryRetention {
+    pub fn from_settings(settings: &Settings) -> Self {
+        HistoryRetention {
+            max_versions: settings.history_max_versions,
+            max_days: settings.history_max_days,
+        }
+    }
+}
+
+/// 时间线中的一个版本 (不含内容)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketVersion {
+    pub version: u32,
+    pub saved_at: String,
+    pub status: FreshdeskStatus,
+    /// Freshdesk 的 updated_at
+    pub updated_at: Option<String>,
+    pub conversations: usize,
+    /// 相对上一版本变化的字段 (首个版本为空)
+    pub changes: Vec<String>,
+}
+
+/// 一个字段的变化；新增的字段 before 为 None，删除的 after 为 None。
+/// 嵌套字段用 `.` 连接，按 ID 比较的列表元素记为 `conversations[123]`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// 工单版本历史：每次内容有变化的保存记录一个完整快照
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryStore {
+    /// 打开 (必要时创建) 数据目录下的 history.db
+    pub fn open(data_dir: &str) -> Result<Self, String> {
+        let path = Path::new(data_dir).join(HISTORY_DB_FILE);
+        let conn = Connection::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::init(conn).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// 不落盘的版本库 (内存存储使用)
+    pub fn open_in_memory() -> Result<Self, String> {
+        let conn = Connection::open_in_memory().map_err(|e| e.to_string())?;
+        Self::init(conn).map_err(|e| e.to_string())
+    }
+
+    /// 数据目录对应的版本库 (进程内共享，只打开并迁移一次)；`in_memory` 时 `data_dir` 仅作名称。
+    /// 打开失败不缓存，下次调用重试
+    pub fn shared(data_dir: &str, in_memory: bool) -> Result<Arc<Self>, String> {
+        let key = data_dir.trim_end_matches(['/', '\\']).to_string();
+        let mut stores = STORES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(store) = stores.get(&key) {
+            return Ok(store.clone());
+        }
+        let store = Arc::new(match in_memory {
+            true => Self::open_in_memory()?,
+            false => Self::open(&key)?,
+        });
+        stores.insert(key, store.clone());
+        Ok(store)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch("PRAGMA journal_mode = WAL;")?;
+        conn.execute_batch(SCHEMA)?;
+        // 旧版本记录的是 "cn" 等别名
+        let langs: Vec<String> = conn
+            .prepare("SELECT DISTINCT lang FROM versions WHERE lang != ''")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        for lang in langs {
+            let code = crate::lang::normalize_lenient(&lang);
+            if code != lang {
+                conn.execute("UPDATE OR IGNORE versions SET lang = ?1 WHERE lang = ?2", params![code, lang])?;
+            }
+        }
+        Ok(HistoryStore { conn: Mutex::new(conn) })
+    }
+
+    /// 内容与最新版本不同时记录新版本，返回新版本号
+    pub fn record(&self, ticket: &Ticket, lang: Option<&str>) -> Result<Option<u32>, String> {
+        let snapshot = snapshot(ticket);
+        let data = serde_json::to_string(&snapshot).map_err(|e| e.to_string())?;
+        let lang = lang_key(lang);
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let latest: Option<(u32, String)> = tx
+            .query_row(
+                "SELECT version, data FROM versions WHERE ticket_id = ?1 AND lang = ?2 ORDER BY version DESC LIMIT 1",
+                params![ticket.id as i64, lang],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        let (version, changes) = match latest {
+            Some((_, previous)) if previous == data => return Ok(None),
+            Some((version, previous)) => {
+                let previous: Ticket = parse_snapshot(&previous)?;
+                let fields: BTreeSet<String> = diff_tickets(&previous, &snapshot)
+                    .into_iter()
+                    .map(|c| top_level(&c.field).to_string())
+                    .collect();
+                // 旧版本的快照可能还带着附件下载地址
+                if fields.is_empty() {
+                    return Ok(None);
+                }
+                (version + 1, fields.into_iter().collect::<Vec<_>>())
+            }
+            None => (1, Vec::new()),
+        };
+
+        tx.execute(
+            "INSERT INTO versions (ticket_id, lang, version, saved_at, status, updated_at, conversations, changes, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                ticket.id as i64,
+                lang,
+                version,
+                chrono::Utc::now().to_rfc3339(),
+                ticket.status.code(),
+                ticket.updated_at,
+                ticket.conversations.len() as i64,
+                serde_json::to_string(&changes).map_err(|e| e.to_string())?,
+                data,
+            ],
+        )
+        .map_err(|e| format!("#{}: {}", ticket.id, e))?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(Some(version))
+    }
+
+    /// 工单 (或某个译文) 的全部版本，按版本号升序
+    pub fn timeline(&self, ticket_id: u64, lang: Option<&str>) -> Result<Vec<TicketVersion>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT version, saved_at, status, updated_at, conversations, changes FROM versions
+                 WHERE ticket_id = ?1 AND lang = ?2 ORDER BY version",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![ticket_id as i64, lang_key(lang)], |row| {
+                let changes: String = row.get(5)?;
+                Ok(TicketVersion {
+                    version: row.get(0)?,
+                    saved_at: row.get(1)?,
+                    status: FreshdeskStatus::from_code(row.get(2)?),
+                    updated_at: row.get(3)?,
+                    conversations: row.get::<_, i64>(4)? as usize,
+                    changes: serde_json::from_str(&changes).unwrap_or_default(),
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    /// 某个版本的完整内容
+    pub fn version(&self, ticket_id: u64, lang: Option<&str>, version: u32) -> Result<Option<Ticket>, String> {
+        let conn = self.conn.lock().unwrap();
+        let data: Option<String> = conn
+            .query_row(
+                "SELECT data FROM versions WHERE ticket_id = ?1 AND lang = ?2 AND version = ?3",
+                params![ticket_id as i64, lang_key(lang), version],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        data.as_deref().map(parse_snapshot).transpose()
+    }
+
+    /// 两个版本之间的字段级差异 (from → to)
+    pub fn diff(&self, ticket_id: u64, lang: Option<&str>, from: u32, to: u32) -> Result<Vec<FieldChange>, String> {
+        let load = |version| {
+            self.version(ticket_id, lang, version)?
+                .ok_or_else(|| format!("Ticket #{} has no version {}", ticket_id, version))
+        };
+        Ok(diff_tickets(&load(from)?, &load(to)?))
+    }
+
+    /// 按保留策略删除旧版本，返回删除的版本数
+    pub fn prune(&self, retention: &HistoryRetention) -> Result<usize, String> {
+        if retention.max_versions == 0 && retention.max_days == 0 {
+            return Ok(0);
+        }
+        let cutoff = (retention.max_days > 0)
+            .then(|| (chrono::Utc::now() - chrono::Duration::days(retention.max_days as i64)).to_rfc3339());
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn
+            .execute(
+                "DELETE FROM versions WHERE id IN (
+                    SELECT id FROM (
+                        SELECT id, saved_at,
+                               ROW_NUMBER() OVER (PARTITION BY ticket_id, lang ORDER BY version DESC) AS rn
+                        FROM versions
+                    )
+                    WHERE rn > 1 AND ((?1 > 0 AND rn > ?1) OR (?2 IS NOT NULL AND saved_at < ?2))
+                )",
+                params![retention.max_versions, cutoff],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(deleted)
+    }
+}
+
+/// 原文为空字符串，译文为规范化的语言代码
+fn lang_key(lang: Option<&str>) -> String {
+    lang.map(crate::lang::normalize_lenient).unwrap_or_default()
+}
+
+fn parse_snapshot(data: &str) -> Result<Ticket, String> {
+    serde_json::from_str(data).map_err(|e| format!("Corrupt ticket version: {}", e))
+}
+
+/// `conversations[1].body_text` → `conversations`
+fn top_level(field: &str) -> &str {
+    field.split(['.', '[']).next().unwrap_or(field)
+}
+
+/// 记录与比较用的工单内容：去掉派生的 available_langs 与附件下载地址
+/// (带过期时间的签名地址，每次同步都不同，不算内容变化)
+fn snapshot(ticket: &Ticket) -> Ticket {
+    let mut ticket = ticket.clone();
+    ticket.available_langs.clear();
+    let conversation_attachments = ticket.conversations.iter_mut().flat_map(|c| c.attachments.iter_mut());
+    for attachment in ticket.attachments.iter_mut().chain(conversation_attachments) {
+        attachment.attachment_url = None;
+    }
+    ticket
+}
+
+/// 两个工单的字段级差异
+pub fn diff_tickets(before: &Ticket, after: &Ticket) -> Vec<FieldChange> {
+    let to_value = |t: &Ticket| serde_json::to_value(snapshot(t)).unwrap_or(Value::Null);
+    let mut changes = Vec::new();
+    diff_values("", &to_value(before), &to_value(after), &mut changes);
+    changes
+}
+
+fn diff_values(path: &str, before: &Value, after: &Value, changes: &mut Vec<FieldChange>) {
+    if before == after {
+        return;
+    }
+    let join = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+    match (before, after) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff_values(&join(key), x, y, changes),
+                    (x, y) => changes.push(FieldChange {
+                        field: join(key),
+                        before: x.cloned(),
+                        after: y.cloned(),
+                    }),
+                }
+            }
+        }
+        // 带 id 的对象列表 (会话、附件) 按 id 对齐比较
+        (Value::Array(a), Value::Array(b)) if keyed_by_id(a) && keyed_by_id(b) => {
+            let id_of = |v: &Value| v.get("id").cloned().unwrap_or(Value::Null);
+            let mut ids: Vec<Value> = Vec::new();
+            for v in a.iter().chain(b.iter()) {
+                let id = id_of(v);
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            for id in ids {
+                let field = format!("{}[{}]", path, id);
+                match (a.iter().find(|v| id_of(v) == id), b.iter().find(|v| id_of(v) == id)) {
+                    (Some(x), Some(y)) => diff_values(&field, x, y, changes),
+                    (x, y) => changes.push(FieldChange {
+                        field,
+                        before: x.cloned(),
+                        after: y.cloned(),
+                    }),
+                }
+            }
+        }
+        _ => changes.push(FieldChange {
+            field: path.to_string(),
+            before: Some(before.clone()),
+            after: Some(after.clone()),
+        }),
+    }
+}
+
+fn keyed_by_id(values: &[Value]) -> bool {
+    values.iter().all(|v| v.get("id").is_some_and(|id| !id.is_null()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Attachment, Conversation};
+
+    fn attachment(id: u64, url: &str) -> Attachment {
+        Attachment {
+            id,
+            name: format!("file{}.png", id),
+            content_type: Some("image/png".to_string()),
+            size: 10,
+            attachment_url: Some(url.to_string()),
+            created_at: None,
+            updated_at: None,
+            sha256: None,
+            skipped: None,
+        }
+    }
+
+    fn ticket(status: FreshdeskStatus, replies: &[&str]) -> Ticket {
+        Ticket {
+            subject: Some("Printer offline".to_string()),
+            attachments: vec![attachment(1, "https://s3/1?X-Amz-Expires=300&sig=a")],
+            conversations: replies
+                .iter()
+                .enumerate()
+                .map(|(i, body)| Conversation { id: i as u64 + 100, body_text: body.to_string(), ..Default::default() })
+                .collect(),
+            ..crate::test_util::ticket(42, status)
+        }
+    }
+
+    fn versions(store: &HistoryStore, lang: Option<&str>) -> Vec<(u32, Vec<String>)> {
+        store.timeline(42, lang).unwrap().into_iter().map(|v| (v.version, v.changes)).collect()
+    }
+
+    #[test]
+    fn record_skips_unchanged_content_and_lists_changed_fields() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        assert_eq!(store.record(&ticket(FreshdeskStatus::Open, &["hi"]), None).unwrap(), Some(1));
+        assert_eq!(store.record(&ticket(FreshdeskStatus::Open, &["hi"]), None).unwrap(), None);
+        assert_eq!(store.record(&ticket(FreshdeskStatus::Pending, &["hi", "more"]), None).unwrap(), Some(2));
+        assert_eq!(store.record(&ticket(FreshdeskStatus::Open, &["hi"]), Some("cn")).unwrap(), Some(1));
+
+        assert_eq!(
+            versions(&store, None),
+            vec![(1, vec![]), (2, vec!["conversations".to_string(), "status".to_string()])]
+        );
+        assert_eq!(versions(&store, Some("zh-CN")).len(), 1);
+        assert_eq!(store.version(42, None, 2).unwrap().unwrap().conversations.len(), 2);
+        assert!(store.version(42, None, 3).unwrap().is_none());
+    }
+
+    #[test]
+    fn refreshed_attachment_urls_are_not_a_change() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let mut first = ticket(FreshdeskStatus::Open, &["hi"]);
+        first.conversations[0].attachments = vec![attachment(2, "https://s3/2?sig=a")];
+        store.record(&first, None).unwrap();
+
+        let mut refreshed = first.clone();
+        refreshed.attachments[0].attachment_url = Some("https://s3/1?X-Amz-Expires=300&sig=b".to_string());
+        refreshed.conversations[0].attachments[0].attachment_url = Some("https://s3/2?sig=b".to_string());
+        assert_eq!(store.record(&refreshed, None).unwrap(), None);
+        assert!(diff_tickets(&first, &refreshed).is_empty());
+        assert!(store.version(42, None, 1).unwrap().unwrap().attachments[0].attachment_url.is_none());
+
+        refreshed.attachments[0].size = 20;
+        assert_eq!(store.record(&refreshed, None).unwrap(), Some(2));
+        let changes = store.diff(42, None, 1, 2).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "attachments[1].size");
+    }
+
+    #[test]
+    fn diff_aligns_conversations_by_id() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        store.record(&ticket(FreshdeskStatus::Open, &["hi", "old"]), None).unwrap();
+        let mut edited = ticket(FreshdeskStatus::Open, &["hi", "new"]);
+        edited.conversations.remove(0);
+        store.record(&edited, None).unwrap();
+
+        let changes = store.diff(42, None, 1, 2).unwrap();
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["conversations[100]", "conversations[101].body_text"]);
+        assert!(changes[0].after.is_none());
+        assert_eq!(changes[1].after, Some(Value::from("new")));
+        assert_eq!(store.diff(42, None, 1, 9).unwrap_err(), "Ticket #42 has no version 9");
+    }
+
+    #[test]
+    fn prune_keeps_the_latest_versions() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        for n in 1..=4 {
+            let replies: Vec<String> = (0..n).map(|i| i.to_string()).collect();
+            let replies: Vec<&str> = replies.iter().map(String::as_str).collect();
+            store.record(&ticket(FreshdeskStatus::Open, &replies), None).unwrap();
+        }
+        store.record(&ticket(FreshdeskStatus::Open, &[]), Some("de")).unwrap();
+
+        assert_eq!(store.prune(&HistoryRetention::default()).unwrap(), 0);
+        assert_eq!(store.prune(&HistoryRetention { max_versions: 2, max_days: 0 }).unwrap(), 2);
+        let kept: Vec<u32> = versions(&store, None).into_iter().map(|(v, _)| v).collect();
+        assert_eq!(kept, vec![3, 4]);
+        // 最新版本即使过期也保留
+        assert_eq!(store.prune(&HistoryRetention { max_versions: 0, max_days: 1 }).unwrap(), 0);
+        assert_eq!(versions(&store, Some("de")).len(), 1);
+    }
+
+    #[test]
+    fn shared_store_is_opened_once_per_data_dir() {
+        let a = HistoryStore::shared("history-shared-test", true).unwrap();
+        let b = HistoryStore::shared("history-shared-test/", true).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &HistoryStore::shared("history-shared-other", true).unwrap()));
+    }
+}
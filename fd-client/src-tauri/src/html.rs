@@ -0,0 +1,510 @@
+//! 工单 HTML 正文的清理、转纯文本，以及翻译时对链接 / 图片的保护
+
+/// 清理 Freshdesk 返回的 HTML：去掉脚本、样式与事件属性，保留链接、图片、表格等结构。
+/// 对已清理的结果再次调用不会产生变化
+pub fn sanitize(html: &str) -> String {
+    ammonia::Builder::default()
+        // 邮件内嵌图片 (src="cid:...")
+        .add_url_schemes(["cid"])
+        .clean(html)
+        .to_string()
+}
+
+/// HTML 转纯文本：块级元素换行，列表项以 "- " 开头，
+/// 链接输出为 `[文字](地址)`，图片输出为 `![说明](地址)`。相同输入总是得到相同输出
+pub fn to_text(html: &str) -> String {
+    let mut out = TextWriter::default();
+    let mut rest = html;
+    let mut skip_until: Option<&'static str> = None;
+    let mut pre_depth = 0usize;
+
+    while !rest.is_empty() {
+        if let Some(stripped) = rest.strip_prefix("<!--") {
+            rest = stripped.find("-->").map(|i| &stripped[i + 3..]).unwrap_or("");
+            continue;
+        }
+        if rest.starts_with('<') {
+            let Some(tag) = Tag::parse(rest) else {
+                out.text("<", pre_depth > 0);
+                rest = &rest[1..];
+                continue;
+            };
+            rest = &rest[tag.len..];
+
+            if let Some(end) = skip_until {
+                if tag.closing && tag.name == end {
+                    skip_until = None;
+                }
+                continue;
+            }
+
+            match (tag.name.as_str(), tag.closing) {
+                ("script", false) => skip_until = Some("script"),
+                ("style", false) => skip_until = Some("style"),
+                ("head", false) => skip_until = Some("head"),
+                ("br", _) => out.newline(),
+                ("li", false) => {
+                    out.line_break();
+                    out.raw("- ");
+                }
+                ("td" | "th", false) => out.cell(),
+                ("pre", false) => {
+                    out.paragraph();
+                    pre_depth += 1;
+                }
+                ("pre", true) => {
+                    pre_depth = pre_depth.saturating_sub(1);
+                    out.paragraph();
+                }
+                ("a", false) => out.open_link(tag.attr("href").unwrap_or_default()),
+                ("a", true) => out.close_link(),
+                ("img", _) => {
+                    // 清理后地址不合法的图片没有 src，不输出
+                    if let Some(src) = tag.attr("src").filter(|s| !s.is_empty()) {
+                        let alt = tag.attr("alt").unwrap_or_default();
+                        out.raw(&format!("![{}]({})", alt.trim(), src));
+                    }
+                }
+                ("hr", _) => {
+                    out.paragraph();
+                    out.raw("---");
+                    out.paragraph();
+                }
+                (name, _) if is_paragraph(name) => out.paragraph(),
+                (name, _) if is_line(name) => out.line_break(),
+                _ => {}
+            }
+            continue;
+        }
+
+        let end = rest.find('<').unwrap_or(rest.len());
+        if skip_until.is_none() {
+            out.text(&decode_entities(&rest[..end]), pre_depth > 0);
+        }
+        rest = &rest[end..];
+    }
+
+    out.finish()
+}
+
+fn is_paragraph(name: &str) -> bool {
+    matches!(
+        name,
+        "p" | "blockquote" | "table" | "ul" | "ol" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+    )
+}
+
+fn is_line(name: &str) -> bool {
+    matches!(name, "div" | "tr" | "dt" | "dd" | "section" | "article" | "header" | "footer")
+}
+
+#[derive(Default)]
+struct TextWriter {
+    buf: String,
+    /// 上一个文本片段以空白结尾，下一个片段前需要补一个空格
+    pending_space: bool,
+    /// 打开的链接：(地址, 链接文字在 buf 中的起始位置)
+    links: Vec<(String, usize)>,
+}
+
+impl TextWriter {
+    /// 去掉行尾空白，但不越过打开的链接的起始位置 (关闭链接时按该位置截取链接文字)
+    fn trim_trailing_spaces(&mut self) {
+        let floor = self.links.last().map_or(0, |(_, start)| *start);
+        let trimmed = self.buf[floor..].trim_end_matches([' ', '\t']).len();
+        self.buf.truncate(floor + trimmed);
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.buf.is_empty() || self.buf.ends_with('\n')
+    }
+
+    fn raw(&mut self, s: &str) {
+        if self.pending_space && !self.at_line_start() {
+            self.buf.push(' ');
+        }
+        self.pending_space = false;
+        self.buf.push_str(s);
+    }
+
+    /// 普通文本折叠空白；`<pre>` 中原样保留
+    fn text(&mut self, s: &str, preformatted: bool) {
+        if preformatted {
+            self.raw(s);
+            return;
+        }
+        let mut words = s.split_whitespace().peekable();
+        let leading = s.starts_with(char::is_whitespace);
+        if words.peek().is_none() {
+            self.pending_space |= leading;
+            return;
+        }
+        self.pending_space |= leading;
+        let joined = words.collect::<Vec<_>>().join(" ");
+        self.raw(&joined);
+        self.pending_space = s.ends_with(char::is_whitespace);
+    }
+
+    fn newline(&mut self) {
+        self.pending_space = false;
+        self.trim_trailing_spaces();
+        self.buf.push('\n');
+    }
+
+    /// 块级元素换行：已在行首时不再产生空行
+    fn line_break(&mut self) {
+        if !self.at_line_start() {
+            self.newline();
+        }
+        self.pending_space = false;
+    }
+
+    /// 段落前后留一个空行 (不重复)
+    fn paragraph(&mut self) {
+        self.pending_space = false;
+        self.trim_trailing_spaces();
+        if self.buf.is_empty() || self.buf.ends_with("\n\n") {
+            return;
+        }
+        self.buf.push_str(if self.buf.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+
+    fn cell(&mut self) {
+        if !self.at_line_start() {
+            self.pending_space = false;
+            self.buf.push_str(" | ");
+        }
+    }
+
+    fn open_link(&mut self, href: String) {
+        self.links.push((href, self.buf.len()));
+    }
+
+    /// 链接文字就是地址本身 (或为空) 时只输出地址
+    fn close_link(&mut self) {
+        let Some((href, start)) = self.links.pop() else {
+            return;
+        };
+        let href = href.as_str();
+        let segment = &self.buf[start..];
+        let label = segment.trim().to_string();
+        if href.is_empty() || label == href || format!("mailto:{}", label) == href {
+            return;
+        }
+        // 链接内首尾的空白与换行留在链接外
+        let leading = segment[..segment.len() - segment.trim_start().len()].to_string();
+        let trailing = match label.is_empty() {
+            true => String::new(),
+            false => segment[segment.trim_end().len()..].to_string(),
+        };
+        self.buf.truncate(start);
+        self.buf.push_str(&leading);
+        if label.is_empty() {
+            self.buf.push_str(href);
+        } else {
+            self.buf.push_str(&format!("[{}]({})", label, href));
+        }
+        self.buf.push_str(&trailing);
+    }
+
+    fn finish(self) -> String {
+        let lines: Vec<&str> = self.buf.lines().map(str::trim_end).collect();
+        let mut text = String::new();
+        let mut blank = 0;
+        for line in lines {
+            if line.is_empty() {
+                blank += 1;
+                if blank > 1 {
+                    continue;
+                }
+            } else {
+                blank = 0;
+            }
+            text.push_str(line);
+            text.push('\n');
+        }
+        // 首行的缩进保留 (`<pre>`)
+        text.trim_start_matches('\n').trim_end().to_string()
+    }
+}
+
+struct Tag {
+    name: String,
+    closing: bool,
+    /// 原始标签文本 (含尖括号)
+    source: String,
+    len: usize,
+}
+
+impl Tag {
+    /// 解析 `s` 开头的标签；不是标签时返回 None
+    fn parse(s: &str) -> Option<Tag> {
+        let body = s.strip_prefix('<')?;
+        let (closing, body) = match body.strip_prefix('/') {
+            Some(b) => (true, b),
+            None => (false, body),
+        };
+        let name_len = body
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(body.len());
+        if name_len == 0 || !body.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        // 找到不在引号内的 '>'
+        let mut quote = None;
+        let mut end = None;
+        for (i, c) in s.char_indices().skip(1) {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '>') => {
+                    end = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let len = end? + 1;
+        Some(Tag {
+            name: body[..name_len].to_ascii_lowercase(),
+            closing,
+            source: s[..len].to_string(),
+            len,
+        })
+    }
+
+    /// 属性值 (已解码实体)
+    fn attr(&self, name: &str) -> Option<String> {
+        let lower = self.source.to_ascii_lowercase();
+        let mut from = 0;
+        while let Some(pos) = lower[from..].find(name) {
+            let start = from + pos;
+            from = start + name.len();
+            let preceded = lower[..start].ends_with(|c: char| c.is_whitespace());
+            let after = lower[from..].trim_start();
+            if !preceded || !after.starts_with('=') {
+                continue;
+            }
+            let value_start = self.source.len() - after.len() + 1;
+            let value = self.source[value_start..].trim_start();
+            let raw = match value.chars().next() {
+                Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or(""),
+                _ => value
+                    .split(|c: char| c.is_whitespace() || c == '>')
+                    .next()
+                    .unwrap_or(""),
+            };
+            return Some(decode_entities(raw));
+        }
+        None
+    }
+}
+
+/// 解码常见的命名实体与数字实体；无法识别的保持原样
+pub fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let decoded = rest[1..].find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..=end];
+            decode_entity(entity).map(|c| (c, end + 2))
+        });
+        match decoded {
+            Some((c, consumed)) => {
+                out.push(c);
+                rest = &rest[consumed..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(num) = entity.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "bull" => '•',
+        "middot" => '·',
+        "euro" => '€',
+        _ => return None,
+    })
+}
+
+/// 翻译前替换为占位符的链接与图片引用
+#[derive(Debug, Default, Clone)]
+pub struct References(Vec<String>);
+
+impl References {
+    fn token(i: usize) -> String {
+        format!("{{{{REF_{}}}}}", i + 1)
+    }
+
+    /// 把 `<img>` 标签、`<a ...>` 开始标签与文本中的地址替换为 `{{REF_n}}`，
+    /// 避免翻译时被改写
+    pub fn protect(s: &str) -> (String, References) {
+        let mut refs = Vec::new();
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+
+        while !rest.is_empty() {
+            if rest.starts_with('<') {
+                if let Some(tag) = Tag::parse(rest) {
+                    if !tag.closing && (tag.name == "img" || tag.name == "a") {
+                        out.push_str(&Self::token(refs.len()));
+                        refs.push(tag.source);
+                    } else {
+                        out.push_str(&tag.source);
+                    }
+                    rest = &rest[tag.len..];
+                    continue;
+                }
+            }
+            if let Some(url_len) = url_at(rest) {
+                out.push_str(&Self::token(refs.len()));
+                refs.push(rest[..url_len].to_string());
+                rest = &rest[url_len..];
+                continue;
+            }
+            let c = rest.chars().next().unwrap_or_default();
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+
+        (out, References(refs))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 还原占位符；译文中丢失的引用追加在末尾，保证链接与图片不会丢
+    pub fn restore(&self, translated: &str) -> String {
+        let mut out = translated.to_string();
+        let mut missing = Vec::new();
+        for (i, original) in self.0.iter().enumerate() {
+            let token = Self::token(i);
+            if out.contains(&token) {
+                out = out.replace(&token, original);
+            } else {
+                missing.push(original.as_str());
+            }
+        }
+        if !missing.is_empty() {
+            out.push('\n');
+            out.push_str(&missing.join("\n"));
+        }
+        out
+    }
+}
+
+/// `s` 以地址开头时返回地址长度 (去掉末尾的标点)
+fn url_at(s: &str) -> Option<usize> {
+    const SCHEMES: [&str; 4] = ["https://", "http://", "mailto:", "cid:"];
+    let scheme = SCHEMES.iter().find(|p| {
+        s.get(..p.len()).is_some_and(|head| head.eq_ignore_ascii_case(p))
+    })?;
+    let end = s
+        .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'' | ')' | ']'))
+        .unwrap_or(s.len());
+    let url = s[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+    (url.len() > scheme.len()).then_some(url.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_keep_their_address() {
+        assert_eq!(
+            to_text(r#"<p>See <a href="https://example.com/a?b=1&amp;c=2">the docs</a> now</p>"#),
+            "See [the docs](https://example.com/a?b=1&c=2) now"
+        );
+        assert_eq!(to_text(r#"<a href="https://x.io">https://x.io</a>"#), "https://x.io");
+        assert_eq!(to_text(r#"<a href="mailto:a@b.io">a@b.io</a>"#), "a@b.io");
+        assert_eq!(to_text(r#"<img src="cid:abc" alt=" logo "> <img alt="no src">"#), "![logo](cid:abc)");
+    }
+
+    #[test]
+    fn lists_and_tables_become_lines() {
+        assert_eq!(
+            to_text("<ul><li>one</li><li>two <b>bold</b></li></ul><p>after</p>"),
+            "- one\n- two bold\n\nafter"
+        );
+        assert_eq!(
+            to_text("<table><tr><th>Name</th><th>Qty</th></tr><tr><td>Apple</td><td>3</td></tr></table>"),
+            "Name | Qty\nApple | 3"
+        );
+        assert_eq!(to_text("a<br><br>b"), "a\n\nb");
+    }
+
+    #[test]
+    fn entities_are_decoded() {
+        assert_eq!(
+            to_text("Tom &amp; Jerry &lt;3 &quot;x&quot; &#39;y&#39; &#x4e2d; &nbsp;end &bogus;"),
+            "Tom & Jerry <3 \"x\" 'y' 中 end &bogus;"
+        );
+    }
+
+    #[test]
+    fn malformed_markup_is_kept_as_text() {
+        assert_eq!(to_text("a < b and <p unclosed text"), "a < b and <p unclosed text");
+        assert_eq!(
+            to_text("<div>x</div><script>alert(1)</script><style>p{}</style><!-- c -->y"),
+            "x\ny"
+        );
+        assert_eq!(to_text("<pre>  line 1\n    line 2</pre>"), "  line 1\n    line 2");
+    }
+
+    #[test]
+    fn line_breaks_inside_links_do_not_panic() {
+        assert_eq!(to_text(&sanitize(r#"<pre>ab   <a href="x"><br></a></pre>"#)), "ab\nx");
+        assert_eq!(to_text(r#"<pre>中文   <a href="x"><p></p></a></pre>"#), "中文\n\nx");
+        assert_eq!(to_text(r#"<pre>中   <a href="x"><pre>中 </pre></a></pre>"#), "中\n\n[中](x)");
+        assert_eq!(to_text(r#"<p>see   <a href="https://x.io"><br>docs<br></a>end</p>"#), "see\n[docs](https://x.io)\nend");
+    }
+
+    #[test]
+    fn references_survive_translation() {
+        let (protected, refs) = References::protect(r#"Read <a href="https://x.io/doc">this</a> or https://y.io."#);
+        assert_eq!(protected, "Read {{REF_1}}this</a> or {{REF_2}}.");
+        assert_eq!(
+            refs.restore("Lies {{REF_1}}das</a> oder {{REF_2}}."),
+            r#"Lies <a href="https://x.io/doc">das</a> oder https://y.io."#
+        );
+        assert_eq!(refs.restore("Lies das."), "Lies das.\n<a href=\"https://x.io/doc\">\nhttps://y.io");
+    }
+}
@@ -0,0 +1,257 @@
+use crate::store::StoreLocation;
+use chrono::Utc;
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// 任务被取消时同步函数返回的错误
+pub const SYNC_CANCELLED: &str = "Sync cancelled";
+
+/// 保留的已结束任务数量 (供状态查询)
+const FINISHED_JOBS_KEPT: usize = 20;
+
+/// 同步期间加排他锁的文件 (位于数据目录)
+pub const SYNC_LOCK_FILE: &str = "sync.lock";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobState {
+    Running,
+    Paused,
+    /// 已请求取消，等待同步在下一个检查点退出
+    Cancelling,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    pub fn is_active(self) -> bool {
+        matches!(self, JobState::Running | JobState::Paused | JobState::Cancelling)
+    }
+}
+
+/// 同步任务状态 (返回给界面 / CLI)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncJobStatus {
+    pub id: String,
+    pub data_dir: String,
+    pub full_sync: bool,
+    pub state: JobState,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// 完成时的摘要或失败原因
+    pub message: Option<String>,
+}
+
+/// 一次同步任务；同步过程在检查点调用 `checkpoint()` 响应暂停与取消
+pub struct SyncJob {
+    status: Mutex<SyncJobStatus>,
+    resumed: Notify,
+    /// 持有 `sync.lock` 的文件句柄，任务结束时释放
+    lock: Mutex<Option<File>>,
+}
+
+impl SyncJob {
+    pub fn id(&self) -> String {
+        self.status.lock().unwrap().id.clone()
+    }
+
+    pub fn status(&self) -> SyncJobStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn state(&self) -> JobState {
+        self.status.lock().unwrap().state
+    }
+
+    /// 暂停时在此等待恢复；已取消时返回 `SYNC_CANCELLED`
+    pub async fn checkpoint(&self) -> Result<(), String> {
+        loop {
+            // 先注册等待再检查状态，避免错过检查之后的 resume/cancel 通知
+            let resumed = self.resumed.notified();
+            match self.state() {
+                JobState::Paused => resumed.await,
+                JobState::Cancelling | JobState::Cancelled => return Err(SYNC_CANCELLED.to_string()),
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    pub fn pause(&self) -> Result<(), String> {
+        self.transition(&[JobState::Running], JobState::Paused)
+    }
+
+    pub fn resume(&self) -> Result<(), String> {
+        self.transition(&[JobState::Paused], JobState::Running)?;
+        self.resumed.notify_waiters();
+        Ok(())
+    }
+
+    pub fn cancel(&self) -> Result<(), String> {
+        self.transition(&[JobState::Running, JobState::Paused], JobState::Cancelling)?;
+        self.resumed.notify_waiters();
+        Ok(())
+    }
+
+    fn transition(&self, from: &[JobState], to: JobState) -> Result<(), String> {
+        let mut status = self.status.lock().unwrap();
+        if !from.contains(&status.state) {
+            return Err(format!("Sync job {} is {:?}, cannot switch to {:?}", status.id, status.state, to));
+        }
+        status.state = to;
+        Ok(())
+    }
+
+    fn finish(&self, result: &Result<String, String>) {
+        let mut status = self.status.lock().unwrap();
+        let (state, message) = match result {
+            Ok(summary) => (JobState::Completed, summary.clone()),
+            Err(e) if e == SYNC_CANCELLED => (JobState::Cancelled, e.clone()),
+            Err(e) => (JobState::Failed, e.clone()),
+        };
+        status.state = state;
+        status.message = Some(message);
+        status.finished_at = Some(Utc::now().to_rfc3339());
+        self.lock.lock().unwrap().take();
+    }
+}
+
+/// 同步任务登记表：同一数据目录同时只允许一个活动任务 (进程内由登记表检查，
+/// 进程之间由数据目录中 `sync.lock` 的文件锁保证)
+#[derive(Default)]
+pub struct SyncJobs {
+    jobs: Mutex<Vec<Arc<SyncJob>>>,
+    next_id: AtomicU64,
+}
+
+impl SyncJobs {
+    /// 登记新任务；该数据目录已有活动任务 (本进程或其他进程) 时拒绝
+    pub fn start(&self, data_dir: &str, full_sync: bool) -> Result<Arc<SyncJob>, String> {
+        let key = dir_key(data_dir);
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(running) = jobs.iter().find(|j| {
+            let s = j.status.lock().unwrap();
+            s.state.is_active() && dir_key(&s.data_dir) == key
+        }) {
+            return Err(format!("A sync is already running for {} (job {})", data_dir, running.id()));
+        }
+        let lock = lock_data_dir(data_dir)?;
+
+        let seq = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let job = Arc::new(SyncJob {
+            status: Mutex::new(SyncJobStatus {
+                id: format!("sync-{}-{}", Utc::now().format("%Y%m%d%H%M%S"), seq),
+                data_dir: data_dir.to_string(),
+                full_sync,
+                state: JobState::Running,
+                started_at: Utc::now().to_rfc3339(),
+                finished_at: None,
+                message: None,
+            }),
+            resumed: Notify::new(),
+            lock: Mutex::new(lock),
+        });
+        jobs.push(job.clone());
+
+        // 只保留最近的已结束任务
+        let finished = jobs.iter().filter(|j| !j.state().is_active()).count();
+        if finished > FINISHED_JOBS_KEPT {
+            let mut to_drop = finished - FINISHED_JOBS_KEPT;
+            jobs.retain(|j| {
+                if to_drop > 0 && !j.state().is_active() {
+                    to_drop -= 1;
+                    return false;
+                }
+                true
+            });
+        }
+        Ok(job)
+    }
+
+    /// 记录任务结果 (完成 / 失败 / 已取消)
+    pub fn finish(&self, job: &SyncJob, result: &Result<String, String>) {
+        job.finish(result);
+    }
+
+    pub fn get(&self, id: &str) -> Result<Arc<SyncJob>, String> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|j| j.id() == id)
+            .cloned()
+            .ok_or_else(|| format!("Sync job not found: {}", id))
+    }
+
+    /// 所有任务状态，最新的在前
+    pub fn list(&self) -> Vec<SyncJobStatus> {
+        self.jobs.lock().unwrap().iter().rev().map(|j| j.status()).collect()
+    }
+}
+
+/// 给数据目录的 `sync.lock` 加排他锁，防止其他进程 (另一个桌面端或 fdctl) 同时同步；
+/// 锁随文件句柄释放，进程异常退出时由系统释放。内存存储没有数据目录，不加锁
+fn lock_data_dir(data_dir: &str) -> Result<Option<File>, String> {
+    let Some(dir) = StoreLocation::parse(data_dir).data_dir().map(|d| Path::new(d).to_path_buf()) else {
+        return Ok(None);
+    };
+    fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let path = dir.join(SYNC_LOCK_FILE);
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    match file.try_lock() {
+        Ok(()) => Ok(Some(file)),
+        Err(TryLockError::WouldBlock) => Err(format!("Another process is already syncing {} ({} is locked)", data_dir, path.display())),
+        Err(TryLockError::Error(e)) => Err(format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// 数据目录的比较键 (尽量解析为规范路径)
+fn dir_key(data_dir: &str) -> String {
+    std::fs::canonicalize(Path::new(data_dir))
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| data_dir.trim_end_matches(['/', '\\']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn data_dir_lock_blocks_other_registries_until_the_job_ends() {
+        let dir = temp_dir("jobs-lock");
+        let data_dir = dir.to_string_lossy().into_owned();
+        // 两个登记表相当于两个进程
+        let (first, second) = (SyncJobs::default(), SyncJobs::default());
+
+        let error = |result: Result<Arc<SyncJob>, String>| result.err().unwrap_or_default();
+
+        let job = first.start(&data_dir, false).unwrap();
+        assert!(dir.join(SYNC_LOCK_FILE).exists());
+        assert!(error(first.start(&data_dir, true)).contains("already running"));
+        assert!(error(second.start(&data_dir, true)).contains("Another process"));
+
+        first.finish(&job, &Ok("done".to_string()));
+        let job = second.start(&data_dir, true).unwrap();
+        second.finish(&job, &Err(SYNC_CANCELLED.to_string()));
+        assert_eq!(job.state(), JobState::Cancelled);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn memory_storage_is_not_locked() {
+        let jobs = SyncJobs::default();
+        assert!(jobs.start("memory:jobs-lock", false).is_ok());
+        assert!(SyncJobs::default().start("memory:jobs-lock", false).is_ok());
+    }
+}
@@ -0,0 +1,152 @@
+use serde::Serialize;
+
+/// 支持的翻译语言：BCP-47 代码、英文名 (用于 AI 提示词)、本地名与历史别名
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Language {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub native_name: &'static str,
+    pub aliases: &'static [&'static str],
+}
+
+/// 语言表；别名不区分大小写，`_` 与 `-` 等价
+pub const LANGUAGES: &[Language] = &[
+    Language { code: "zh-CN", name: "Simplified Chinese", native_name: "简体中文", aliases: &["cn", "zh", "zh-hans", "zh-hans-cn", "chs"] },
+    Language { code: "zh-TW", name: "Traditional Chinese", native_name: "繁體中文", aliases: &["tw", "zh-hant", "zh-hant-tw", "cht"] },
+    Language { code: "en", name: "English", native_name: "English", aliases: &["en-us", "en-gb", "eng"] },
+    Language { code: "ja", name: "Japanese", native_name: "日本語", aliases: &["jp", "ja-jp", "jpn"] },
+    Language { code: "ko", name: "Korean", native_name: "한국어", aliases: &["kr", "ko-kr", "kor"] },
+    Language { code: "de", name: "German", native_name: "Deutsch", aliases: &["de-de", "ger", "deu"] },
+    Language { code: "fr", name: "French", native_name: "Français", aliases: &["fr-fr", "fra", "fre"] },
+    Language { code: "es", name: "Spanish", native_name: "Español", aliases: &["es-es", "spa"] },
+    Language { code: "it", name: "Italian", native_name: "Italiano", aliases: &["it-it", "ita"] },
+    Language { code: "pt-BR", name: "Brazilian Portuguese", native_name: "Português (Brasil)", aliases: &["br"] },
+    Language { code: "pt", name: "Portuguese", native_name: "Português", aliases: &["pt-pt", "por"] },
+    Language { code: "ru", name: "Russian", native_name: "Русский", aliases: &["ru-ru", "rus"] },
+    Language { code: "nl", name: "Dutch", native_name: "Nederlands", aliases: &["nl-nl", "dut", "nld"] },
+    Language { code: "pl", name: "Polish", native_name: "Polski", aliases: &["pl-pl", "pol"] },
+    Language { code: "tr", name: "Turkish", native_name: "Türkçe", aliases: &["tr-tr", "tur"] },
+    Language { code: "ar", name: "Arabic", native_name: "العربية", aliases: &["ara"] },
+    Language { code: "vi", name: "Vietnamese", native_name: "Tiếng Việt", aliases: &["vn", "vie"] },
+    Language { code: "th", name: "Thai", native_name: "ไทย", aliases: &["tha"] },
+    Language { code: "id", name: "Indonesian", native_name: "Bahasa Indonesia", aliases: &["in", "ind"] },
+];
+
+/// 默认翻译语言
+pub const DEFAULT_LANG: &str = "zh-CN";
+
+/// 按代码或别名查找语言
+pub fn find(code: &str) -> Option<&'static Language> {
+    let key = code.trim().replace('_', "-").to_ascii_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|l| l.code.eq_ignore_ascii_case(&key) || l.aliases.contains(&key.as_str()))
+}
+
+/// 规范化为 BCP-47 代码：已知语言与别名映射到表中的代码 (`cn` → `zh-CN`，`jp` → `ja`)，
+/// 其他格式正确的代码按 BCP-47 大小写惯例返回 (`pt_br` → `pt-BR`)
+pub fn normalize(code: &str) -> Result<String, String> {
+    if let Some(lang) = find(code) {
+        return Ok(lang.code.to_string());
+    }
+    let subtags: Vec<&str> = code.trim().split(['-', '_']).collect();
+    let valid = subtags.iter().all(|s| !s.is_empty() && s.len() <= 8 && s.chars().all(|c| c.is_ascii_alphanumeric()))
+        && (2..=3).contains(&subtags[0].len())
+        && subtags[0].chars().all(|c| c.is_ascii_alphabetic());
+    if !valid {
+        return Err(format!("Invalid language code: {:?}", code));
+    }
+    let canonical: Vec<String> = subtags
+        .iter()
+        .enumerate()
+        .map(|(i, s)| match (i, s.len()) {
+            (0, _) => s.to_ascii_lowercase(),
+            // 地区 (US、419)
+            (_, 2) | (_, 3) if s.chars().all(|c| c.is_ascii_alphabetic()) || s.chars().all(|c| c.is_ascii_digit()) => {
+                s.to_ascii_uppercase()
+            }
+            // 文字 (Hans)
+            (_, 4) => {
+                let lower = s.to_ascii_lowercase();
+                lower[..1].to_ascii_uppercase() + &lower[1..]
+            }
+            _ => s.to_ascii_lowercase(),
+        })
+        .collect();
+    Ok(canonical.join("-"))
+}
+
+/// 读取文件名、旧数据等来源的代码：能规范化则规范化，否则原样返回
+pub fn normalize_lenient(code: &str) -> String {
+    normalize(code).unwrap_or_else(|_| code.to_string())
+}
+
+/// AI 提示词等处使用的英文名，未知语言返回代码本身
+pub fn display_name(code: &str) -> String {
+    find(code).map(|l| l.name.to_string()).unwrap_or_else(|| code.to_string())
+}
+
+/// 用于文件名的代码：规范化后的 BCP-47 代码只含字母、数字与 `-`，不会与 `_` 分隔符冲突
+pub fn file_code(code: &str) -> String {
+    normalize_lenient(code).replace(['_', '/', '\\', '.'], "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_maps_aliases_regardless_of_case_and_separator() {
+        for (input, code) in [
+            ("cn", "zh-CN"),
+            ("ZH_hans", "zh-CN"),
+            (" zh-cn ", "zh-CN"),
+            ("tw", "zh-TW"),
+            ("jp", "ja"),
+            ("EN_us", "en"),
+            ("pt_br", "pt-BR"),
+            ("pt-PT", "pt"),
+            ("in", "id"),
+        ] {
+            assert_eq!(normalize(input).unwrap(), code, "{}", input);
+        }
+    }
+
+    #[test]
+    fn normalize_canonicalizes_unknown_codes() {
+        assert_eq!(normalize("SV").unwrap(), "sv");
+        assert_eq!(normalize("es_419").unwrap(), "es-419");
+        assert_eq!(normalize("sr-latn-rs").unwrap(), "sr-Latn-RS");
+        assert_eq!(normalize("fil").unwrap(), "fil");
+        assert_eq!(normalize("de-CH-1996").unwrap(), "de-CH-1996");
+    }
+
+    #[test]
+    fn normalize_rejects_malformed_codes() {
+        for input in ["", "e", "english", "12", "zh--cn", "zh-", "en/us", "../x", "ja-verylongtag"] {
+            assert!(normalize(input).is_err(), "{:?}", input);
+        }
+        assert_eq!(normalize("e").unwrap_err(), "Invalid language code: \"e\"");
+        assert_eq!(normalize_lenient("../x"), "../x");
+        assert_eq!(normalize_lenient("cn"), "zh-CN");
+    }
+
+    #[test]
+    fn table_codes_and_aliases_are_unique_and_normalized() {
+        let mut keys = std::collections::HashSet::new();
+        for lang in LANGUAGES {
+            assert_eq!(normalize(lang.code).unwrap(), lang.code);
+            assert!(keys.insert(lang.code.to_ascii_lowercase()), "{}", lang.code);
+            for alias in lang.aliases {
+                assert_eq!(*alias, alias.to_ascii_lowercase());
+                assert!(keys.insert(alias.to_string()), "{}", alias);
+                assert_eq!(find(alias).map(|l| l.code), Some(lang.code));
+            }
+        }
+        assert_eq!(display_name("cn"), "Simplified Chinese");
+        assert_eq!(display_name("sv"), "sv");
+        assert_eq!(file_code("pt_br"), "pt-BR");
+        assert_eq!(file_code("x/y.z"), "x-y-z");
+    }
+}
@@ -1,8 +1,35 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
+/// 附件元数据 (Freshdesk 返回的 `attachment_url` 为限时签名地址)
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Attachment {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// 已下载到本地附件存储时的内容哈希
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// 未下载的原因 (超出大小限制、类型不在白名单或下载失败)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Conversation {
     pub id: u64,
+    /// HTML 正文 (已清理)，旧数据可能没有
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
     pub body_text: String,
     pub user_id: Option<u64>,
     #[serde(default)]
@@ -14,64 +41,739 @@ pub struct Conversation {
     #[serde(default)]
     pub private: bool,
     pub source: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// Freshdesk 工单状态；线上格式为数字 (2 open / 3 pending / 4 resolved / 5 closed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "StatusRepr", into = "i32")]
+pub enum FreshdeskStatus {
+    #[default]
+    Open,
+    Pending,
+    Resolved,
+    Closed,
+    /// 自定义状态
+    Other(i32),
+}
+
+impl FreshdeskStatus {
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            2 => Self::Open,
+            3 => Self::Pending,
+            4 => Self::Resolved,
+            5 => Self::Closed,
+            other => Self::Other(other),
+        }
+    }
+
+    pub fn code(self) -> i32 {
+        match self {
+            Self::Open => 2,
+            Self::Pending => 3,
+            Self::Resolved => 4,
+            Self::Closed => 5,
+            Self::Other(code) => code,
+        }
+    }
+
+    /// 小写名称，用于文件名
+    pub fn name(self) -> String {
+        match self {
+            Self::Open => "open".to_string(),
+            Self::Pending => "pending".to_string(),
+            Self::Resolved => "resolved".to_string(),
+            Self::Closed => "closed".to_string(),
+            Self::Other(code) => format!("status{}", code),
+        }
+    }
+
+    /// 由 `name()` 反解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "open" => Some(Self::Open),
+            "pending" => Some(Self::Pending),
+            "resolved" => Some(Self::Resolved),
+            "closed" => Some(Self::Closed),
+            other => other.strip_prefix("status")?.parse().ok().map(Self::from_code),
+        }
+    }
+}
+
+impl std::fmt::Display for FreshdeskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "Open"),
+            Self::Pending => write!(f, "Pending"),
+            Self::Resolved => write!(f, "Resolved"),
+            Self::Closed => write!(f, "Closed"),
+            Self::Other(code) => write!(f, "Status {}", code),
+        }
+    }
+}
+
+impl From<FreshdeskStatus> for i32 {
+    fn from(status: FreshdeskStatus) -> i32 {
+        status.code()
+    }
+}
+
+/// 反序列化时同时接受数字与名称 ("open"、"status6"、"6")；无法识别的名称报错
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StatusRepr {
+    Code(i32),
+    Name(String),
+}
+
+impl TryFrom<StatusRepr> for FreshdeskStatus {
+    type Error = String;
+
+    fn try_from(repr: StatusRepr) -> Result<Self, String> {
+        match repr {
+            StatusRepr::Code(code) => Ok(Self::from_code(code)),
+            StatusRepr::Name(name) => Self::from_name(&name.to_lowercase())
+                .or_else(|| name.parse().ok().map(Self::from_code))
+                .ok_or_else(|| format!("Unknown ticket status: {}", name)),
+        }
+    }
+}
+
+/// AutoPilot 服务端的工单流转状态；线上格式为 `PENDING_TRANS` 等字符串。
+///
+/// PENDING_TRANS → TRANSLATING → PENDING_REPLY → REPLYING → PENDING_AUDIT → AUDITING → COMPLETED，
+/// 处理中的状态失败时退回对应的待处理状态，审核驳回 (REJECT) 退回 PENDING_REPLY。
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum TicketStatus {
+pub enum WorkflowStatus {
+    #[default]
     PendingTrans,
+    Translating,
     PendingReply,
+    Replying,
     PendingAudit,
+    Auditing,
     Completed,
     #[serde(untagged)]
     Unknown(serde_json::Value),
 }
 
-impl std::fmt::Display for TicketStatus {
+impl WorkflowStatus {
+    /// 合法的下一状态
+    pub fn next_states(&self) -> &'static [WorkflowStatus] {
+        use WorkflowStatus::*;
+        match self {
+            PendingTrans => &[Translating],
+            Translating => &[PendingReply, PendingTrans],
+            PendingReply => &[Replying],
+            Replying => &[PendingAudit, PendingReply],
+            PendingAudit => &[Auditing],
+            Auditing => &[Completed, PendingReply, PendingAudit],
+            Completed | Unknown(_) => &[],
+        }
+    }
+
+    /// 处理中的状态 (由某个客户端持有)
+    pub fn is_in_progress(&self) -> bool {
+        matches!(self, Self::Translating | Self::Replying | Self::Auditing)
+    }
+
+    pub fn can_transition_to(&self, next: &WorkflowStatus) -> bool {
+        self.next_states().contains(next)
+    }
+
+    /// 校验并返回下一状态；非法流转返回说明原因的错误
+    pub fn transition_to(&self, next: WorkflowStatus) -> Result<WorkflowStatus, String> {
+        if self.can_transition_to(&next) {
+            return Ok(next);
+        }
+        let allowed: Vec<String> = self.next_states().iter().map(|s| s.to_string()).collect();
+        Err(if allowed.is_empty() {
+            format!("Illegal workflow transition {} → {}: {} is a final state", self, next, self)
+        } else {
+            format!("Illegal workflow transition {} → {} (allowed: {})", self, next, allowed.join(", "))
+        })
+    }
+
+    /// 开始处理：从待处理状态进入 `working`；已处于 `working` (消息重投) 也允许
+    pub fn begin(&self, working: WorkflowStatus) -> Result<WorkflowStatus, String> {
+        if working.is_in_progress() && *self == working {
+            return Ok(working);
+        }
+        self.transition_to(working)
+    }
+}
+
+impl std::fmt::Display for WorkflowStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::PendingTrans => write!(f, "PENDING_TRANS"),
+            Self::Translating => write!(f, "TRANSLATING"),
             Self::PendingReply => write!(f, "PENDING_REPLY"),
+            Self::Replying => write!(f, "REPLYING"),
             Self::PendingAudit => write!(f, "PENDING_AUDIT"),
+            Self::Auditing => write!(f, "AUDITING"),
             Self::Completed => write!(f, "COMPLETED"),
             Self::Unknown(v) => write!(f, "{}", v),
         }
     }
 }
 
-impl Default for TicketStatus {
-    fn default() -> Self {
-        Self::PendingTrans
-    }
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
+/// 本地工单，字段与 Freshdesk API 一致 (snake_case，数字状态)，可直接反序列化接口返回
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Ticket {
     pub id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub external_id: Option<String>,
     pub subject: Option<String>,
+    /// HTML 描述 (已清理)，旧数据可能没有
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
     pub description_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
     #[serde(default)]
-    pub status: TicketStatus,
+    pub status: FreshdeskStatus,
+    /// AutoPilot 流转状态，仅来自服务端的工单才有
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow_status: Option<WorkflowStatus>,
     #[serde(default)]
     pub priority: i32,
     #[serde(default)]
     pub created_at: Option<String>,
     #[serde(default)]
     pub updated_at: Option<String>,
+    #[serde(default)]
     pub requester_id: Option<u64>,
+    #[serde(default)]
     pub responder_id: Option<u64>,
     #[serde(default)]
     pub cc_emails: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub ticket_type: Option<String>,
+    /// 1 Email / 2 Portal / 3 Phone / 7 Chat / 9 Feedback Widget / 10 Outbound Email
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fr_due_by: Option<String>,
+    /// 自定义字段 (`cf_*`)，类型与标签见同步下来的 ticket_fields
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom_fields: BTreeMap<String, CustomFieldValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
     #[serde(default)]
     pub conversations: Vec<Conversation>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub available_langs: Vec<String>,
+    /// 会话拉取失败，本地保存的会话可能不全 (已记录到同步失败列表)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub conversations_incomplete: bool,
+}
+
+impl Ticket {
+    /// 清理描述与会话的 HTML；纯文本为空时由 HTML 生成
+    pub fn sanitize_html(&mut self) {
+        if let Some(html) = self.description.as_deref() {
+            let clean = crate::html::sanitize(html);
+            if self.description_text.as_deref().is_none_or(|t| t.trim().is_empty()) {
+                self.description_text = Some(crate::html::to_text(&clean));
+            }
+            self.description = Some(clean);
+        }
+        for conv in &mut self.conversations {
+            if let Some(html) = conv.body.as_deref() {
+                let clean = crate::html::sanitize(html);
+                if conv.body_text.trim().is_empty() {
+                    conv.body_text = crate::html::to_text(&clean);
+                }
+                conv.body = Some(clean);
+            }
+        }
+    }
+
+    /// 去掉描述与会话正文，只留列表与筛选用到的字段 (会话与附件的元数据保留)
+    pub fn strip_bodies(&mut self) {
+        self.description = None;
+        self.description_text = None;
+        for conv in &mut self.conversations {
+            conv.body = None;
+            conv.body_text.clear();
+        }
+    }
+
+    /// 解析本地工单文件，兼容旧格式：camelCase 字段，以及把流转状态写进 `status` 的文件
+    /// (此时 Freshdesk 状态取 `fallback_status`，通常来自文件名)
+    pub fn from_legacy_json(content: &str, fallback_status: Option<FreshdeskStatus>) -> Result<Ticket, String> {
+        let value: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        let serde_json::Value::Object(map) = value else {
+            return Err("Ticket file is not a JSON object".to_string());
+        };
+
+        // snake_case 与 camelCase 同时存在时以 snake_case 为准
+        let (snake, camel): (Vec<_>, Vec<_>) = map
+            .into_iter()
+            .partition(|(key, _)| !key.chars().any(|c| c.is_ascii_uppercase()));
+        let mut normalized: serde_json::Map<String, serde_json::Value> = snake.into_iter().collect();
+        for (key, value) in camel {
+            normalized.entry(camel_to_snake(&key)).or_insert(value);
+        }
+
+        if let Some(serde_json::Value::String(status)) = normalized.get("status").cloned() {
+            let workflow: WorkflowStatus = serde_json::from_value(serde_json::Value::String(status.clone()))
+                .map_err(|e| e.to_string())?;
+            if !matches!(workflow, WorkflowStatus::Unknown(_)) {
+                normalized.insert("workflow_status".to_string(), serde_json::Value::String(status));
+                normalized.insert(
+                    "status".to_string(),
+                    serde_json::json!(fallback_status.unwrap_or_default().code()),
+                );
+            }
+        }
+
+        serde_json::from_value(serde_json::Value::Object(normalized)).map_err(|e| e.to_string())
+    }
+}
+
+fn camel_to_snake(key: &str) -> String {
+    let mut out = String::with_capacity(key.len() + 4);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Freshdesk 工单来源名称
+pub fn source_name(source: i32) -> String {
+    match source {
+        1 => "Email".to_string(),
+        2 => "Portal".to_string(),
+        3 => "Phone".to_string(),
+        7 => "Chat".to_string(),
+        9 => "Feedback Widget".to_string(),
+        10 => "Outbound Email".to_string(),
+        other => format!("Source {}", other),
+    }
+}
+
+/// 自定义字段的值；序列化与 Freshdesk 原始 JSON 一致
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum CustomFieldValue {
+    Bool(bool),
+    Integer(i64),
+    Decimal(f64),
+    Text(String),
+    Null,
+    /// 嵌套字段等其他结构
+    Other(serde_json::Value),
+}
+
+impl CustomFieldValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// 文本值 (不带 JSON 引号)
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Text(s) => Some(s),
+            Self::Other(v) => v.as_str(),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for CustomFieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Integer(n) => write!(f, "{}", n),
+            Self::Decimal(n) => write!(f, "{}", n),
+            Self::Text(s) => write!(f, "{}", s),
+            Self::Null => Ok(()),
+            Self::Other(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// `GET /api/v2/ticket_fields` 返回的字段定义
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TicketField {
+    pub id: u64,
+    /// 自定义字段以 `cf_` 开头
+    pub name: String,
+    pub label: String,
+    /// 如 `custom_text`、`custom_number`、`custom_checkbox`、`custom_dropdown`
+    #[serde(rename = "type")]
+    pub field_type: String,
+    #[serde(default)]
+    pub default: bool,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub choices: serde_json::Value,
+}
+
+/// 同步下来的工单字段定义 (ticket_fields.json)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TicketFieldSchema {
+    pub fields: Vec<TicketField>,
+    pub synced_at: Option<String>,
+}
+
+impl TicketFieldSchema {
+    /// 自定义字段定义，按 Freshdesk 中的顺序
+    pub fn custom_fields(&self) -> impl Iterator<Item = &TicketField> {
+        self.fields.iter().filter(|f| !f.default)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&TicketField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// 按字段类型解析值 (如 number 字段以字符串传入时转为整数)
+    pub fn coerce(&self, name: &str, value: &str) -> CustomFieldValue {
+        let field_type = self.field(name).map(|f| f.field_type.as_str()).unwrap_or("");
+        match field_type {
+            "custom_number" => value.parse().map(CustomFieldValue::Integer).unwrap_or_else(|_| CustomFieldValue::Text(value.to_string())),
+            "custom_decimal" => value.parse().map(CustomFieldValue::Decimal).unwrap_or_else(|_| CustomFieldValue::Text(value.to_string())),
+            "custom_checkbox" => value.parse().map(CustomFieldValue::Bool).unwrap_or_else(|_| CustomFieldValue::Text(value.to_string())),
+            _ => CustomFieldValue::Text(value.to_string()),
+        }
+    }
+}
+
+/// 本地工单筛选条件；未设置的条件不参与过滤
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TicketFilter {
+    /// 必须包含全部标签 (不区分大小写)
+    pub tags: Vec<String>,
+    pub ticket_type: Option<String>,
+    pub source: Option<i32>,
+    pub group_id: Option<u64>,
+    pub product_id: Option<u64>,
+    pub company_id: Option<u64>,
+    pub requester_id: Option<u64>,
+    pub responder_id: Option<u64>,
+    /// Freshdesk 状态码
+    pub status: Option<i32>,
+    pub priority: Option<i32>,
+    /// 创建时间范围 (YYYY-MM-DD 或 RFC 3339，含首尾)
+    pub created_from: Option<String>,
+    pub created_to: Option<String>,
+    /// `due_by` 早于该时间 (RFC 3339，按字符串比较)
+    pub due_before: Option<String>,
+    /// 自定义字段取值相等
+    pub custom_fields: BTreeMap<String, CustomFieldValue>,
+}
+
+impl TicketFilter {
+    pub fn matches(&self, ticket: &Ticket) -> bool {
+        let has_tags = self
+            .tags
+            .iter()
+            .all(|tag| ticket.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+        let type_ok = self.ticket_type.as_ref().is_none_or(|ty| {
+            ticket.ticket_type.as_ref().is_some_and(|t| t.eq_ignore_ascii_case(ty))
+        });
+        let due_ok = self
+            .due_before
+            .as_ref()
+            .is_none_or(|before| ticket.due_by.as_ref().is_some_and(|due| due < before));
+        let created = ticket.created_at.as_deref();
+        let from_ok = self
+            .created_from
+            .as_ref()
+            .is_none_or(|from| created.is_some_and(|c| c >= from.as_str()));
+        // 只比较到截止值的精度，`2024-05-31` 包含当天全部时间
+        let to_ok = self
+            .created_to
+            .as_ref()
+            .is_none_or(|to| created.is_some_and(|c| c.get(..to.len()).unwrap_or(c) <= to.as_str()));
+        let fields_ok = self
+            .custom_fields
+            .iter()
+            .all(|(name, value)| ticket.custom_fields.get(name) == Some(value));
+
+        has_tags
+            && type_ok
+            && due_ok
+            && from_ok
+            && to_ok
+            && fields_ok
+            && (self.source.is_none() || ticket.source == self.source)
+            && (self.group_id.is_none() || ticket.group_id == self.group_id)
+            && (self.product_id.is_none() || ticket.product_id == self.product_id)
+            && (self.company_id.is_none() || ticket.company_id == self.company_id)
+            && (self.requester_id.is_none() || ticket.requester_id == self.requester_id)
+            && (self.responder_id.is_none() || ticket.responder_id == self.responder_id)
+            && self.status.is_none_or(|code| ticket.status.code() == code)
+            && self.priority.is_none_or(|p| ticket.priority == p)
+    }
+}
+
+/// Freshdesk 联系人 (工单的 requester)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Contact {
+    pub id: u64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub company_id: Option<u64>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Company {
+    pub id: u64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub domains: Vec<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// Freshdesk 客服；姓名与邮箱在 `contact` 中
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Agent {
+    pub id: u64,
+    #[serde(default)]
+    pub contact: AgentContact,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AgentContact {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+/// 服务端会话格式 (camelCase)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationDto {
+    pub id: u64,
+    pub body_text: String,
+    pub is_private: Option<bool>,
+    pub incoming: Option<bool>,
+    pub user_id: Option<u64>,
+    pub created_at: Option<String>,
+}
+
+impl From<ConversationDto> for Conversation {
+    fn from(c: ConversationDto) -> Self {
+        Conversation {
+            id: c.id,
+            body_text: c.body_text,
+            user_id: c.user_id,
+            created_at: c.created_at,
+            incoming: c.incoming.unwrap_or(false),
+            private: c.is_private.unwrap_or(false),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&Conversation> for ConversationDto {
+    fn from(c: &Conversation) -> Self {
+        ConversationDto {
+            id: c.id,
+            body_text: c.body_text.clone(),
+            is_private: Some(c.private),
+            incoming: Some(c.incoming),
+            user_id: c.user_id,
+            created_at: c.created_at.clone(),
+        }
+    }
+}
+
+/// 服务端工单 `content` 字段 (JSON 字符串) 的内容
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TicketContent {
+    pub description: Option<String>,
+    pub conversations: Option<Vec<ConversationDto>>,
+}
+
+impl TicketContent {
+    /// 由本地工单构造 (提交译文时使用)
+    pub fn from_ticket(ticket: &Ticket) -> Self {
+        TicketContent {
+            description: ticket.description_text.clone(),
+            conversations: Some(ticket.conversations.iter().map(ConversationDto::from).collect()),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// AutoPilot 服务端的工单格式 (camelCase，流转状态为字符串)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerTicket {
+    pub id: u64,
+    pub external_id: Option<String>,
+    pub subject: Option<String>,
+    /// `TicketContent` 的 JSON
+    pub content: Option<String>,
+    #[serde(default)]
+    pub status: WorkflowStatus,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl From<ServerTicket> for Ticket {
+    fn from(server: ServerTicket) -> Self {
+        let content: TicketContent = server
+            .content
+            .as_deref()
+            .and_then(|c| serde_json::from_str(c).ok())
+            .unwrap_or_default();
+        Ticket {
+            id: server.id,
+            external_id: server.external_id,
+            subject: server.subject,
+            description_text: content.description,
+            content: server.content,
+            workflow_status: Some(server.status),
+            created_at: server.created_at,
+            updated_at: server.updated_at,
+            conversations: content.conversations.unwrap_or_default().into_iter().map(Conversation::from).collect(),
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SyncState {
     pub last_updated_at: Option<String>,
 }
+
+/// 同步失败发生的步骤
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncStep {
+    FetchTicket,
+    FetchConversations,
+    Save,
+}
+
+impl std::fmt::Display for SyncStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FetchTicket => write!(f, "fetch ticket"),
+            Self::FetchConversations => write!(f, "fetch conversations"),
+            Self::Save => write!(f, "save"),
+        }
+    }
+}
+
+/// 同步失败记录 (sync_failures.json)，重试成功后移除
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncFailure {
+    pub ticket_id: u64,
+    pub step: SyncStep,
+    pub error: String,
+    /// 连续失败次数
+    pub attempts: u32,
+    pub failed_at: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use WorkflowStatus::*;
+
+    const ALL: [WorkflowStatus; 7] = [PendingTrans, Translating, PendingReply, Replying, PendingAudit, Auditing, Completed];
+
+    #[test]
+    fn transition_table_matches_the_lifecycle() {
+        let allowed: Vec<(WorkflowStatus, WorkflowStatus)> = ALL
+            .iter()
+            .flat_map(|from| ALL.iter().filter(|to| from.can_transition_to(to)).map(|to| (from.clone(), to.clone())))
+            .collect();
+        assert_eq!(
+            allowed,
+            [
+                (PendingTrans, Translating),
+                (Translating, PendingTrans),
+                (Translating, PendingReply),
+                (PendingReply, Replying),
+                (Replying, PendingReply),
+                (Replying, PendingAudit),
+                (PendingAudit, Auditing),
+                (Auditing, PendingReply),
+                (Auditing, PendingAudit),
+                (Auditing, Completed),
+            ]
+        );
+    }
+
+    #[test]
+    fn illegal_transitions_explain_what_is_allowed() {
+        assert_eq!(
+            PendingTrans.transition_to(Completed).unwrap_err(),
+            "Illegal workflow transition PENDING_TRANS → COMPLETED (allowed: TRANSLATING)"
+        );
+        assert_eq!(
+            Completed.transition_to(PendingReply).unwrap_err(),
+            "Illegal workflow transition COMPLETED → PENDING_REPLY: COMPLETED is a final state"
+        );
+    }
+
+    #[test]
+    fn begin_accepts_redelivered_messages() {
+        assert_eq!(PendingReply.begin(Replying), Ok(Replying));
+        assert_eq!(Replying.begin(Replying), Ok(Replying));
+        assert!(PendingReply.begin(PendingReply).is_err());
+        assert!(PendingTrans.begin(Replying).is_err());
+    }
+
+    #[test]
+    fn freshdesk_status_accepts_codes_and_names_but_rejects_unknown_names() {
+        let parse = |json: &str| serde_json::from_str::<FreshdeskStatus>(json);
+        assert_eq!(parse("4").unwrap(), FreshdeskStatus::Resolved);
+        assert_eq!(parse("\"Closed\"").unwrap(), FreshdeskStatus::Closed);
+        assert_eq!(parse("\"status6\"").unwrap(), FreshdeskStatus::Other(6));
+        assert_eq!(parse("\"3\"").unwrap(), FreshdeskStatus::Pending);
+        assert!(parse("\"on hold\"").unwrap_err().to_string().contains("Unknown ticket status: on hold"));
+        assert!(Ticket::from_legacy_json(r#"{"id": 1, "status": "ON_HOLD"}"#, Some(FreshdeskStatus::Open)).is_err());
+    }
+
+    #[test]
+    fn unknown_statuses_round_trip_and_are_final() {
+        let status: WorkflowStatus = serde_json::from_str("\"ON_HOLD\"").unwrap();
+        assert_eq!(status, Unknown(serde_json::json!("ON_HOLD")));
+        assert_eq!(serde_json::to_string(&status).unwrap(), "\"ON_HOLD\"");
+        assert!(status.next_states().is_empty());
+        assert_eq!(serde_json::from_str::<WorkflowStatus>("\"PENDING_AUDIT\"").unwrap(), PendingAudit);
+    }
+}
@@ -5,10 +5,10 @@ use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
-use tauri::AppHandle;
 
-use crate::ai::GeminiClient;
-use crate::models::Ticket;
+use crate::ai::{GeminiClient, TranslateFormat};
+use crate::events::EventSink;
+use crate::models::{ServerTicket, Ticket, TicketContent, WorkflowStatus};
 use crate::settings::Settings;
 use crate::storage::Storage;
 
@@ -27,6 +27,10 @@ pub struct MqConfig {
     pub port: u16,
     pub username: String,
     pub password: String,
+    /// 译文保存目录
+    pub output_dir: String,
+    /// 翻译目标语言
+    pub translation_lang: String,
 }
 
 impl MqConfig {
@@ -36,27 +40,12 @@ impl MqConfig {
             port: settings.mq_port,
             username: settings.mq_username.clone(),
             password: settings.mq_password.clone(),
+            output_dir: settings.output_dir.clone(),
+            translation_lang: crate::lang::normalize_lenient(&settings.translation_lang),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ConversationDto {
-    pub id: u64,
-    pub body_text: String,
-    pub is_private: Option<bool>,
-    pub incoming: Option<bool>,
-    pub user_id: Option<u64>,
-    pub created_at: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TicketContent {
-    pub description: Option<String>,
-    pub conversations: Option<Vec<ConversationDto>>,
-}
-
 /// MQ 消息结构
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -186,7 +175,7 @@ impl MqConsumer {
     /// 启动消费循环
     pub async fn start_consuming(
         &self,
-        app: AppHandle,
+        sink: Arc<dyn EventSink>,
         auth_token: String,
         queue_type: &str, // "translate" or "reply"
     ) -> Result<(), String> {
@@ -201,7 +190,7 @@ impl MqConsumer {
         }
 
         self.state.is_running.store(true, Ordering::SeqCst);
-        GeminiClient::log(&app, &format!("🐰 Connecting to RabbitMQ for {}...", queue_name));
+        GeminiClient::log(sink.as_ref(), &format!("🐰 Connecting to RabbitMQ for {}...", queue_name));
 
         let conn = self.connect().await?;
         let channel = conn
@@ -240,7 +229,7 @@ impl MqConsumer {
             .await
             .map_err(|e| format!("Failed to declare queue: {}", e))?;
 
-        GeminiClient::log(&app, &format!("✅ Connected to RabbitMQ, consuming from {} (batch: {})", queue_name, batch_size));
+        GeminiClient::log(sink.as_ref(), &format!("✅ Connected to RabbitMQ, consuming from {} (batch: {})", queue_name, batch_size));
 
         // 创建消费者
         let mut consumer = channel
@@ -259,7 +248,7 @@ impl MqConsumer {
                 Ok(Some(delivery_result)) => {
                     match delivery_result {
                         Ok(delivery) => {
-                            let app_clone = app.clone();
+                            let sink_clone = sink.clone();
                             let channel_clone = channel.clone();
                             let self_clone = self.clone();
                             let auth_token_clone = auth_token.clone();
@@ -268,14 +257,14 @@ impl MqConsumer {
                             // 派发到异步任务处理，实现并发 (受 QoS prefetch 限制)
                             tokio::spawn(async move {
                                 if q_type == "translate" {
-                                    self_clone.handle_delivery(app_clone, channel_clone, delivery, auth_token_clone).await;
+                                    self_clone.handle_delivery(sink_clone, channel_clone, delivery, auth_token_clone).await;
                                 } else {
-                                    self_clone.handle_reply_delivery(app_clone, channel_clone, delivery, auth_token_clone).await;
+                                    self_clone.handle_reply_delivery(sink_clone, channel_clone, delivery, auth_token_clone).await;
                                 }
                             });
                         }
                         Err(e) => {
-                            GeminiClient::log(&app, &format!("❌ Delivery error: {}", e));
+                            GeminiClient::log(sink.as_ref(), &format!("❌ Delivery error: {}", e));
                         }
                     }
                 }
@@ -290,7 +279,7 @@ impl MqConsumer {
             }
         }
 
-        GeminiClient::log(&app, "🛑 MQ Consumer stopped");
+        GeminiClient::log(sink.as_ref(), "🛑 MQ Consumer stopped");
         self.state.is_running.store(false, Ordering::SeqCst);
         Ok(())
     }
@@ -298,19 +287,19 @@ impl MqConsumer {
     /// 处理单个消息
     async fn handle_delivery(
         &self,
-        app: AppHandle,
+        sink: Arc<dyn EventSink>,
         channel: lapin::Channel,
         delivery: lapin::message::Delivery,
         auth_token: String,
     ) {
         let _data = String::from_utf8_lossy(&delivery.data);
         let preview = _data.chars().take(200).collect::<String>();
-        GeminiClient::log(&app, &format!("📨 Raw MQ Message (len: {}): {}{}", _data.len(), preview, if _data.len() > 200 { "..." } else { "" }));
+        GeminiClient::log(sink.as_ref(), &format!("📨 Raw MQ Message (len: {}): {}{}", _data.len(), preview, if _data.len() > 200 { "..." } else { "" }));
 
         // 解析消息
         match serde_json::from_slice::<TranslationMessage>(&delivery.data) {
             Ok(msg) => {
-                GeminiClient::log(&app, &format!("📝 Processing ticket #{} (external_id: {})", msg.ticket_id, msg.payload.external_id));
+                GeminiClient::log(sink.as_ref(), &format!("📝 Processing ticket #{} (external_id: {})", msg.ticket_id, msg.payload.external_id));
                 
                 let started_at = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -331,7 +320,7 @@ impl MqConsumer {
                 }
                 
                 // 执行翻译并提交
-                let result = self.translate_and_submit(&app, &msg, &auth_token).await;
+                let result = self.translate_and_submit(&sink, &msg, &auth_token).await;
                 
                 let completed_at = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -366,14 +355,14 @@ impl MqConsumer {
                 
                 match result {
                     Ok(_) => {
-                        GeminiClient::log(&app, &format!("✅ Ticket #{} processing completed and saved", msg.ticket_id));
+                        GeminiClient::log(sink.as_ref(), &format!("✅ Ticket #{} processing completed and saved", msg.ticket_id));
                         // 任务成功完成且保存成功后 ACK 消息
                         let _ = channel
                             .basic_ack(delivery.delivery_tag, BasicAckOptions::default())
                             .await;
                     }
                     Err(ref e) => {
-                        GeminiClient::log(&app, &format!("❌ Translation failed for ticket #{}: {}", msg.ticket_id, e));
+                        GeminiClient::log(sink.as_ref(), &format!("❌ Translation failed for ticket #{}: {}", msg.ticket_id, e));
                         // 失败则 NACK 且不重新入队，防止无限循环抢占资源
                         let _ = channel
                             .basic_nack(delivery.delivery_tag, BasicNackOptions { requeue: false, ..Default::default() })
@@ -382,7 +371,7 @@ impl MqConsumer {
                 }
             }
             Err(e) => {
-                GeminiClient::log(&app, &format!("❌ Failed to parse message: {}", e));
+                GeminiClient::log(sink.as_ref(), &format!("❌ Failed to parse message: {}", e));
                 // 格式错误无法解析，ACK 避免死循环
                 let _ = channel
                     .basic_ack(delivery.delivery_tag, BasicAckOptions::default())
@@ -394,17 +383,17 @@ impl MqConsumer {
     /// 处理回复消息
     async fn handle_reply_delivery(
         &self,
-        app: AppHandle,
+        sink: Arc<dyn EventSink>,
         channel: lapin::Channel,
         delivery: lapin::message::Delivery,
         auth_token: String,
     ) {
         let _data = String::from_utf8_lossy(&delivery.data);
-        GeminiClient::log(&app, &format!("📨 Received Reply Task MQ Message (len: {})", _data.len()));
+        GeminiClient::log(sink.as_ref(), &format!("📨 Received Reply Task MQ Message (len: {})", _data.len()));
 
         match serde_json::from_slice::<ReplyMessage>(&delivery.data) {
             Ok(msg) => {
-                GeminiClient::log(&app, &format!("📝 Processing reply for ticket #{}", msg.ticket_id));
+                GeminiClient::log(sink.as_ref(), &format!("📝 Processing reply for ticket #{}", msg.ticket_id));
                 
                 let started_at = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -427,7 +416,7 @@ impl MqConsumer {
                 // (注意：generate_reply_and_submit 内部会负责注册 ACK 等待信号)
 
                 // 通知前端开始处理，并在这里等待结果（generate_reply_and_submit 内部已包含 rx 等待）
-                let result = self.generate_reply_and_submit(&app, &msg, &auth_token).await;
+                let result = self.generate_reply_and_submit(sink.as_ref(), &msg, &auth_token).await;
                 let final_success = result.is_ok();
 
                 let completed_at = std::time::SystemTime::now()
@@ -465,10 +454,10 @@ impl MqConsumer {
                 }
                 
                 if final_success {
-                    GeminiClient::log(&app, &format!("✅ Reply task for ticket #{} completed and ACKed", msg.ticket_id));
+                    GeminiClient::log(sink.as_ref(), &format!("✅ Reply task for ticket #{} completed and ACKed", msg.ticket_id));
                     let _ = channel.basic_ack(delivery.delivery_tag, BasicAckOptions::default()).await;
                 } else {
-                    GeminiClient::log(&app, &format!("❌ Reply task for ticket #{} failed or timed out, NACKing (no requeue)", msg.ticket_id));
+                    GeminiClient::log(sink.as_ref(), &format!("❌ Reply task for ticket #{} failed or timed out, NACKing (no requeue)", msg.ticket_id));
                     let _ = channel.basic_nack(delivery.delivery_tag, BasicNackOptions { requeue: false, ..Default::default() }).await;
                 }
 
@@ -479,7 +468,7 @@ impl MqConsumer {
                 }
             }
             Err(e) => {
-                GeminiClient::log(&app, &format!("❌ Failed to parse reply message: {}", e));
+                GeminiClient::log(sink.as_ref(), &format!("❌ Failed to parse reply message: {}", e));
                 let _ = channel.basic_ack(delivery.delivery_tag, BasicAckOptions::default()).await;
             }
         }
@@ -494,11 +483,11 @@ impl MqConsumer {
     /// 翻译并提交结果 (改为发送事件通知前端处理)
     async fn translate_and_submit(
         &self,
-        app: &AppHandle,
+        shared_sink: &Arc<dyn EventSink>,
         msg: &TranslationMessage,
         auth_token: &str,
     ) -> Result<(), String> {
-        let settings = crate::settings::load_settings(app);
+        let sink = shared_sink.as_ref();
         // 1. 从 API 获取最新完整工单数据 (包含 conversations)
         let client = reqwest::Client::new();
         let get_url = format!("{}/tickets/{}", SERVER_API_URL, msg.ticket_id);
@@ -509,40 +498,31 @@ impl MqConsumer {
             .await
             .map_err(|e| format!("Failed to fetch ticket from server: {}", e))?;
         
-        let api_resp: RustApiResponse<Ticket> = resp.json()
+        let api_resp: RustApiResponse<ServerTicket> = resp.json()
             .await
             .map_err(|e| format!("Failed to parse ticket response: {}", e))?;
         
-        let original_ticket = api_resp.data.ok_or_else(|| format!("Ticket #{} not found on server", msg.ticket_id))?;
+        let server_ticket = api_resp.data
+            .ok_or_else(|| format!("Ticket #{} not found on server", msg.ticket_id))?;
+        server_ticket.status.begin(WorkflowStatus::Translating)
+            .map_err(|e| format!("Ticket #{} cannot be translated: {}", msg.ticket_id, e))?;
+        let original_ticket: Ticket = server_ticket.into();
         
         // 2. 调用 AI 模块进行翻译 (后端直接调用，并发受 QoS 限制)
-        GeminiClient::log(app, &format!("⚙️ Backend AI translating ticket #{}...", msg.ticket_id));
-        let target_lang = settings.translation_lang.clone();
-        let translated = GeminiClient::translate_ticket(app, &original_ticket, &target_lang).await?;
+        GeminiClient::log(sink, &format!("⚙️ Backend AI translating ticket #{}...", msg.ticket_id));
+        let target_lang = self.config.translation_lang.clone();
+        let mut translated = GeminiClient::translate_ticket(sink, &original_ticket, &target_lang, TranslateFormat::Text).await?;
+        translated.workflow_status = Some(WorkflowStatus::Translating);
         
         // 3. 保存到本地存储
-        let storage = Storage::new(&settings.output_dir);
+        let storage = Storage::new(&self.config.output_dir).with_sink(shared_sink.clone());
         storage.save_ticket(&translated, Some(&target_lang))?;
         
         // 4. 提交到服务端
-        GeminiClient::log(app, &format!("📤 Submitting translation for ticket #{} to server...", msg.ticket_id));
+        GeminiClient::log(sink, &format!("📤 Submitting translation for ticket #{} to server...", msg.ticket_id));
         
-        // 构造服务端期望的 JSON 结构 (与前端 ServerTicketDetail.tsx:L194 一致)
-        let translated_conversations: Vec<serde_json::Value> = translated.conversations.iter().map(|c| {
-            serde_json::json!({
-                "id": c.id,
-                "bodyText": c.body_text,
-                "userId": c.user_id,
-                "createdAt": c.created_at,
-                "incoming": c.incoming,
-                "isPrivate": c.private
-            })
-        }).collect();
-
-        let final_translated_content = serde_json::json!({
-            "description": translated.description_text,
-            "conversations": translated_conversations
-        }).to_string();
+        // 构造服务端期望的 content JSON (与前端 ServerTicketDetail.tsx 一致)
+        let final_translated_content = TicketContent::from_ticket(&translated).to_json();
 
         let submit_data = serde_json::json!({
             "targetLang": target_lang,
@@ -564,11 +544,14 @@ impl MqConsumer {
             return Err(format!("Server returned error during translation submission ({}): {}", status, body));
         }
 
-        GeminiClient::log(app, &format!("✅ Translation for ticket #{} successfully submitted to server", msg.ticket_id));
+        GeminiClient::log(sink, &format!("✅ Translation for ticket #{} successfully submitted to server", msg.ticket_id));
+
+        // 服务端已接受译文，本地副本随之进入 PENDING_REPLY
+        translated.workflow_status = Some(WorkflowStatus::Translating.transition_to(WorkflowStatus::PendingReply)?);
+        storage.save_ticket(&translated, Some(&target_lang))?;
 
         // 5. 发出事件通知前端刷新
-        use tauri::Emitter;
-        let _ = app.emit("ticket-updated", msg.ticket_id);
+        sink.event("ticket-updated", serde_json::json!(msg.ticket_id));
         
         Ok(())
     }
@@ -576,7 +559,7 @@ impl MqConsumer {
     /// 生成回复并提交 (已弃用直接调用，改为通知前端)
     async fn generate_reply_and_submit(
         &self,
-        app: &AppHandle,
+        sink: &dyn EventSink,
         msg: &ReplyMessage,
         auth_token: &str,
     ) -> Result<(), String> {
@@ -589,11 +572,15 @@ impl MqConsumer {
             .await
             .map_err(|e| format!("Failed to fetch ticket from server: {}", e))?;
         
-        let api_resp: RustApiResponse<Ticket> = resp.json()
+        let api_resp: RustApiResponse<ServerTicket> = resp.json()
             .await
             .map_err(|e| format!("Failed to parse ticket response: {}", e))?;
         
-        let server_ticket = api_resp.data.ok_or_else(|| format!("Ticket #{} not found on server", msg.ticket_id))?;
+        let server_ticket = api_resp.data
+            .ok_or_else(|| format!("Ticket #{} not found on server", msg.ticket_id))?;
+        server_ticket.status.begin(WorkflowStatus::Replying)
+            .map_err(|e| format!("Ticket #{} cannot be replied to: {}", msg.ticket_id, e))?;
+        let server_ticket: Ticket = server_ticket.into();
         
         {
             let mut translating = self.state.translating_tickets.lock().await;
@@ -611,7 +598,6 @@ impl MqConsumer {
         }
 
         // --- 核心改动：发出事件通知前端处理 ---
-        use tauri::Emitter;
         let payload = serde_json::json!({
             "ticketId": msg.ticket_id,
             "externalId": server_ticket.external_id,
@@ -621,10 +607,9 @@ impl MqConsumer {
             "authToken": auth_token,
         });
         
-        app.emit("mq-reply-request", payload.to_string())
-            .map_err(|e| format!("Failed to emit mq-reply-request: {}", e))?;
+        sink.event("mq-reply-request", serde_json::Value::String(payload.to_string()));
 
-        GeminiClient::log(app, &format!("📡 Emitted mq-reply-request for ticket #{}", msg.ticket_id));
+        GeminiClient::log(sink, &format!("📡 Emitted mq-reply-request for ticket #{}", msg.ticket_id));
         
         // 等待前端完成信号（或者超时/失败）
         match tokio::time::timeout(std::time::Duration::from_secs(300), rx).await {
@@ -636,7 +621,7 @@ impl MqConsumer {
                 }
             }
             _ => {
-                GeminiClient::log(app, &format!("⏳ Reply task for ticket #{} timed out waiting for frontend", msg.ticket_id));
+                GeminiClient::log(sink, &format!("⏳ Reply task for ticket #{} timed out waiting for frontend", msg.ticket_id));
                 Err("Reply task timed out".to_string())
             }
         }
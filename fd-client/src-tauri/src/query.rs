@@ -0,0 +1,414 @@
+use crate::directory::Directory;
+use crate::lang;
+use crate::models::{Contact, FreshdeskStatus, Ticket, TicketFilter};
+use crate::storage::Storage;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PAGE_LIMIT: usize = 50;
+pub const MAX_PAGE_LIMIT: usize = 500;
+
+/// 列表排序字段；值相同时按工单 ID 排序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    #[default]
+    Id,
+    CreatedAt,
+    UpdatedAt,
+    LastActivity,
+    Priority,
+    Status,
+    Subject,
+}
+
+impl SortKey {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+            Self::LastActivity => "last_activity",
+            Self::Priority => "priority",
+            Self::Status => "status",
+            Self::Subject => "subject",
+        }
+    }
+}
+
+impl std::str::FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        [
+            Self::Id,
+            Self::CreatedAt,
+            Self::UpdatedAt,
+            Self::LastActivity,
+            Self::Priority,
+            Self::Status,
+            Self::Subject,
+        ]
+        .into_iter()
+        .find(|key| key.name() == s.to_ascii_lowercase().replace('-', "_"))
+        .ok_or_else(|| format!("Unknown sort key: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// 本地工单列表查询。`cursor` 为上一页返回的 `nextCursor`，换页时其他条件应保持不变
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TicketListQuery {
+    pub filter: TicketFilter,
+    /// 按该语言的译文列出 (只包含已有该译文的工单)，未设置时列出原文
+    pub lang: Option<String>,
+    /// 必须已有这些语言的译文
+    pub has_langs: Vec<String>,
+    /// 还没有该语言的译文 (待翻译)
+    pub missing_lang: Option<String>,
+    pub sort: SortKey,
+    pub order: SortOrder,
+    pub cursor: Option<String>,
+    /// 每页条数，默认 50，最多 500
+    pub limit: Option<usize>,
+}
+
+/// 列表中的一张工单 (不含描述与会话正文)；完整内容用 `load_ticket_cmd` 读取
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketSummary {
+    pub id: u64,
+    pub subject: Option<String>,
+    pub status: FreshdeskStatus,
+    pub priority: i32,
+    pub requester_id: Option<u64>,
+    pub tags: Vec<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// updated_at 与最新会话时间中较晚的一个
+    pub last_activity_at: Option<String>,
+    pub conversation_count: usize,
+    /// 工单与会话的附件总数
+    pub attachment_count: usize,
+    pub available_langs: Vec<String>,
+}
+
+impl From<&Ticket> for TicketSummary {
+    fn from(ticket: &Ticket) -> Self {
+        let last_conversation = ticket
+            .conversations
+            .iter()
+            .filter_map(|c| c.updated_at.as_ref().or(c.created_at.as_ref()))
+            .max();
+        TicketSummary {
+            id: ticket.id,
+            subject: ticket.subject.clone(),
+            status: ticket.status,
+            priority: ticket.priority,
+            requester_id: ticket.requester_id,
+            tags: ticket.tags.clone(),
+            created_at: ticket.created_at.clone(),
+            updated_at: ticket.updated_at.clone(),
+            last_activity_at: ticket.updated_at.as_ref().max(last_conversation).cloned(),
+            conversation_count: ticket.conversations.len(),
+            attachment_count: ticket.attachments.len()
+                + ticket.conversations.iter().map(|c| c.attachments.len()).sum::<usize>(),
+            available_langs: ticket.available_langs.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketPage {
+    /// 符合条件的工单总数
+    pub total: usize,
+    pub items: Vec<TicketSummary>,
+    /// 下一页的游标；已是最后一页时为 None
+    pub next_cursor: Option<String>,
+}
+
+/// 可按字符串比较的排序值：数字补零，缺失的时间为空串
+fn sort_value(key: SortKey, ticket: &TicketSummary) -> String {
+    let number = |n: i32| format!("{:010}", n as i64 - i32::MIN as i64);
+    match key {
+        SortKey::Id => format!("{:020}", ticket.id),
+        SortKey::CreatedAt => ticket.created_at.clone().unwrap_or_default(),
+        SortKey::UpdatedAt => ticket.updated_at.clone().unwrap_or_default(),
+        SortKey::LastActivity => ticket.last_activity_at.clone().unwrap_or_default(),
+        SortKey::Priority => number(ticket.priority),
+        SortKey::Status => number(ticket.status.code()),
+        SortKey::Subject => ticket.subject.as_deref().unwrap_or("").to_lowercase(),
+    }
+}
+
+/// 游标：`{排序字段}:{工单 ID}:{排序值}`，即上一页最后一条的位置
+fn parse_cursor(cursor: &str, sort: SortKey) -> Result<(String, u64), String> {
+    let invalid = || format!("Invalid cursor: {}", cursor);
+    let mut parts = cursor.splitn(3, ':');
+    let (Some(key), Some(id), Some(value)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    if key != sort.name() {
+        return Err(format!("Cursor was created for sort key {}", key));
+    }
+    Ok((value.to_string(), id.parse().map_err(|_| invalid())?))
+}
+
+/// 按条件筛选、排序并分页本地工单；只读取工单头部 (不含描述与会话正文，见 `Storage::list_headers`)
+pub fn query_tickets(storage: &Storage, query: &TicketListQuery) -> Result<TicketPage, String> {
+    let has_langs: Vec<String> = query.has_langs.iter().map(|l| lang::normalize(l)).collect::<Result<_, _>>()?;
+    let missing_lang = query.missing_lang.as_deref().map(lang::normalize).transpose()?;
+    let list_lang = query.lang.as_deref().map(lang::normalize).transpose()?;
+
+    let mut rows: Vec<(String, TicketSummary)> = storage
+        .list_headers(list_lang.as_deref(), &query.filter)?
+        .iter()
+        .filter(|t| query.filter.matches(t))
+        .filter(|t| has_langs.iter().all(|l| t.available_langs.contains(l)))
+        .filter(|t| missing_lang.as_ref().is_none_or(|l| !t.available_langs.contains(l)))
+        .map(|t| {
+            let summary = TicketSummary::from(t);
+            (sort_value(query.sort, &summary), summary)
+        })
+        .collect();
+    rows.sort_by(|(a, x), (b, y)| a.cmp(b).then(x.id.cmp(&y.id)));
+    if query.order == SortOrder::Desc {
+        rows.reverse();
+    }
+    let total = rows.len();
+
+    let start = match query.cursor.as_deref() {
+        Some(cursor) => {
+            let (value, id) = parse_cursor(cursor, query.sort)?;
+            let after = |(v, t): &(String, TicketSummary)| match query.order {
+                SortOrder::Asc => (v.as_str(), t.id) > (value.as_str(), id),
+                SortOrder::Desc => (v.as_str(), t.id) < (value.as_str(), id),
+            };
+            rows.iter().position(after).unwrap_or(rows.len())
+        }
+        None => 0,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT);
+    let end = (start + limit).min(rows.len());
+    let next_cursor = (end < rows.len())
+        .then(|| &rows[end - 1])
+        .map(|(value, t)| format!("{}:{}:{}", query.sort.name(), t.id, value));
+
+    Ok(TicketPage {
+        total,
+        items: rows.drain(start..end).map(|(_, t)| t).collect(),
+        next_cursor,
+    })
+}
+
+/// 某个联系人的全部本地工单 (摘要，按 ID 倒序) 与联系人信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequesterTickets {
+    pub requester_id: u64,
+    /// 联系人缓存中的信息；未缓存时为 None
+    pub contact: Option<Contact>,
+    pub company: Option<String>,
+    pub tickets: Vec<TicketSummary>,
+}
+
+/// `requester` 为联系人 ID 或邮箱；`lang` 同 `TicketListQuery::lang`
+pub fn requester_tickets(storage: &Storage, requester: &str, lang: Option<String>) -> Result<RequesterTickets, String> {
+    let directory = Directory::load(storage);
+    let requester_id = directory.resolve_contact(requester)?;
+    let mut query = TicketListQuery {
+        filter: TicketFilter { requester_id: Some(requester_id), ..Default::default() },
+        lang,
+        limit: Some(MAX_PAGE_LIMIT),
+        ..Default::default()
+    };
+    let mut tickets = Vec::new();
+    loop {
+        let page = query_tickets(storage, &query)?;
+        tickets.extend(page.items);
+        match page.next_cursor {
+            Some(next) => query.cursor = Some(next),
+            None => break,
+        }
+    }
+    let contact = directory.contacts.get(&requester_id).cloned();
+    let company = contact.as_ref().and_then(|c| c.company_id).and_then(|id| directory.company_name(id));
+    Ok(RequesterTickets { requester_id, contact, company, tickets })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Conversation;
+
+    fn ticket(id: u64, subject: &str, status: FreshdeskStatus, created_at: &str) -> Ticket {
+        Ticket {
+            subject: Some(subject.to_string()),
+            description_text: Some(format!("Description of {}", id)),
+            requester_id: Some(id % 2),
+            created_at: Some(created_at.to_string()),
+            updated_at: Some(created_at.to_string()),
+            conversations: vec![Conversation {
+                id: id * 10,
+                body_text: "reply".to_string(),
+                updated_at: Some(format!("{}T12:00:00Z", &created_at[..10])),
+                ..Default::default()
+            }],
+            ..crate::test_util::ticket(id, status)
+        }
+    }
+
+    fn seed(storage: &Storage) {
+        let subjects = ["delta", "Alpha", "charlie", "bravo", "echo"];
+        for (i, subject) in subjects.iter().enumerate() {
+            let status = if i % 2 == 0 { FreshdeskStatus::Open } else { FreshdeskStatus::Closed };
+            let t = ticket(i as u64 + 1, subject, status, &format!("2024-05-0{}T08:00:00Z", i + 1));
+            storage.save_ticket(&t, None).unwrap();
+        }
+        storage.save_ticket(&ticket(2, "Alpha (de)", FreshdeskStatus::Closed, "2024-05-02"), Some("de")).unwrap();
+    }
+
+    fn ids(page: &TicketPage) -> Vec<u64> {
+        page.items.iter().map(|t| t.id).collect()
+    }
+
+    /// 按 `limit` 逐页取完，返回每页的 ID
+    fn pages(storage: &Storage, mut query: TicketListQuery) -> Vec<Vec<u64>> {
+        let mut pages = Vec::new();
+        loop {
+            let page = query_tickets(storage, &query).unwrap();
+            pages.push(ids(&page));
+            match page.next_cursor {
+                Some(next) => query.cursor = Some(next),
+                None => return pages,
+            }
+        }
+    }
+
+    #[test]
+    fn pages_cover_every_ticket_once_in_sort_order() {
+        let storage = Storage::new("memory:query-pages");
+        seed(&storage);
+        let query = |sort, order| TicketListQuery { sort, order, limit: Some(2), ..Default::default() };
+
+        assert_eq!(pages(&storage, query(SortKey::Id, SortOrder::Desc)), vec![vec![5, 4], vec![3, 2], vec![1]]);
+        assert_eq!(pages(&storage, query(SortKey::Subject, SortOrder::Asc)), vec![vec![2, 4], vec![3, 1], vec![5]]);
+        // 相同排序值按 ID
+        assert_eq!(pages(&storage, query(SortKey::Status, SortOrder::Asc)), vec![vec![1, 3], vec![5, 2], vec![4]]);
+
+        let page = query_tickets(&storage, &query(SortKey::Subject, SortOrder::Asc)).unwrap();
+        assert_eq!(page.total, 5);
+        assert_eq!(page.next_cursor.as_deref(), Some("subject:4:bravo"));
+        let summary = &page.items[0];
+        assert_eq!((summary.conversation_count, summary.available_langs.clone()), (1, vec!["de".to_string()]));
+        assert_eq!(summary.last_activity_at.as_deref(), Some("2024-05-02T12:00:00Z"));
+    }
+
+    #[test]
+    fn cursor_stays_valid_when_tickets_change_between_pages() {
+        let storage = Storage::new("memory:query-cursor");
+        seed(&storage);
+        let mut query = TicketListQuery { sort: SortKey::CreatedAt, order: SortOrder::Asc, limit: Some(2), ..Default::default() };
+        let first = query_tickets(&storage, &query).unwrap();
+        assert_eq!(ids(&first), [1, 2]);
+        assert_eq!(first.next_cursor.as_deref(), Some("created_at:2:2024-05-02T08:00:00Z"));
+
+        // 游标所在的工单被删除、前面插入新工单，都不影响后续页
+        storage.delete_ticket(2).unwrap();
+        storage.save_ticket(&ticket(9, "new", FreshdeskStatus::Open, "2024-04-30T00:00:00Z"), None).unwrap();
+        query.cursor = first.next_cursor;
+        assert_eq!(ids(&query_tickets(&storage, &query).unwrap()), [3, 4]);
+    }
+
+    #[test]
+    fn invalid_cursors_are_rejected() {
+        let storage = Storage::new("memory:query-invalid");
+        seed(&storage);
+        let query = |cursor: &str| TicketListQuery { cursor: Some(cursor.to_string()), ..Default::default() };
+        assert_eq!(query_tickets(&storage, &query("nonsense")).unwrap_err(), "Invalid cursor: nonsense");
+        assert_eq!(query_tickets(&storage, &query("id:x:1")).unwrap_err(), "Invalid cursor: id:x:1");
+        assert_eq!(
+            query_tickets(&storage, &query("subject:4:bravo")).unwrap_err(),
+            "Cursor was created for sort key subject"
+        );
+        // 排序值中可以有 `:`
+        assert_eq!(parse_cursor("updated_at:7:2024-05-01T08:00:00Z", SortKey::UpdatedAt).unwrap(), ("2024-05-01T08:00:00Z".to_string(), 7));
+    }
+
+    #[test]
+    fn filters_and_translation_languages_apply_before_paging() {
+        let storage = Storage::new("memory:query-filter");
+        seed(&storage);
+        let page = |query: TicketListQuery| ids(&query_tickets(&storage, &query).unwrap());
+
+        let open = TicketFilter { status: Some(FreshdeskStatus::Open.code()), ..Default::default() };
+        assert_eq!(page(TicketListQuery { filter: open, ..Default::default() }), [5, 3, 1]);
+        let may = TicketFilter {
+            created_from: Some("2024-05-02".to_string()),
+            created_to: Some("2024-05-04".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(page(TicketListQuery { filter: may, ..Default::default() }), [4, 3, 2]);
+        assert_eq!(page(TicketListQuery { has_langs: vec!["DE".to_string()], ..Default::default() }), [2]);
+        assert_eq!(page(TicketListQuery { missing_lang: Some("de".to_string()), ..Default::default() }), [5, 4, 3, 1]);
+        assert_eq!(page(TicketListQuery { lang: Some("de".to_string()), ..Default::default() }), [2]);
+        assert!(query_tickets(&storage, &TicketListQuery { lang: Some("?".to_string()), ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn file_and_sqlite_backends_list_headers_without_bodies() {
+        let dir = crate::test_util::temp_dir("query");
+        for output_dir in [dir.display().to_string(), format!("sqlite:{}", dir.display())] {
+            let storage = Storage::new(&output_dir);
+            seed(&storage);
+            let filter = TicketFilter { requester_id: Some(1), ..Default::default() };
+            let headers = storage.list_headers(None, &filter).unwrap();
+            assert!(headers.iter().all(|t| t.description_text.is_none()), "{}", output_dir);
+            assert!(headers.iter().all(|t| t.conversations.len() == 1 && t.conversations[0].body_text.is_empty()));
+
+            let query = TicketListQuery { filter, sort: SortKey::Subject, order: SortOrder::Asc, ..Default::default() };
+            assert_eq!(ids(&query_tickets(&storage, &query).unwrap()), [3, 1, 5], "{}", output_dir);
+
+            // 文件变化后不再使用缓存的头部
+            storage.save_ticket(&ticket(3, "zulu", FreshdeskStatus::Open, "2024-05-03T08:00:00Z"), None).unwrap();
+            assert_eq!(ids(&query_tickets(&storage, &query).unwrap()), [1, 5, 3], "{}", output_dir);
+            let full = storage.load_ticket(3, None).unwrap().unwrap();
+            assert_eq!(full.conversations[0].body_text, "reply");
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn requester_tickets_resolve_the_contact_by_email() {
+        let storage = Storage::new("memory:query-requester");
+        seed(&storage);
+        let mut directory = Directory::default();
+        directory.merge_contacts(vec![Contact {
+            id: 1,
+            name: "Ada".to_string(),
+            email: Some("ada@example.com".to_string()),
+            phone: None,
+            company_id: None,
+            updated_at: None,
+        }]);
+        directory.save(&storage).unwrap();
+
+        let found = requester_tickets(&storage, "ADA@example.com", None).unwrap();
+        assert_eq!(found.requester_id, 1);
+        assert_eq!(found.contact.map(|c| c.name).as_deref(), Some("Ada"));
+        assert_eq!(found.tickets.iter().map(|t| t.id).collect::<Vec<_>>(), [5, 3, 1]);
+
+        let unknown = requester_tickets(&storage, "0", Some("de".to_string())).unwrap();
+        assert!(unknown.contact.is_none());
+        assert_eq!(unknown.tickets.iter().map(|t| t.id).collect::<Vec<_>>(), [2]);
+        assert!(requester_tickets(&storage, "nobody@example.com", None).is_err());
+    }
+}
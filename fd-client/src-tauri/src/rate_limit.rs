@@ -0,0 +1,202 @@
+use once_cell::sync::Lazy;
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, sleep_until, Duration, Instant};
+
+/// Freshdesk 按分钟计算配额
+const QUOTA_WINDOW: Duration = Duration::from_secs(60);
+/// 剩余配额低于总量的该比例后开始匀速发送
+const LOW_WATERMARK_RATIO: u32 = 10;
+/// 5xx / 超时 / 连接错误的最大重试次数
+const MAX_RETRIES: u32 = 4;
+/// 429 的最大重试次数
+const MAX_RATE_LIMIT_RETRIES: u32 = 10;
+/// 429 未带 Retry-After 时的等待时间
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// 同一 Freshdesk 域名的所有客户端共享一个限流器
+static LIMITERS: Lazy<Mutex<HashMap<String, Arc<RateLimiter>>>> = Lazy::new(Default::default);
+
+#[derive(Default)]
+struct LimiterState {
+    /// X-RateLimit-Remaining
+    remaining: Option<u32>,
+    /// X-RateLimit-Total
+    total: Option<u32>,
+    /// Retry-After 指定的解禁时间
+    blocked_until: Option<Instant>,
+    last_request: Option<Instant>,
+}
+
+/// 根据 Freshdesk 响应头限流：遵守 Retry-After，配额将尽时提前放慢请求
+#[derive(Default)]
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+}
+
+impl RateLimiter {
+    pub fn for_domain(domain: &str) -> Arc<RateLimiter> {
+        LIMITERS
+            .lock()
+            .unwrap()
+            .entry(domain.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// 在锁内为下一个请求预留发送时间并扣减配额，返回该时间；
+    /// 并发请求依次排在前一个预留时间之后
+    fn reserve(&self) -> Instant {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut at = Instant::now();
+        if let Some(until) = state.blocked_until {
+            at = at.max(until);
+        }
+        if let (Some(interval), Some(last)) = (state.pacing_interval(), state.last_request) {
+            at = at.max(last + interval);
+        }
+        state.last_request = Some(at);
+        if let Some(r) = state.remaining.as_mut() {
+            *r = r.saturating_sub(1);
+        }
+        at
+    }
+
+    /// 发送请求前等待配额；等待时不持锁
+    async fn acquire(&self) {
+        sleep_until(self.reserve()).await;
+    }
+
+    /// 从响应头更新配额
+    fn observe(&self, status: StatusCode, headers: &HeaderMap) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(total) = header_u32(headers, "x-ratelimit-total") {
+            state.total = Some(total);
+        }
+        if let Some(remaining) = header_u32(headers, "x-ratelimit-remaining") {
+            state.remaining = Some(remaining);
+        }
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let wait = header_u32(headers, "retry-after")
+                .map(|s| Duration::from_secs(s as u64))
+                .unwrap_or(DEFAULT_RETRY_AFTER);
+            let until = Instant::now() + wait;
+            if state.blocked_until.is_none_or(|b| b < until) {
+                state.blocked_until = Some(until);
+            }
+        }
+    }
+
+    /// 发送请求：限流、429 按 Retry-After 等待后重试、5xx / 超时按指数退避重试。
+    /// `build` 每次重试都会重新构造请求；重试用尽后返回最后的响应或错误。
+    pub async fn send(&self, build: impl Fn() -> RequestBuilder) -> Result<Response, String> {
+        let mut retries = 0;
+        let mut rate_limited = 0;
+
+        loop {
+            self.acquire().await;
+
+            match build().send().await {
+                Ok(resp) => {
+                    let status = resp.status();
+                    self.observe(status, resp.headers());
+
+                    if status == StatusCode::TOO_MANY_REQUESTS && rate_limited < MAX_RATE_LIMIT_RETRIES {
+                        rate_limited += 1;
+                        continue;
+                    }
+                    if status.is_server_error() && retries < MAX_RETRIES {
+                        retries += 1;
+                        sleep(backoff(retries)).await;
+                        continue;
+                    }
+                    return Ok(resp);
+                }
+                Err(e) if (e.is_timeout() || e.is_connect()) && retries < MAX_RETRIES => {
+                    retries += 1;
+                    sleep(backoff(retries)).await;
+                }
+                Err(e) if retries > 0 => return Err(format!("{} (after {} retries)", e, retries)),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+}
+
+impl LimiterState {
+    /// 剩余配额不足时，把剩余请求均匀分布到一个配额周期内
+    fn pacing_interval(&self) -> Option<Duration> {
+        let total = self.total?.max(1);
+        let remaining = self.remaining?;
+        if remaining > total / LOW_WATERMARK_RATIO {
+            return None;
+        }
+        Some(QUOTA_WINDOW / (remaining + 1))
+    }
+}
+
+/// 1s, 2s, 4s, 8s ... 最多 30s
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs((1u64 << attempt.saturating_sub(1).min(5)).min(30))
+}
+
+fn header_u32(headers: &HeaderMap, name: &str) -> Option<u32> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_thirty_seconds() {
+        let waits: Vec<u64> = (1..=7).map(|n| backoff(n).as_secs()).collect();
+        assert_eq!(waits, [1, 2, 4, 8, 16, 30, 30]);
+    }
+
+    #[test]
+    fn pacing_starts_below_low_watermark() {
+        let state = LimiterState { total: Some(100), remaining: Some(50), ..Default::default() };
+        assert_eq!(state.pacing_interval(), None);
+        let state = LimiterState { total: Some(100), remaining: Some(9), ..Default::default() };
+        assert_eq!(state.pacing_interval(), Some(QUOTA_WINDOW / 10));
+    }
+
+    #[test]
+    fn retry_after_delays_reservations_without_holding_the_lock() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS, &headers(&[("retry-after", "5")]));
+
+        let at = limiter.reserve();
+        assert!(at >= start + Duration::from_secs(5));
+        assert!(at < start + Duration::from_secs(6));
+        // 预留后锁已释放，其他请求可以更新配额
+        assert!(limiter.state.try_lock().is_ok());
+    }
+
+    #[test]
+    fn paced_requests_queue_one_interval_apart() {
+        let limiter = RateLimiter::default();
+        limiter.observe(StatusCode::OK, &headers(&[("x-ratelimit-total", "100"), ("x-ratelimit-remaining", "4")]));
+
+        let start = Instant::now();
+        let first = limiter.reserve();
+        let second = limiter.reserve();
+        assert!(first >= start && first < start + Duration::from_secs(1));
+        // 第二个请求时剩余 3 次，间隔为 60s / 4
+        assert_eq!(second - first, QUOTA_WINDOW / 4);
+        assert_eq!(limiter.state.lock().unwrap().remaining, Some(2));
+    }
+}
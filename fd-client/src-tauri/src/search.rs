@@ -0,0 +1,479 @@
+use crate::models::{FreshdeskStatus, Ticket};
+use crate::storage::Storage;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// 全文索引库 (位于数据目录)
+pub const SEARCH_DB_FILE: &str = "search.db";
+
+/// 每页默认条数与上限
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 200;
+
+/// `lang` 过滤取该值时只搜索原文
+pub const ORIGINAL_LANG: &str = "original";
+
+/// 中日韩文字两侧插入的分隔符：unicode61 分词器按字切分，结果中再去掉
+const CJK_SEPARATOR: char = '\u{200B}';
+
+/// 索引格式；与 meta 中记录的不同时视为未建立，下次搜索时重建 (2：语言为 BCP-47 代码)
+const INDEX_FORMAT: &str = "2";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS docs (
+    id INTEGER PRIMARY KEY,
+    ticket_id INTEGER NOT NULL,
+    lang TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    requester_id INTEGER,
+    created_at TEXT,
+    updated_at TEXT,
+    subject TEXT,
+    UNIQUE (ticket_id, lang)
+);
+CREATE INDEX IF NOT EXISTS idx_docs_created_at ON docs(created_at);
+CREATE INDEX IF NOT EXISTS idx_docs_requester ON docs(requester_id);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS docs_fts USING fts5(
+    subject, description, conversations,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// 搜索条件。`query` 中的词默认全部匹配，`\"...\"` 为短语，`-词` 排除，`词*` 前缀匹配；
+/// 日期为 YYYY-MM-DD，按创建时间过滤 (含首尾)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchQuery {
+    pub query: String,
+    pub status: Option<i32>,
+    /// 语言代码，或 "original" 只搜原文；未设置时原文与译文都搜
+    pub lang: Option<String>,
+    pub requester_id: Option<u64>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// 从 1 开始
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+/// 一条命中 (工单的原文或某个译文)；snippet 中命中的词以 `<mark>` 标出
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub ticket_id: u64,
+    /// None 为原文
+    pub lang: Option<String>,
+    pub subject: Option<String>,
+    pub status: FreshdeskStatus,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub hits: Vec<SearchHit>,
+}
+
+/// 工单原文与译文的全文索引 (SQLite FTS5)：标题、描述与会话正文，按相关度排序
+pub struct SearchIndex {
+    conn: Mutex<Connection>,
+}
+
+impl SearchIndex {
+    /// 打开 (必要时创建) 数据目录下的 search.db
+    pub fn open(data_dir: &str) -> Result<Self, String> {
+        let path = Path::new(data_dir).join(SEARCH_DB_FILE);
+        let conn = Connection::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::init(conn).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// 不落盘的索引 (内存存储使用)
+    pub fn open_in_memory() -> Result<Self, String> {
+        let conn = Connection::open_in_memory().map_err(|e| e.to_string())?;
+        Self::init(conn).map_err(|e| e.to_string())
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch("PRAGMA journal_mode = WAL;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(SearchIndex { conn: Mutex::new(conn) })
+    }
+
+    /// 是否已从存储完整建立过索引
+    pub fn is_built(&self) -> bool {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT value FROM meta WHERE key = 'format'", [], |row| row.get::<_, String>(0))
+            .optional()
+            .ok()
+            .flatten()
+            .is_some_and(|format| format == INDEX_FORMAT)
+    }
+
+    /// 标记为需要重建 (增量更新失败后索引与存储可能不一致)
+    pub fn invalidate(&self) {
+        let conn = self.conn.lock().unwrap();
+        let _ = conn.execute("DELETE FROM meta WHERE key = 'format'", []);
+    }
+
+    /// 清空后按存储中的全部工单与译文重建，返回索引的文档数
+    pub fn rebuild(&self, storage: &Storage) -> Result<usize, String> {
+        let originals = storage.list_tickets(None)?;
+        let langs: BTreeSet<String> = originals.iter().flat_map(|t| t.available_langs.iter().cloned()).collect();
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute_batch("DELETE FROM docs; DELETE FROM docs_fts; DELETE FROM meta;")
+            .map_err(|e| e.to_string())?;
+        let mut count = 0;
+        for ticket in &originals {
+            index_in(&tx, ticket, None)?;
+            count += 1;
+        }
+        for lang in &langs {
+            for ticket in storage.list_tickets(Some(lang))? {
+                index_in(&tx, &ticket, Some(lang))?;
+                count += 1;
+            }
+        }
+        tx.execute(
+            "INSERT INTO meta (key, value) VALUES ('built_at', ?1), ('format', ?2)",
+            [chrono::Utc::now().to_rfc3339(), INDEX_FORMAT.to_string()],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(count)
+    }
+
+    /// 索引 (或更新) 工单原文 / 译文
+    pub fn index_ticket(&self, ticket: &Ticket, lang: Option<&str>) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        index_in(&tx, ticket, lang)?;
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// 移除工单的索引；`lang` 为 None 时移除原文与全部译文
+    pub fn remove_ticket(&self, ticket_id: u64, lang: Option<&str>) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        let (filter, lang) = match lang {
+            Some(lang) => ("ticket_id = ?1 AND lang = ?2", lang),
+            None => ("ticket_id = ?1 AND ?2 IS NOT NULL", ""),
+        };
+        conn.execute(
+            &format!("DELETE FROM docs_fts WHERE rowid IN (SELECT id FROM docs WHERE {})", filter),
+            params![ticket_id as i64, lang],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(&format!("DELETE FROM docs WHERE {}", filter), params![ticket_id as i64, lang])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn search(&self, query: &SearchQuery) -> Result<SearchResults, String> {
+        let mut sql = String::from(" FROM docs_fts JOIN docs d ON d.id = docs_fts.rowid WHERE docs_fts MATCH ?");
+        let mut args: Vec<rusqlite::types::Value> = vec![fts_query(&query.query)?.into()];
+        if let Some(status) = query.status {
+            sql.push_str(" AND d.status = ?");
+            args.push(status.into());
+        }
+        match query.lang.as_deref().map(str::trim) {
+            Some(ORIGINAL_LANG) => sql.push_str(" AND d.lang = ''"),
+            Some(lang) if !lang.is_empty() => {
+                sql.push_str(" AND d.lang = ?");
+                args.push(crate::lang::normalize_lenient(lang).into());
+            }
+            _ => {}
+        }
+        if let Some(requester) = query.requester_id {
+            sql.push_str(" AND d.requester_id = ?");
+            args.push((requester as i64).into());
+        }
+        if let Some(from) = non_empty(query.from.as_deref()) {
+            sql.push_str(" AND substr(d.created_at, 1, 10) >= ?");
+            args.push(from.to_string().into());
+        }
+        if let Some(to) = non_empty(query.to.as_deref()) {
+            sql.push_str(" AND substr(d.created_at, 1, 10) <= ?");
+            args.push(to.to_string().into());
+        }
+
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+        let conn = self.conn.lock().unwrap();
+        let total: i64 = conn
+            .query_row(&format!("SELECT count(*){}", sql), rusqlite::params_from_iter(args.iter()), |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+
+        // 标题命中的权重更高
+        let select = format!(
+            "SELECT d.ticket_id, d.lang, d.subject, d.status, d.created_at, d.updated_at,
+                    snippet(docs_fts, -1, '<mark>', '</mark>', '…', 16){}
+             ORDER BY bm25(docs_fts, 3.0, 1.0, 1.0), d.updated_at DESC
+             LIMIT {} OFFSET {}",
+            sql,
+            page_size,
+            (page - 1) * page_size
+        );
+        let mut stmt = conn.prepare(&select).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(args.iter()), |row| {
+                let lang: String = row.get(1)?;
+                Ok(SearchHit {
+                    ticket_id: row.get::<_, i64>(0)? as u64,
+                    lang: (!lang.is_empty()).then_some(lang),
+                    subject: row.get(2)?,
+                    status: FreshdeskStatus::from_code(row.get(3)?),
+                    created_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                    snippet: join_cjk(&row.get::<_, String>(6)?),
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        let hits = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+
+        Ok(SearchResults {
+            total: total as usize,
+            page,
+            page_size,
+            hits,
+        })
+    }
+}
+
+/// 按条件搜索本地工单；索引尚未建立时先从存储建立 (内存存储每次临时建立)
+pub fn search_tickets(storage: &Storage, query: &SearchQuery) -> Result<SearchResults, String> {
+    match storage.search_index()? {
+        Some(index) => {
+            if !index.is_built() {
+                index.rebuild(storage)?;
+            }
+            index.search(query)
+        }
+        None => {
+            let index = SearchIndex::open_in_memory()?;
+            index.rebuild(storage)?;
+            index.search(query)
+        }
+    }
+}
+
+fn index_in(conn: &Connection, ticket: &Ticket, lang: Option<&str>) -> Result<(), String> {
+    let lang = lang.unwrap_or("");
+    let id: i64 = conn
+        .query_row(
+            "INSERT INTO docs (ticket_id, lang, status, requester_id, created_at, updated_at, subject)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(ticket_id, lang) DO UPDATE SET
+                status = excluded.status, requester_id = excluded.requester_id, created_at = excluded.created_at,
+                updated_at = excluded.updated_at, subject = excluded.subject
+             RETURNING id",
+            params![
+                ticket.id as i64,
+                lang,
+                ticket.status.code(),
+                ticket.requester_id.map(|id| id as i64),
+                ticket.created_at,
+                ticket.updated_at,
+                ticket.subject,
+            ],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("#{}: {}", ticket.id, e))?;
+
+    let conversations: Vec<&str> = ticket.conversations.iter().map(|c| c.body_text.as_str()).collect();
+    conn.execute("DELETE FROM docs_fts WHERE rowid = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO docs_fts (rowid, subject, description, conversations) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            id,
+            split_cjk(ticket.subject.as_deref().unwrap_or("")),
+            split_cjk(ticket.description_text.as_deref().unwrap_or("")),
+            split_cjk(&conversations.join("\n")),
+        ],
+    )
+    .map_err(|e| format!("#{}: {}", ticket.id, e))?;
+    Ok(())
+}
+
+/// 把用户输入转换为 FTS5 查询：每个词或短语都加引号 (避免 `USB-B` 之类被当作语法)
+fn fts_query(input: &str) -> Result<String, String> {
+    let mut include = Vec::new();
+    let mut exclude = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let negated = c == '-';
+        if negated {
+            chars.next();
+        }
+        let (term, quoted) = if chars.peek() == Some(&'"') {
+            chars.next();
+            let term: String = chars.by_ref().take_while(|&c| c != '"').collect();
+            (term, true)
+        } else {
+            let mut term = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                term.push(c);
+                chars.next();
+            }
+            (term, false)
+        };
+
+        let (term, prefix) = match term.strip_suffix('*') {
+            Some(t) if !quoted => (t.to_string(), true),
+            _ => (term, false),
+        };
+        if term.trim().is_empty() {
+            continue;
+        }
+        let mut phrase = format!("\"{}\"", split_cjk(&term).replace('"', "\"\""));
+        if prefix {
+            phrase.push_str(" *");
+        }
+        if negated {
+            exclude.push(phrase);
+        } else {
+            include.push(phrase);
+        }
+    }
+
+    if include.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+    let mut query = include.join(" AND ");
+    for phrase in exclude {
+        query.push_str(" NOT ");
+        query.push_str(&phrase);
+    }
+    Ok(query)
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}')
+}
+
+/// 在中日韩文字两侧插入分隔符，使其逐字成词 (否则整段连续文字只是一个词)
+fn split_cjk(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if is_cjk(c) {
+            out.push(CJK_SEPARATOR);
+            out.push(c);
+            out.push(CJK_SEPARATOR);
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn join_cjk(text: &str) -> String {
+    text.replace(CJK_SEPARATOR, "")
+}
+
+fn non_empty(s: Option<&str>) -> Option<&str> {
+    s.map(str::trim).filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket(id: u64, subject: &str, description: &str) -> Ticket {
+        Ticket {
+            subject: Some(subject.to_string()),
+            description_text: Some(description.to_string()),
+            created_at: Some("2024-03-01T00:00:00Z".to_string()),
+            updated_at: Some("2024-03-01T00:00:00Z".to_string()),
+            ..crate::test_util::ticket(id, FreshdeskStatus::Open)
+        }
+    }
+
+    fn ids(index: &SearchIndex, query: &str) -> Vec<u64> {
+        let query = SearchQuery { query: query.to_string(), ..Default::default() };
+        let mut ids: Vec<u64> = index.search(&query).unwrap().hits.iter().map(|h| h.ticket_id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn fts_query_quotes_terms_phrases_and_prefixes() {
+        assert_eq!(fts_query("usb  cable").unwrap(), "\"usb\" AND \"cable\"");
+        assert_eq!(fts_query("\"power supply\" USB-B").unwrap(), "\"power supply\" AND \"USB-B\"");
+        assert_eq!(fts_query("print*").unwrap(), "\"print\" *");
+        assert_eq!(fts_query("\"print*\"").unwrap(), "\"print*\"");
+        assert_eq!(fts_query("say \"hi").unwrap(), "\"say\" AND \"hi\"");
+    }
+
+    #[test]
+    fn fts_query_appends_negated_terms() {
+        assert_eq!(fts_query("printer -laser -\"ink jet\"").unwrap(), "\"printer\" NOT \"laser\" NOT \"ink jet\"");
+        assert_eq!(fts_query("-laser").unwrap_err(), "Search query is empty");
+        assert_eq!(fts_query("  * \"\" ").unwrap_err(), "Search query is empty");
+    }
+
+    #[test]
+    fn cjk_text_is_split_per_character_and_joined_back() {
+        let sep = CJK_SEPARATOR;
+        assert_eq!(split_cjk("打印a"), format!("{sep}打{sep}{sep}印{sep}a"));
+        assert_eq!(fts_query("打印").unwrap(), format!("\"{sep}打{sep}{sep}印{sep}\""));
+        assert_eq!(join_cjk(&split_cjk("無法打印 PDF")), "無法打印 PDF");
+    }
+
+    #[test]
+    fn search_matches_cjk_substrings_negation_and_prefixes() {
+        let index = SearchIndex::open_in_memory().unwrap();
+        index.index_ticket(&ticket(1, "打印机无法连接", "USB-B cable"), None).unwrap();
+        index.index_ticket(&ticket(2, "Printer offline", "laser printer"), None).unwrap();
+        index.index_ticket(&ticket(3, "Printing issue", "ink jet"), None).unwrap();
+
+        assert_eq!(ids(&index, "无法"), vec![1]);
+        assert_eq!(ids(&index, "USB-B"), vec![1]);
+        assert_eq!(ids(&index, "print*"), vec![2, 3]);
+        assert_eq!(ids(&index, "print* -laser"), vec![3]);
+        assert_eq!(ids(&index, "\"ink jet\""), vec![3]);
+
+        let query = SearchQuery { query: "无法".to_string(), ..Default::default() };
+        let hit = &index.search(&query).unwrap().hits[0];
+        assert_eq!(hit.snippet, "打印机<mark>无法</mark>连接");
+    }
+
+    #[test]
+    fn invalidate_marks_the_index_for_rebuild() {
+        let storage = Storage::new("memory:search-invalidate");
+        storage.save_ticket(&ticket(7, "Router reboot", ""), None).unwrap();
+        let index = SearchIndex::open_in_memory().unwrap();
+        assert!(!index.is_built());
+        assert_eq!(index.rebuild(&storage).unwrap(), 1);
+        assert!(index.is_built());
+        index.invalidate();
+        assert!(!index.is_built());
+    }
+}
@@ -1,13 +1,42 @@
 use rusqlite::{Connection, Result};
-use std::path::PathBuf;
-use tauri::AppHandle;
-use tauri::Manager;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "desktop")]
+use tauri::{AppHandle, Manager};
+
+/// 与 tauri.conf.json 中的 identifier 保持一致，用于定位桌面端的数据目录
+pub const APP_IDENTIFIER: &str = "com.zangjiafu.fd-client";
+
+/// 未配置时使用的 Freshdesk 域名
+pub const DEFAULT_FRESHDESK_DOMAIN: &str = "simsonn.freshdesk.com";
+
+/// Freshdesk 账号配置：每个账号有独立的域名、API Key 与数据目录
+/// (同步游标 sync_state.json 保存在各自的数据目录中)
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+pub struct AccountProfile {
+    pub name: String,
+    pub domain: String,
+    pub api_key: String,
+    pub output_dir: String,
+}
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
 pub struct Settings {
     pub api_key: String,
-    pub output_dir: String,
+    pub output_dir: String,       // 数据目录；`sqlite:<目录>` / `*.db` 使用 SQLite，`memory:` 使用内存存储
     pub sync_start_date: String,
+    pub sync_end_date: String,    // 全量同步截止日期，空表示到今天
+    pub sync_window_days: u32,    // 全量同步每个查询窗口的天数
+    pub sync_concurrency: u32,    // 同步时并发拉取会话的请求数
+    pub attachment_max_mb: u32,   // 单个附件下载上限 (MB)，0 表示不下载附件
+    pub attachment_mime_types: String, // 允许下载的附件类型，逗号分隔，支持 image/*
+    pub history_max_versions: u32, // 每张工单保留的历史版本数，0 表示不限
+    pub history_max_days: u32,    // 历史版本保留天数，0 表示不限
+    pub watch_interval_secs: u32, // 检查其他进程修改工单文件的间隔 (秒)，0 表示不检查
+    pub freshdesk_domain: String,
+    // 多账号配置：当前账号的域名/Key/目录会同步到上面的顶层字段
+    pub active_account: String,
+    #[serde(default)]
+    pub accounts: Vec<AccountProfile>,
     // RabbitMQ 配置
     pub mq_host: String,
     pub mq_port: u16,
@@ -16,7 +45,7 @@ pub struct Settings {
     // MQ 消费者配置
     pub mq_consumer_enabled: bool, // MQ消费者是否应该自动启动
     pub mq_batch_size: u32,        // 每批翻译任务数量
-    pub translation_lang: String,  // 翻译目标语言 (如 "cn", "en")
+    pub translation_lang: String,  // 翻译目标语言 (BCP-47，如 "zh-CN", "en")
 }
 
 impl Default for Settings {
@@ -25,6 +54,17 @@ impl Default for Settings {
             api_key: String::new(),
             output_dir: "data".to_string(),
             sync_start_date: "2025-01".to_string(),
+            sync_end_date: String::new(),
+            sync_window_days: 7,
+            sync_concurrency: 4,
+            attachment_max_mb: 25,
+            attachment_mime_types: crate::attachments::DEFAULT_MIME_TYPES.to_string(),
+            history_max_versions: 50,
+            history_max_days: 0,
+            watch_interval_secs: crate::ticket_index::DEFAULT_WATCH_INTERVAL_SECS,
+            freshdesk_domain: DEFAULT_FRESHDESK_DOMAIN.to_string(),
+            active_account: String::new(),
+            accounts: Vec::new(),
             // MQ 默认配置
             mq_host: "localhost".to_string(),
             mq_port: 5672,
@@ -33,11 +73,93 @@ impl Default for Settings {
             // MQ 消费者默认配置
             mq_consumer_enabled: false,
             mq_batch_size: 5,
-            translation_lang: "cn".to_string(),
+            translation_lang: crate::lang::DEFAULT_LANG.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn account(&self, name: &str) -> Option<&AccountProfile> {
+        self.accounts.iter().find(|a| a.name == name)
+    }
+
+    /// 按名称取账号；未指定时返回当前顶层配置对应的账号
+    pub fn resolve_account(&self, name: Option<&str>) -> Result<AccountProfile, String> {
+        match name {
+            Some(n) if !n.is_empty() => self
+                .account(n)
+                .cloned()
+                .ok_or_else(|| format!("Unknown account: {}", n)),
+            _ => Ok(AccountProfile {
+                name: if self.active_account.is_empty() {
+                    "default".to_string()
+                } else {
+                    self.active_account.clone()
+                },
+                domain: self.freshdesk_domain.clone(),
+                api_key: self.api_key.clone(),
+                output_dir: self.output_dir.clone(),
+            }),
+        }
+    }
+
+    /// 新增或更新账号；若为当前账号则同时刷新顶层字段
+    pub fn upsert_account(&mut self, profile: AccountProfile) {
+        if profile.name == self.active_account {
+            self.apply_account(&profile);
+        }
+        match self.accounts.iter_mut().find(|a| a.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => self.accounts.push(profile),
+        }
+    }
+
+    pub fn remove_account(&mut self, name: &str) -> Result<(), String> {
+        let before = self.accounts.len();
+        self.accounts.retain(|a| a.name != name);
+        if self.accounts.len() == before {
+            return Err(format!("Unknown account: {}", name));
+        }
+        if self.active_account == name {
+            self.active_account.clear();
+        }
+        Ok(())
+    }
+
+    /// 切换当前账号，把该账号的配置写入顶层字段
+    pub fn select_account(&mut self, name: &str) -> Result<(), String> {
+        let profile = self
+            .account(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown account: {}", name))?;
+        self.apply_account(&profile);
+        self.active_account = profile.name;
+        Ok(())
+    }
+
+    /// 顶层字段被界面修改后，回写到当前账号
+    pub fn sync_active_account(&mut self) {
+        let (domain, api_key, output_dir) = (
+            self.freshdesk_domain.clone(),
+            self.api_key.clone(),
+            self.output_dir.clone(),
+        );
+        let active = self.active_account.clone();
+        if let Some(profile) = self.accounts.iter_mut().find(|a| a.name == active) {
+            profile.domain = domain;
+            profile.api_key = api_key;
+            profile.output_dir = output_dir;
         }
     }
+
+    fn apply_account(&mut self, profile: &AccountProfile) {
+        self.freshdesk_domain = profile.domain.clone();
+        self.api_key = profile.api_key.clone();
+        self.output_dir = profile.output_dir.clone();
+    }
 }
 
+#[cfg(feature = "desktop")]
 fn get_db_path(app: &AppHandle) -> PathBuf {
     let app_dir = app
         .path()
@@ -47,6 +169,21 @@ fn get_db_path(app: &AppHandle) -> PathBuf {
     app_dir.join("settings.db")
 }
 
+/// 不依赖 Tauri 推算桌面端 settings.db 的位置 (与 app_data_dir 规则一致)
+pub fn default_db_path() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home.map(|h| h.join("Library").join("Application Support"))
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.map(|h| h.join(".local").join("share")))
+    }?;
+    Some(base.join(APP_IDENTIFIER).join("settings.db"))
+}
+
 fn init_db(conn: &Connection) -> Result<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS settings (
@@ -74,14 +211,41 @@ fn load_setting(conn: &Connection, key: &str) -> Option<String> {
     .ok()
 }
 
+#[cfg(feature = "desktop")]
 pub fn save_settings(app: &AppHandle, settings: &Settings) -> Result<(), String> {
-    let db_path = get_db_path(app);
-    let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+    save_settings_to(&get_db_path(app), settings)
+}
+
+#[cfg(feature = "desktop")]
+pub fn load_settings(app: &AppHandle) -> Settings {
+    load_settings_from(&get_db_path(app))
+}
+
+pub fn save_settings_to(db_path: &Path, settings: &Settings) -> Result<(), String> {
+    if let Some(dir) = db_path.parent() {
+        std::fs::create_dir_all(dir).ok();
+    }
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
     init_db(&conn).map_err(|e| e.to_string())?;
 
     save_setting(&conn, "api_key", &settings.api_key)?;
     save_setting(&conn, "output_dir", &settings.output_dir)?;
     save_setting(&conn, "sync_start_date", &settings.sync_start_date)?;
+    save_setting(&conn, "sync_end_date", &settings.sync_end_date)?;
+    save_setting(&conn, "sync_window_days", &settings.sync_window_days.to_string())?;
+    save_setting(&conn, "sync_concurrency", &settings.sync_concurrency.to_string())?;
+    save_setting(&conn, "attachment_max_mb", &settings.attachment_max_mb.to_string())?;
+    save_setting(&conn, "attachment_mime_types", &settings.attachment_mime_types)?;
+    save_setting(&conn, "history_max_versions", &settings.history_max_versions.to_string())?;
+    save_setting(&conn, "history_max_days", &settings.history_max_days.to_string())?;
+    save_setting(&conn, "watch_interval_secs", &settings.watch_interval_secs.to_string())?;
+    save_setting(&conn, "freshdesk_domain", &settings.freshdesk_domain)?;
+    save_setting(&conn, "active_account", &settings.active_account)?;
+    save_setting(
+        &conn,
+        "accounts",
+        &serde_json::to_string(&settings.accounts).map_err(|e| e.to_string())?,
+    )?;
     save_setting(&conn, "mq_host", &settings.mq_host)?;
     save_setting(&conn, "mq_port", &settings.mq_port.to_string())?;
     save_setting(&conn, "mq_username", &settings.mq_username)?;
@@ -97,14 +261,12 @@ pub fn save_settings(app: &AppHandle, settings: &Settings) -> Result<(), String>
     Ok(())
 }
 
-pub fn load_settings(app: &AppHandle) -> Settings {
-    let db_path = get_db_path(app);
-
+pub fn load_settings_from(db_path: &Path) -> Settings {
     if !db_path.exists() {
         return Settings::default();
     }
 
-    let conn = match Connection::open(&db_path) {
+    let conn = match Connection::open(db_path) {
         Ok(c) => c,
         Err(_) => return Settings::default(),
     };
@@ -120,6 +282,39 @@ pub fn load_settings(app: &AppHandle) -> Settings {
     if let Some(v) = load_setting(&conn, "sync_start_date") {
         settings.sync_start_date = v;
     }
+    if let Some(v) = load_setting(&conn, "sync_end_date") {
+        settings.sync_end_date = v;
+    }
+    if let Some(v) = load_setting(&conn, "sync_window_days") {
+        settings.sync_window_days = v.parse().unwrap_or(7);
+    }
+    if let Some(v) = load_setting(&conn, "sync_concurrency") {
+        settings.sync_concurrency = v.parse().unwrap_or(4);
+    }
+    if let Some(v) = load_setting(&conn, "attachment_max_mb") {
+        settings.attachment_max_mb = v.parse().unwrap_or(25);
+    }
+    if let Some(v) = load_setting(&conn, "attachment_mime_types") {
+        settings.attachment_mime_types = v;
+    }
+    if let Some(v) = load_setting(&conn, "history_max_versions") {
+        settings.history_max_versions = v.parse().unwrap_or(50);
+    }
+    if let Some(v) = load_setting(&conn, "history_max_days") {
+        settings.history_max_days = v.parse().unwrap_or(0);
+    }
+    if let Some(v) = load_setting(&conn, "watch_interval_secs") {
+        settings.watch_interval_secs = v.parse().unwrap_or(crate::ticket_index::DEFAULT_WATCH_INTERVAL_SECS);
+    }
+    if let Some(v) = load_setting(&conn, "freshdesk_domain") {
+        settings.freshdesk_domain = v;
+    }
+    if let Some(v) = load_setting(&conn, "active_account") {
+        settings.active_account = v;
+    }
+    if let Some(v) = load_setting(&conn, "accounts") {
+        settings.accounts = serde_json::from_str(&v).unwrap_or_default();
+    }
     if let Some(v) = load_setting(&conn, "mq_host") {
         settings.mq_host = v;
     }
@@ -139,7 +334,8 @@ pub fn load_settings(app: &AppHandle) -> Settings {
         settings.mq_batch_size = v.parse().unwrap_or(5);
     }
     if let Some(v) = load_setting(&conn, "translation_lang") {
-        settings.translation_lang = v;
+        // 旧版本保存的是 "cn" 等别名
+        settings.translation_lang = crate::lang::normalize_lenient(&v);
     }
 
     settings
@@ -0,0 +1,557 @@
+use crate::fs_store::FsStore;
+use crate::models::{Conversation, Ticket, TicketFilter};
+use crate::store::{StoreLocation, TicketStore};
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// SQLite 工单库 (位于数据目录)
+pub const TICKETS_DB_FILE: &str = "tickets.db";
+
+/// 表结构版本 (PRAGMA user_version)
+const SCHEMA_VERSION: i32 = 2;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS tickets (
+    id INTEGER PRIMARY KEY,
+    status INTEGER NOT NULL,
+    workflow_status TEXT,
+    subject TEXT,
+    requester_id INTEGER,
+    responder_id INTEGER,
+    created_at TEXT,
+    updated_at TEXT,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_tickets_status ON tickets(status);
+CREATE INDEX IF NOT EXISTS idx_tickets_created_at ON tickets(created_at);
+CREATE INDEX IF NOT EXISTS idx_tickets_updated_at ON tickets(updated_at);
+CREATE INDEX IF NOT EXISTS idx_tickets_requester ON tickets(requester_id);
+
+CREATE TABLE IF NOT EXISTS conversations (
+    ticket_id INTEGER NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    id INTEGER NOT NULL,
+    user_id INTEGER,
+    incoming INTEGER NOT NULL,
+    created_at TEXT,
+    data TEXT NOT NULL,
+    PRIMARY KEY (ticket_id, position)
+);
+CREATE INDEX IF NOT EXISTS idx_conversations_id ON conversations(id);
+CREATE INDEX IF NOT EXISTS idx_conversations_created_at ON conversations(created_at);
+
+CREATE TABLE IF NOT EXISTS translations (
+    ticket_id INTEGER NOT NULL,
+    lang TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    updated_at TEXT,
+    data TEXT NOT NULL,
+    PRIMARY KEY (ticket_id, lang)
+);
+CREATE INDEX IF NOT EXISTS idx_translations_lang ON translations(lang);
+
+CREATE TABLE IF NOT EXISTS state (
+    name TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+";
+
+/// `import_json` 的结果
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ImportReport {
+    pub files: usize,
+    pub tickets: usize,
+    pub translations: usize,
+    /// 无法解析或写入的文件及原因
+    pub failed: Vec<String>,
+}
+
+/// 按索引列筛选原文的条件；未设置的条件不参与过滤
+#[derive(Debug, Clone, Default)]
+pub struct TicketQuery {
+    pub status: Option<i32>,
+    pub requester_id: Option<u64>,
+    /// created_at 范围 (含首尾)；截止值只比较到其精度，`2024-05-31` 包含当天
+    pub created_from: Option<String>,
+    pub created_to: Option<String>,
+}
+
+impl TicketQuery {
+    /// `TicketFilter` 中有索引的条件
+    pub fn from_filter(filter: &TicketFilter) -> Self {
+        TicketQuery {
+            status: filter.status,
+            requester_id: filter.requester_id,
+            created_from: filter.created_from.clone(),
+            created_to: filter.created_to.clone(),
+        }
+    }
+
+    /// 作用于 `tickets` 表的 WHERE 子句与参数
+    fn where_clause(&self) -> (String, Vec<Value>) {
+        let mut sql = String::from(" WHERE 1 = 1");
+        let mut args: Vec<Value> = Vec::new();
+        if let Some(status) = self.status {
+            sql.push_str(" AND status = ?");
+            args.push(status.into());
+        }
+        if let Some(requester) = self.requester_id {
+            sql.push_str(" AND requester_id = ?");
+            args.push((requester as i64).into());
+        }
+        if let Some(from) = &self.created_from {
+            sql.push_str(" AND created_at >= ?");
+            args.push(from.clone().into());
+        }
+        if let Some(to) = &self.created_to {
+            sql.push_str(" AND substr(created_at, 1, length(?)) <= ?");
+            args.push(to.clone().into());
+            args.push(to.clone().into());
+        }
+        (sql, args)
+    }
+}
+
+/// SQLite 工单库：工单、会话、译文分表保存，按状态、时间、联系人与语言建索引；
+/// 状态文档保存在 state 表
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open_path(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::init(&conn).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(SqliteStore { conn: Mutex::new(conn) })
+    }
+
+    fn init(conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(())
+    }
+
+    fn save_in(conn: &Connection, ticket: &Ticket, lang: Option<&str>) -> Result<(), String> {
+        if let Some(lang) = lang {
+            let mut translated = ticket.clone();
+            translated.available_langs.clear();
+            let data = serde_json::to_string(&translated).map_err(|e| e.to_string())?;
+            conn.execute(
+                "INSERT OR REPLACE INTO translations (ticket_id, lang, status, updated_at, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![ticket.id as i64, lang, ticket.status.code(), ticket.updated_at, data],
+            )
+            .map_err(|e| e.to_string())?;
+            return Ok(());
+        }
+
+        // 会话单独成表，工单行只保存其余字段
+        let mut row = ticket.clone();
+        let conversations = std::mem::take(&mut row.conversations);
+        row.available_langs.clear();
+        let data = serde_json::to_string(&row).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO tickets (id, status, workflow_status, subject, requester_id, responder_id, created_at, updated_at, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(id) DO UPDATE SET
+                status = excluded.status, workflow_status = excluded.workflow_status, subject = excluded.subject,
+                requester_id = excluded.requester_id, responder_id = excluded.responder_id,
+                created_at = excluded.created_at, updated_at = excluded.updated_at, data = excluded.data",
+            params![
+                ticket.id as i64,
+                ticket.status.code(),
+                ticket.workflow_status.as_ref().map(|w| w.to_string()),
+                ticket.subject,
+                ticket.requester_id.map(|id| id as i64),
+                ticket.responder_id.map(|id| id as i64),
+                ticket.created_at,
+                ticket.updated_at,
+                data,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        conn.execute("DELETE FROM conversations WHERE ticket_id = ?1", [ticket.id as i64])
+            .map_err(|e| e.to_string())?;
+        let mut insert = conn
+            .prepare_cached(
+                "INSERT INTO conversations (ticket_id, position, id, user_id, incoming, created_at, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )
+            .map_err(|e| e.to_string())?;
+        for (position, conv) in conversations.iter().enumerate() {
+            let data = serde_json::to_string(conv).map_err(|e| e.to_string())?;
+            insert
+                .execute(params![
+                    ticket.id as i64,
+                    position as i64,
+                    conv.id as i64,
+                    conv.user_id.map(|id| id as i64),
+                    conv.incoming,
+                    conv.created_at,
+                    data,
+                ])
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// 按条件列出原文 (连同会话)，按 ID 倒序；`bodies` 为 false 时不含描述与会话正文
+    fn select_tickets(conn: &Connection, query: &TicketQuery, bodies: bool) -> Result<Vec<Ticket>, String> {
+        let (filter, args) = query.where_clause();
+        let data = match bodies {
+            true => "data",
+            false => "json_remove(data, '$.description', '$.description_text')",
+        };
+        let mut stmt = conn
+            .prepare(&format!("SELECT {} FROM tickets{} ORDER BY id DESC", data, filter))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(args.iter()), |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        let mut tickets = Vec::new();
+        for data in rows {
+            tickets.push(parse_ticket(&data.map_err(|e| e.to_string())?)?);
+        }
+        let mut conversations = load_conversations(conn, &filter, &args, bodies)?;
+        for ticket in &mut tickets {
+            ticket.conversations = conversations.remove(&ticket.id).unwrap_or_default();
+        }
+        Ok(tickets)
+    }
+
+    /// 某个语言的全部译文 (可按状态过滤)，按工单 ID 倒序
+    fn select_translations(conn: &Connection, lang: &str, status: Option<i32>) -> Result<Vec<Ticket>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT data FROM translations WHERE lang = ?1 AND (?2 IS NULL OR status = ?2)
+                 ORDER BY ticket_id DESC",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![lang, status], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        rows.map(|data| parse_ticket(&data.map_err(|e| e.to_string())?)).collect()
+    }
+
+    /// 一次性导入 JSON 目录中的全部工单与译文 (单个事务；可重复执行，已有的记录被覆盖)
+    pub fn import_json(&self, files: &FsStore) -> Result<ImportReport, String> {
+        let mut report = ImportReport::default();
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+        for (path, lang, ticket) in files.ticket_files() {
+            report.files += 1;
+            let ticket = match ticket {
+                Ok(t) => t,
+                Err(e) => {
+                    report.failed.push(e);
+                    continue;
+                }
+            };
+            match Self::save_in(&tx, &ticket, lang.as_deref()) {
+                Ok(()) if lang.is_some() => report.translations += 1,
+                Ok(()) => report.tickets += 1,
+                Err(e) => report.failed.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(report)
+    }
+}
+
+/// 把 `output_dir` (见 `StoreLocation`) 数据目录中的 JSON 工单导入对应的 SQLite 库：
+/// JSON 目录导入到其下的 tickets.db，SQLite 位置导入到该库文件
+pub fn import_json_dir(output_dir: &str) -> Result<ImportReport, String> {
+    let (data_dir, db_path) = match StoreLocation::parse(output_dir) {
+        StoreLocation::Filesystem(dir) => {
+            let db_path = Path::new(&dir).join(TICKETS_DB_FILE);
+            (dir, db_path)
+        }
+        StoreLocation::Sqlite { data_dir, db_path } => (data_dir, PathBuf::from(db_path)),
+        StoreLocation::Memory(_) => return Err("In-memory storage has no ticket files to import".to_string()),
+    };
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    SqliteStore::open_path(&db_path)?.import_json(&FsStore::new(&data_dir))
+}
+
+impl TicketStore for SqliteStore {
+    /// 保存工单；`lang` 为 None 时保存原文 (连同会话)，否则保存该语言的译文
+    fn save_ticket(&self, ticket: &Ticket, lang: Option<&str>) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        Self::save_in(&tx, ticket, lang).map_err(|e| format!("#{}: {}", ticket.id, e))?;
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    fn load_ticket(&self, ticket_id: u64, lang: Option<&str>) -> Result<Option<Ticket>, String> {
+        let conn = self.conn.lock().unwrap();
+        let data: Option<String> = match lang {
+            Some(lang) => conn
+                .query_row(
+                    "SELECT data FROM translations WHERE ticket_id = ?1 AND lang = ?2",
+                    params![ticket_id as i64, lang],
+                    |row| row.get(0),
+                )
+                .optional(),
+            None => conn
+                .query_row("SELECT data FROM tickets WHERE id = ?1", [ticket_id as i64], |row| row.get(0))
+                .optional(),
+        }
+        .map_err(|e| e.to_string())?;
+
+        let Some(data) = data else {
+            return Ok(None);
+        };
+        let mut ticket = parse_ticket(&data)?;
+        if lang.is_none() {
+            let mut conversations = load_conversations(&conn, " WHERE id = ?", &[(ticket_id as i64).into()], true)?;
+            ticket.conversations = conversations.remove(&ticket_id).unwrap_or_default();
+        }
+        Ok(Some(ticket))
+    }
+
+    fn list_tickets(&self, preferred_lang: Option<&str>) -> Result<Vec<Ticket>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut tickets = match preferred_lang {
+            Some(lang) => Self::select_translations(&conn, lang, None)?,
+            None => Self::select_tickets(&conn, &TicketQuery::default(), true)?,
+        };
+        let langs = all_translation_langs(&conn)?;
+        for ticket in &mut tickets {
+            ticket.available_langs = langs.get(&ticket.id).cloned().unwrap_or_default();
+        }
+        Ok(tickets)
+    }
+
+    /// 原文按索引列 (状态、联系人、创建时间) 过滤，正文不出库；译文按状态过滤
+    fn list_headers(&self, preferred_lang: Option<&str>, filter: &TicketFilter) -> Result<Vec<Ticket>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut tickets = match preferred_lang {
+            Some(lang) => {
+                let mut tickets = Self::select_translations(&conn, lang, filter.status)?;
+                tickets.iter_mut().for_each(Ticket::strip_bodies);
+                tickets
+            }
+            None => Self::select_tickets(&conn, &TicketQuery::from_filter(filter), false)?,
+        };
+        let langs = all_translation_langs(&conn)?;
+        for ticket in &mut tickets {
+            ticket.available_langs = langs.get(&ticket.id).cloned().unwrap_or_default();
+        }
+        Ok(tickets)
+    }
+
+    fn delete_ticket(&self, ticket_id: u64) -> Result<bool, String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        // 会话随工单级联删除
+        let tickets = tx
+            .execute("DELETE FROM tickets WHERE id = ?1", [ticket_id as i64])
+            .map_err(|e| e.to_string())?;
+        let translations = tx
+            .execute("DELETE FROM translations WHERE ticket_id = ?1", [ticket_id as i64])
+            .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(tickets + translations > 0)
+    }
+
+    fn translation_langs(&self, ticket_id: u64) -> Result<Vec<String>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT lang FROM translations WHERE ticket_id = ?1 ORDER BY lang")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([ticket_id as i64], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        rows.map(|r| r.map_err(|e| e.to_string())).collect()
+    }
+
+    fn delete_translation(&self, ticket_id: u64, lang: &str) -> Result<bool, String> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn
+            .execute(
+                "DELETE FROM translations WHERE ticket_id = ?1 AND lang = ?2",
+                params![ticket_id as i64, lang],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(deleted > 0)
+    }
+
+    fn read_state(&self, name: &str) -> Option<String> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT data FROM state WHERE name = ?1", [name], |row| row.get(0))
+            .optional()
+            .ok()
+            .flatten()
+    }
+
+    fn write_state(&self, name: &str, json: &str) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("INSERT OR REPLACE INTO state (name, data) VALUES (?1, ?2)", params![name, json])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn remove_state(&self, name: &str) -> Result<bool, String> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn
+            .execute("DELETE FROM state WHERE name = ?1", [name])
+            .map_err(|e| e.to_string())?;
+        Ok(deleted > 0)
+    }
+}
+
+fn parse_ticket(data: &str) -> Result<Ticket, String> {
+    serde_json::from_str(data).map_err(|e| format!("Corrupt ticket row: {}", e))
+}
+
+/// 按工单分组的会话 (保持原有顺序)：只取 `filter` (作用于 tickets 表的 WHERE 子句) 选中的工单；
+/// `bodies` 为 false 时不含正文
+fn load_conversations(
+    conn: &Connection,
+    filter: &str,
+    args: &[Value],
+    bodies: bool,
+) -> Result<HashMap<u64, Vec<Conversation>>, String> {
+    let data = match bodies {
+        true => "data",
+        false => "json_set(json_remove(data, '$.body'), '$.body_text', '')",
+    };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT ticket_id, {} FROM conversations
+             WHERE ticket_id IN (SELECT id FROM tickets{})
+             ORDER BY ticket_id, position",
+            data, filter
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(args.iter()), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut grouped: HashMap<u64, Vec<Conversation>> = HashMap::new();
+    for row in rows {
+        let (id, data) = row.map_err(|e| e.to_string())?;
+        let conv = serde_json::from_str(&data).map_err(|e| format!("Corrupt conversation row: {}", e))?;
+        grouped.entry(id as u64).or_default().push(conv);
+    }
+    Ok(grouped)
+}
+
+/// 每张工单已有译文的语言
+fn all_translation_langs(conn: &Connection) -> Result<HashMap<u64, Vec<String>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT ticket_id, lang FROM translations ORDER BY ticket_id, lang")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+    let mut langs: HashMap<u64, Vec<String>> = HashMap::new();
+    for row in rows {
+        let (id, lang) = row.map_err(|e| e.to_string())?;
+        langs.entry(id as u64).or_default().push(lang);
+    }
+    Ok(langs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FreshdeskStatus;
+
+    fn ticket(id: u64, status: FreshdeskStatus, created_at: &str, requester_id: u64) -> Ticket {
+        Ticket {
+            created_at: Some(created_at.to_string()),
+            updated_at: Some(created_at.to_string()),
+            requester_id: Some(requester_id),
+            ..crate::test_util::ticket(id, status)
+        }
+    }
+
+    fn conversation(id: u64, body: &str) -> Conversation {
+        Conversation { id, body_text: body.to_string(), ..Default::default() }
+    }
+
+    fn store() -> SqliteStore {
+        SqliteStore::open_path(Path::new(":memory:")).unwrap()
+    }
+
+    fn ids(tickets: &[Ticket]) -> Vec<u64> {
+        tickets.iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn tickets_translations_and_conversations_round_trip() {
+        let store = store();
+        let mut original = ticket(1, FreshdeskStatus::Open, "2024-01-01T00:00:00Z", 7);
+        original.conversations = vec![conversation(12, "second id, first"), conversation(11, "first id, second")];
+        store.save_ticket(&original, None).unwrap();
+        store.save_ticket(&ticket(2, FreshdeskStatus::Closed, "2024-02-01T00:00:00Z", 8), None).unwrap();
+        let mut translated = original.clone();
+        translated.subject = Some("チケット 1".to_string());
+        store.save_ticket(&translated, Some("ja")).unwrap();
+
+        let loaded = store.load_ticket(1, None).unwrap().unwrap();
+        assert_eq!(loaded.conversations.iter().map(|c| c.id).collect::<Vec<_>>(), [12, 11]);
+        assert_eq!(store.load_ticket(1, Some("ja")).unwrap().unwrap().subject.as_deref(), Some("チケット 1"));
+        assert!(store.load_ticket(2, Some("ja")).unwrap().is_none());
+
+        let originals = store.list_tickets(None).unwrap();
+        assert_eq!(ids(&originals), [2, 1]);
+        assert_eq!(originals[1].available_langs, ["ja"]);
+        assert_eq!(originals[1].conversations.len(), 2);
+        let japanese = store.list_tickets(Some("ja")).unwrap();
+        assert_eq!(japanese[0].subject.as_deref(), Some("チケット 1"));
+
+        assert!(store.delete_ticket(1).unwrap());
+        assert!(store.translation_langs(1).unwrap().is_empty());
+        assert_eq!(ids(&store.list_tickets(None).unwrap()), [2]);
+    }
+
+    #[test]
+    fn indexed_query_filters_original_rows() {
+        let store = store();
+        store.save_ticket(&ticket(1, FreshdeskStatus::Open, "2024-05-30T10:00:00Z", 7), None).unwrap();
+        store.save_ticket(&ticket(2, FreshdeskStatus::Open, "2024-05-31T23:59:00Z", 8), None).unwrap();
+        store.save_ticket(&ticket(3, FreshdeskStatus::Pending, "2024-06-01T00:00:00Z", 7), None).unwrap();
+        let conn = store.conn.lock().unwrap();
+        let select = |query: TicketQuery| ids(&SqliteStore::select_tickets(&conn, &query, true).unwrap());
+
+        assert_eq!(select(TicketQuery { status: Some(2), ..Default::default() }), [2, 1]);
+        assert_eq!(select(TicketQuery { requester_id: Some(7), ..Default::default() }), [3, 1]);
+        assert_eq!(
+            select(TicketQuery {
+                created_from: Some("2024-05-31".to_string()),
+                created_to: Some("2024-05-31".to_string()),
+                ..Default::default()
+            }),
+            [2]
+        );
+    }
+
+    #[test]
+    fn import_resolves_the_storage_location() {
+        let dir = crate::test_util::temp_dir("sqlite-import");
+        let data_dir = dir.to_string_lossy().into_owned();
+        let files = FsStore::new(&data_dir);
+        files.save_ticket(&ticket(5, FreshdeskStatus::Resolved, "2024-03-01T00:00:00Z", 1), None).unwrap();
+        files.save_ticket(&ticket(5, FreshdeskStatus::Resolved, "2024-03-01T00:00:00Z", 1), Some("fr")).unwrap();
+
+        let report = import_json_dir(&format!("sqlite:{}", data_dir)).unwrap();
+        assert_eq!((report.files, report.tickets, report.translations), (2, 1, 1));
+        let imported = SqliteStore::open_path(&dir.join(TICKETS_DB_FILE)).unwrap();
+        assert_eq!(imported.translation_langs(5).unwrap(), ["fr"]);
+        assert!(import_json_dir("memory:import").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
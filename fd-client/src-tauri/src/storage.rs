@@ -1,249 +1,293 @@
-use crate::models::{SyncState, Ticket};
-use std::fs;
-use std::path::Path;
+use crate::events::EventSink;
+use crate::fs_store::FsStore;
+use crate::models::{SyncFailure, SyncState, SyncStep, Ticket, TicketField, TicketFieldSchema, TicketFilter};
+use crate::history::HistoryStore;
+use crate::lang;
+use crate::search::SearchIndex;
+use crate::store::{StoreLocation, TicketStore, UnavailableStore};
+use crate::ticket_index::TicketIndex;
+use once_cell::sync::OnceCell;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
 
+/// 增量同步游标 (状态文档)
+const SYNC_STATE_FILE: &str = "sync_state.json";
+
+/// 同步失败记录 (位于数据目录)
+const SYNC_FAILURES_FILE: &str = "sync_failures.json";
+/// 同步下来的工单字段定义
+const TICKET_FIELDS_FILE: &str = "ticket_fields.json";
+
+pub use crate::fs_store::{IntegrityIssue, IntegrityReport, IssueAction, IssueKind, MigrationReport, RepairReport};
+
+/// 工单存储入口：按 `output_dir` 选择后端 (见 `StoreLocation`)，调用方无需关心具体存储
 pub struct Storage {
     data_dir: String,
+    location: StoreLocation,
+    backend: Arc<dyn TicketStore>,
+    /// 全文索引，首次用到时打开；内存存储为 None
+    search: OnceCell<Result<Option<SearchIndex>, String>>,
+    /// 版本历史，首次用到时取得 (同一数据目录进程内共享)
+    history: OnceCell<Result<Arc<HistoryStore>, String>>,
+    /// 接收不影响主操作的警告 (如索引更新失败)
+    sink: Option<Arc<dyn EventSink>>,
 }
 
 impl Storage {
     pub fn new(output_dir: &str) -> Self {
-        let tickets_path = Path::new(output_dir).join("tickets");
-        fs::create_dir_all(&tickets_path).unwrap_or_default();
+        let location = StoreLocation::parse(output_dir);
+        // 打开失败时各操作返回该错误
+        let backend = location
+            .open()
+            .unwrap_or_else(|e| Arc::new(UnavailableStore(format!("Storage {} is unavailable: {}", output_dir, e))));
         Storage {
-            data_dir: output_dir.to_string(),
+            data_dir: location.data_dir().unwrap_or(output_dir).to_string(),
+            location,
+            backend,
+            search: OnceCell::new(),
+            history: OnceCell::new(),
+            sink: None,
         }
     }
 
-    /// Get status name from status code
-    fn status_name(status: &crate::models::TicketStatus) -> &'static str {
-        use crate::models::TicketStatus::*;
-        match status {
-            PendingTrans => "open",
-            PendingReply => "pending",
-            PendingAudit => "audit",
-            Completed => "resolved",
-            _ => "unknown",
-        }
+    /// 警告发送到 `sink`；未设置时不输出
+    pub fn with_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.sink = Some(sink);
+        self
     }
 
-    /// Get filename for a ticket: {id}_{status}_{lang}.json or {id}_{status}.json
-    fn ticket_filename(ticket: &Ticket, lang: Option<&str>) -> String {
-        match lang {
-            Some(l) => format!(
-                "{}_{}_{}.json",
-                ticket.id,
-                Self::status_name(&ticket.status),
-                l
-            ),
-            None => format!("{}_{}.json", ticket.id, Self::status_name(&ticket.status)),
+    fn warn(&self, msg: &str) {
+        if let Some(sink) = &self.sink {
+            sink.log(msg);
         }
     }
 
-    /// Save ticket with status and optional language in filename
-    pub fn save_ticket(&self, ticket: &Ticket, lang: Option<&str>) -> Result<(), String> {
-        let tickets_dir = Path::new(&self.data_dir).join("tickets");
+    /// 数据目录 (附件、任务记录等文件的位置)；内存存储时为原始的 `output_dir`
+    pub fn data_dir(&self) -> &str {
+        &self.data_dir
+    }
 
-        // Remove any existing files for this ticket ID (different status) ONLY for the same language
-        self.remove_old_ticket_files(ticket.id, lang)?;
+    /// 内存存储没有数据目录，不下载附件
+    pub fn is_in_memory(&self) -> bool {
+        matches!(self.location, StoreLocation::Memory(_))
+    }
 
-        // Save with new filename
-        let path = tickets_dir.join(Self::ticket_filename(ticket, lang));
-        let json = serde_json::to_string_pretty(ticket).map_err(|e| e.to_string())?;
-        fs::write(path, json).map_err(|e| e.to_string())?;
-        Ok(())
+    /// 文件存储的工单文件索引 (进程内共享)；其他后端为 None
+    pub fn ticket_index(&self) -> Option<Arc<TicketIndex>> {
+        match &self.location {
+            StoreLocation::Filesystem(dir) => Some(TicketIndex::shared(dir)),
+            _ => None,
+        }
     }
 
-    /// Load a specific ticket by ID and language
-    pub fn load_ticket(
-        &self,
-        ticket_id: u64,
-        lang: Option<&str>,
-    ) -> Result<Option<Ticket>, String> {
-        let tickets_dir = Path::new(&self.data_dir).join("tickets");
-
-        // We don't know the status, so we have to search for the file
-        if let Ok(entries) = fs::read_dir(&tickets_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if let Some(filename) = path.file_stem().and_then(|s| s.to_str()) {
-                    let parts: Vec<&str> = filename.split('_').collect();
-                    if parts.len() < 2 {
-                        continue;
-                    }
-
-                    if parts[0] == ticket_id.to_string() {
-                        // Check language match
-                        let file_lang = if parts.len() > 2 {
-                            Some(parts[2])
-                        } else {
-                            None
-                        };
-
-                        if file_lang == lang {
-                            let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-                            let ticket = serde_json::from_str::<Ticket>(&content)
-                                .map_err(|e| e.to_string())?;
-                            return Ok(Some(ticket));
-                        }
-                    }
-                }
-            }
+    /// 数据目录下的全文索引；内存存储为 None
+    pub fn search_index(&self) -> Result<Option<&SearchIndex>, String> {
+        self.search
+            .get_or_init(|| match self.is_in_memory() {
+                true => Ok(None),
+                false => SearchIndex::open(&self.data_dir).map(Some),
+            })
+            .as_ref()
+            .map(Option::as_ref)
+            .map_err(Clone::clone)
+    }
+
+    /// 数据目录下的版本历史 (内存存储时为进程内的)；无法打开时返回该错误
+    pub fn history(&self) -> Result<&HistoryStore, String> {
+        self.history
+            .get_or_init(|| HistoryStore::shared(&self.data_dir, self.is_in_memory()))
+            .as_deref()
+            .map_err(Clone::clone)
+    }
+
+    /// 更新全文索引；失败时标记索引待重建 (下次搜索时重建) 并发出警告，不影响主操作。
+    /// 索引无法打开时跳过，搜索时返回该错误
+    fn update_search_index(&self, update: impl FnOnce(&SearchIndex) -> Result<(), String>) {
+        let Ok(Some(index)) = self.search_index() else {
+            return;
+        };
+        if let Err(e) = update(index) {
+            index.invalidate();
+            self.warn(&format!("⚠️ Search index update failed, it will be rebuilt on the next search: {}", e));
         }
-        Ok(None)
-    }
-
-    /// Remove any existing files for a ticket ID (different status) for a specific language
-    fn remove_old_ticket_files(&self, ticket_id: u64, lang: Option<&str>) -> Result<(), String> {
-        let tickets_dir = Path::new(&self.data_dir).join("tickets");
-
-        if let Ok(entries) = fs::read_dir(&tickets_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if let Some(filename) = path.file_stem().and_then(|s| s.to_str()) {
-                    // Check if filename starts with ticket ID
-                    if filename.starts_with(&format!("{}_", ticket_id)) {
-                        let parts: Vec<&str> = filename.split('_').collect();
-
-                        let file_lang = if parts.len() > 2 {
-                            Some(parts[2])
-                        } else {
-                            None
-                        };
-
-                        // Only remove if language matches
-                        if file_lang == lang {
-                            let _ = fs::remove_file(&path);
-                        }
-                    }
-                }
-            }
+    }
+
+    /// Save ticket with status and optional language (代码规范化为 BCP-47，如 `cn` → `zh-CN`)
+    pub fn save_ticket(&self, ticket: &Ticket, lang: Option<&str>) -> Result<(), String> {
+        let lang = lang.map(lang::normalize).transpose()?;
+        let lang = lang.as_deref();
+        self.backend.save_ticket(ticket, lang)?;
+        // 内容有变化时记录版本；失败不影响保存
+        if let Err(e) = self.history().and_then(|history| history.record(ticket, lang)) {
+            self.warn(&format!("⚠️ Ticket history was not recorded: {}", e));
         }
+        self.update_search_index(|index| index.index_ticket(ticket, lang));
         Ok(())
     }
 
-    /// Sync all ticket statuses - rename files to match their internal status
+    /// Load a specific ticket by ID and language
+    pub fn load_ticket(&self, ticket_id: u64, lang: Option<&str>) -> Result<Option<Ticket>, String> {
+        let lang = lang.map(lang::normalize).transpose()?;
+        self.backend.load_ticket(ticket_id, lang.as_deref())
+    }
+
+    pub fn list_tickets(&self, preferred_lang: Option<&str>) -> Result<Vec<Ticket>, String> {
+        let lang = preferred_lang.map(lang::normalize_lenient);
+        self.backend.list_tickets(lang.as_deref())
+    }
+
+    /// 不含描述与会话正文的工单 (列表分页用)；`filter` 只用于后端预先过滤，调用方仍需完整匹配
+    pub fn list_headers(&self, preferred_lang: Option<&str>, filter: &TicketFilter) -> Result<Vec<Ticket>, String> {
+        let lang = preferred_lang.map(lang::normalize_lenient);
+        self.backend.list_headers(lang.as_deref(), filter)
+    }
+
+    /// 删除工单原文及全部译文，返回工单是否存在
+    pub fn delete_ticket(&self, ticket_id: u64) -> Result<bool, String> {
+        let deleted = self.backend.delete_ticket(ticket_id)?;
+        self.update_search_index(|index| index.remove_ticket(ticket_id, None));
+        Ok(deleted)
+    }
+
+    pub fn translation_langs(&self, ticket_id: u64) -> Result<Vec<String>, String> {
+        self.backend.translation_langs(ticket_id)
+    }
+
+    pub fn delete_translation(&self, ticket_id: u64, lang: &str) -> Result<bool, String> {
+        let lang = lang::normalize(lang)?;
+        let deleted = self.backend.delete_translation(ticket_id, &lang)?;
+        self.update_search_index(|index| index.remove_ticket(ticket_id, Some(&lang)));
+        Ok(deleted)
+    }
+
+    /// Sync all ticket statuses - rename files to match their internal status.
+    /// 同时迁移旧格式文件 (见 `migrate_ticket_files`)，返回 (变更数, 总数)
     pub fn sync_all_statuses(&self) -> Result<(usize, usize), String> {
-        let tickets_dir = Path::new(&self.data_dir).join("tickets");
-        let mut synced = 0;
-        let mut total = 0;
-
-        if let Ok(entries) = fs::read_dir(&tickets_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) != Some("json") {
-                    continue;
-                }
-
-                total += 1;
-
-                // Read ticket to get current status
-                if let Ok(content) = fs::read_to_string(&path) {
-                    if let Ok(ticket) = serde_json::from_str::<Ticket>(&content) {
-                        let expected_filename = Self::ticket_filename(&ticket, None);
-                        let current_filename =
-                            path.file_name().and_then(|s| s.to_str()).unwrap_or("");
-
-                        // If filename doesn't match expected (for original files only), rename
-                        let has_lang = current_filename.split('_').count() > 2;
-                        if !has_lang && current_filename != expected_filename {
-                            let new_path = tickets_dir.join(&expected_filename);
-                            if fs::rename(&path, &new_path).is_ok() {
-                                synced += 1;
-                            }
-                        }
-                    }
-                }
-            }
+        let report = self.migrate_ticket_files()?;
+        Ok((report.rewritten.max(report.renamed), report.total))
+    }
+
+    /// 迁移旧格式的工单文件；只有文件存储需要，其他后端返回空结果
+    pub fn migrate_ticket_files(&self) -> Result<MigrationReport, String> {
+        match &self.location {
+            StoreLocation::Filesystem(dir) => FsStore::new(dir).migrate_ticket_files(),
+            _ => Ok(MigrationReport::default()),
         }
+    }
 
-        Ok((synced, total))
+    /// 启动检查：修复上次中断的写入 (见 `FsStore::repair_journal`)；其他后端自带事务，返回空结果
+    pub fn repair_journal(&self) -> Result<RepairReport, String> {
+        match &self.location {
+            StoreLocation::Filesystem(dir) => FsStore::new(dir).repair_journal(),
+            _ => Ok(RepairReport::default()),
+        }
     }
 
-    pub fn get_last_updated_at(&self) -> Option<String> {
-        let path = Path::new(&self.data_dir).join("sync_state.json");
-        if path.exists() {
-            if let Ok(content) = fs::read_to_string(path) {
-                if let Ok(state) = serde_json::from_str::<SyncState>(&content) {
-                    return state.last_updated_at;
-                }
-            }
+    /// 检查 (并可修复) 数据目录中的工单文件 (见 `FsStore::check_data_dir`)；其他后端返回空结果。
+    /// 修复后重建全文索引
+    pub fn check_data_dir(&self, repair: bool) -> Result<IntegrityReport, String> {
+        let report = match &self.location {
+            StoreLocation::Filesystem(dir) => FsStore::new(dir).check_data_dir(repair)?,
+            _ => IntegrityReport::default(),
+        };
+        if report.repaired() > 0 {
+            self.update_search_index(|index| index.rebuild(self).map(|_| ()));
         }
-        None
+        Ok(report)
+    }
+
+    pub fn get_last_updated_at(&self) -> Option<String> {
+        self.read_state::<SyncState>(SYNC_STATE_FILE)?.last_updated_at
     }
 
     pub fn update_last_sync_time(&self, dt: &str) -> Result<(), String> {
-        let path = Path::new(&self.data_dir).join("sync_state.json");
         let state = SyncState {
             last_updated_at: Some(dt.to_string()),
         };
-        let json = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
-        fs::write(path, json).map_err(|e| e.to_string())?;
-        Ok(())
+        self.write_state(SYNC_STATE_FILE, &state)
     }
 
-    pub fn list_tickets(&self, preferred_lang: Option<&str>) -> Vec<Ticket> {
-        let tickets_path = Path::new(&self.data_dir).join("tickets");
-        let mut tickets_map = std::collections::HashMap::new();
-
-        if let Ok(entries) = fs::read_dir(tickets_path) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                    if let Some(filename) = path.file_stem().and_then(|s| s.to_str()) {
-                        let parts: Vec<&str> = filename.split('_').collect();
-                        if parts.len() < 2 {
-                            continue;
-                        }
-
-                        if let Ok(id) = parts[0].parse::<u64>() {
-                            let file_lang = if parts.len() > 2 {
-                                Some(parts[2].to_string())
-                            } else {
-                                None
-                            };
-
-                            // Load ticket content
-                            if let Ok(content) = fs::read_to_string(&path) {
-                                if let Ok(ticket) = serde_json::from_str::<Ticket>(&content) {
-                                    let entry =
-                                        tickets_map.entry(id).or_insert_with(|| (None, Vec::new()));
-                                    if let Some(ref l) = file_lang {
-                                        entry.1.push(l.clone());
-                                    }
-                                    if file_lang.is_none() {
-                                        entry.0 = Some(ticket);
-                                    } else if file_lang.as_deref() == preferred_lang {
-                                        // If we're looking for a specific translation, hold onto it
-                                        // but we also want to track that it exists in entry.1
-                                        entry.0 = Some(ticket);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    /// 读取状态文档 (如同步断点)，不存在或无法解析时返回 None
+    pub fn read_state<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        let content = self.backend.read_state(name)?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn write_state<T: Serialize>(&self, name: &str, state: &T) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+        self.backend.write_state(name, &json)
+    }
+
+    /// 删除状态文档，返回是否存在
+    pub fn remove_state(&self, name: &str) -> Result<bool, String> {
+        self.backend.remove_state(name)
+    }
+
+    /// 同步失败记录，按工单 ID 排序
+    pub fn sync_failures(&self) -> Vec<SyncFailure> {
+        self.read_state(SYNC_FAILURES_FILE).unwrap_or_default()
+    }
+
+    /// 记录 (或更新) 工单的同步失败
+    pub fn record_sync_failure(&self, ticket_id: u64, step: SyncStep, error: &str) -> Result<(), String> {
+        let mut failures = self.sync_failures();
+        let attempts = match failures.iter().position(|f| f.ticket_id == ticket_id) {
+            Some(i) => failures.remove(i).attempts + 1,
+            None => 1,
+        };
+        failures.push(SyncFailure {
+            ticket_id,
+            step,
+            error: error.to_string(),
+            attempts,
+            failed_at: chrono::Utc::now().to_rfc3339(),
+        });
+        failures.sort_by_key(|f| f.ticket_id);
+        self.write_state(SYNC_FAILURES_FILE, &failures)
+    }
+
+    pub fn clear_sync_failure(&self, ticket_id: u64) -> Result<(), String> {
+        let mut failures = self.sync_failures();
+        let before = failures.len();
+        failures.retain(|f| f.ticket_id != ticket_id);
+        if failures.len() != before {
+            self.write_state(SYNC_FAILURES_FILE, &failures)?;
         }
+        Ok(())
+    }
+
+    /// 最近一次同步的工单字段定义，未同步过时为空
+    pub fn ticket_field_schema(&self) -> TicketFieldSchema {
+        self.read_state(TICKET_FIELDS_FILE).unwrap_or_default()
+    }
+
+    pub fn save_ticket_field_schema(&self, fields: Vec<TicketField>) -> Result<(), String> {
+        let schema = TicketFieldSchema {
+            fields,
+            synced_at: Some(chrono::Utc::now().to_rfc3339()),
+        };
+        self.write_state(TICKET_FIELDS_FILE, &schema)
+    }
 
-        let mut result = Vec::new();
-        for (_, (ticket_opt, langs)) in tickets_map {
-            if let Some(mut t) = ticket_opt {
-                t.available_langs = langs;
-
-                if let Some(pref) = preferred_lang {
-                    // If preferred_lang is set, only include if that language is available
-                    if t.available_langs.contains(&pref.to_string()) {
-                        result.push(t);
-                    }
-                } else {
-                    // Original view: include everything
-                    result.push(t);
-                }
+    /// 保存同步拉取到的工单 (清理 HTML) 并更新失败记录：会话拉取失败时标记为不完整，保存失败时记录后返回错误
+    pub fn save_synced_ticket(&self, ticket: &mut Ticket, fetch_error: Option<&str>) -> Result<(), String> {
+        ticket.sanitize_html();
+        ticket.conversations_incomplete = fetch_error.is_some();
+        if ticket.conversations_incomplete && ticket.conversations.is_empty() {
+            // 保留本地已有的会话，避免一次失败把它们清空
+            if let Ok(Some(existing)) = self.load_ticket(ticket.id, None) {
+                ticket.conversations = existing.conversations;
             }
         }
+        if let Err(e) = self.save_ticket(ticket, None) {
+            self.record_sync_failure(ticket.id, SyncStep::Save, &e)?;
+            return Err(e);
+        }
 
-        result.sort_by(|a, b| b.id.cmp(&a.id));
-        result
+        match fetch_error {
+            Some(e) => self.record_sync_failure(ticket.id, SyncStep::FetchConversations, e),
+            None => self.clear_sync_failure(ticket.id),
+        }
     }
 }
@@ -0,0 +1,332 @@
+use crate::fs_store::FsStore;
+use crate::models::{Ticket, TicketFilter};
+use crate::sqlite_store::SqliteStore;
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// 工单存储后端：工单原文、译文与状态 (同步游标、断点、失败记录等 JSON 文档)
+pub trait TicketStore: Send + Sync {
+    /// `lang` 为 None 时保存原文，否则保存该语言的译文
+    fn save_ticket(&self, ticket: &Ticket, lang: Option<&str>) -> Result<(), String>;
+
+    fn load_ticket(&self, ticket_id: u64, lang: Option<&str>) -> Result<Option<Ticket>, String>;
+
+    /// 不带语言时列出原文，带语言时只列出有该译文的工单 (内容为译文)；
+    /// 填充 available_langs，按 ID 倒序
+    fn list_tickets(&self, preferred_lang: Option<&str>) -> Result<Vec<Ticket>, String>;
+
+    /// 范围同 `list_tickets`，但不含描述与会话正文 (见 `Ticket::strip_bodies`)，用于列表分页；
+    /// 后端可先按 `filter` 中有索引的条件过滤，调用方仍需用 `filter.matches` 完整匹配
+    fn list_headers(&self, preferred_lang: Option<&str>, filter: &TicketFilter) -> Result<Vec<Ticket>, String>;
+
+    /// 删除工单原文及全部译文，返回工单是否存在
+    fn delete_ticket(&self, ticket_id: u64) -> Result<bool, String>;
+
+    fn translation_langs(&self, ticket_id: u64) -> Result<Vec<String>, String>;
+
+    fn delete_translation(&self, ticket_id: u64, lang: &str) -> Result<bool, String>;
+
+    /// 按名称读取状态文档 (JSON)，不存在时返回 None
+    fn read_state(&self, name: &str) -> Option<String>;
+
+    fn write_state(&self, name: &str, json: &str) -> Result<(), String>;
+
+    /// 删除状态文档，返回是否存在
+    fn remove_state(&self, name: &str) -> Result<bool, String>;
+}
+
+/// 由 `output_dir` 决定的存储位置：
+/// - `memory:` / `memory:<名称>`：进程内存储，同名的共享数据
+/// - `sqlite:<目录>`：目录下的 tickets.db
+/// - 以 `.db` / `.sqlite` 结尾的路径：该 SQLite 文件
+/// - 其他：JSON 文件目录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreLocation {
+    Filesystem(String),
+    Sqlite { data_dir: String, db_path: String },
+    Memory(String),
+}
+
+impl StoreLocation {
+    pub fn parse(output_dir: &str) -> Self {
+        let output_dir = output_dir.trim();
+        if let Some(name) = output_dir.strip_prefix("memory:") {
+            return StoreLocation::Memory(name.to_string());
+        }
+        if let Some(dir) = output_dir.strip_prefix("sqlite:") {
+            let db_path = Path::new(dir).join(crate::sqlite_store::TICKETS_DB_FILE);
+            return StoreLocation::Sqlite {
+                data_dir: dir.to_string(),
+                db_path: db_path.to_string_lossy().into_owned(),
+            };
+        }
+        let lower = output_dir.to_ascii_lowercase();
+        if lower.ends_with(".db") || lower.ends_with(".sqlite") {
+            let data_dir = Path::new(output_dir)
+                .parent()
+                .map(|p| p.to_string_lossy().into_owned())
+                .filter(|p| !p.is_empty())
+                .unwrap_or_else(|| ".".to_string());
+            return StoreLocation::Sqlite {
+                data_dir,
+                db_path: output_dir.to_string(),
+            };
+        }
+        StoreLocation::Filesystem(output_dir.to_string())
+    }
+
+    /// 数据目录 (附件等文件存放的位置)；内存存储没有数据目录
+    pub fn data_dir(&self) -> Option<&str> {
+        match self {
+            StoreLocation::Filesystem(dir) => Some(dir),
+            StoreLocation::Sqlite { data_dir, .. } => Some(data_dir),
+            StoreLocation::Memory(_) => None,
+        }
+    }
+
+    pub fn open(&self) -> Result<Arc<dyn TicketStore>, String> {
+        Ok(match self {
+            StoreLocation::Filesystem(dir) => Arc::new(FsStore::new(dir)),
+            StoreLocation::Sqlite { data_dir, db_path } => {
+                std::fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+                Arc::new(SqliteStore::open_path(Path::new(db_path))?)
+            }
+            StoreLocation::Memory(name) => MemoryStore::shared(name),
+        })
+    }
+}
+
+/// 同名内存存储在进程内共享
+static MEMORY_STORES: Lazy<Mutex<HashMap<String, Arc<MemoryStore>>>> = Lazy::new(Default::default);
+
+#[derive(Default)]
+struct MemoryData {
+    tickets: BTreeMap<u64, Ticket>,
+    translations: BTreeMap<(u64, String), Ticket>,
+    state: HashMap<String, String>,
+}
+
+/// 进程内存储 (不落盘)，用于测试与演练
+#[derive(Default)]
+pub struct MemoryStore {
+    data: Mutex<MemoryData>,
+}
+
+impl MemoryStore {
+    /// 取名为 `name` 的共享内存存储，不存在时创建
+    pub fn shared(name: &str) -> Arc<MemoryStore> {
+        MEMORY_STORES.lock().unwrap().entry(name.to_string()).or_default().clone()
+    }
+}
+
+impl TicketStore for MemoryStore {
+    fn save_ticket(&self, ticket: &Ticket, lang: Option<&str>) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        let mut ticket = ticket.clone();
+        ticket.available_langs.clear();
+        match lang {
+            Some(lang) => data.translations.insert((ticket.id, lang.to_string()), ticket),
+            None => data.tickets.insert(ticket.id, ticket),
+        };
+        Ok(())
+    }
+
+    fn load_ticket(&self, ticket_id: u64, lang: Option<&str>) -> Result<Option<Ticket>, String> {
+        let data = self.data.lock().unwrap();
+        Ok(match lang {
+            Some(lang) => data.translations.get(&(ticket_id, lang.to_string())).cloned(),
+            None => data.tickets.get(&ticket_id).cloned(),
+        })
+    }
+
+    fn list_tickets(&self, preferred_lang: Option<&str>) -> Result<Vec<Ticket>, String> {
+        let data = self.data.lock().unwrap();
+        let mut langs: HashMap<u64, Vec<String>> = HashMap::new();
+        for (id, lang) in data.translations.keys() {
+            langs.entry(*id).or_default().push(lang.clone());
+        }
+
+        let mut tickets: Vec<Ticket> = match preferred_lang {
+            Some(lang) => data
+                .translations
+                .iter()
+                .filter(|((_, l), _)| l == lang)
+                .map(|(_, t)| t.clone())
+                .collect(),
+            None => data.tickets.values().cloned().collect(),
+        };
+        for ticket in &mut tickets {
+            ticket.available_langs = langs.get(&ticket.id).cloned().unwrap_or_default();
+        }
+        tickets.sort_by_key(|t| std::cmp::Reverse(t.id));
+        Ok(tickets)
+    }
+
+    fn list_headers(&self, preferred_lang: Option<&str>, _: &TicketFilter) -> Result<Vec<Ticket>, String> {
+        let mut tickets = self.list_tickets(preferred_lang)?;
+        tickets.iter_mut().for_each(Ticket::strip_bodies);
+        Ok(tickets)
+    }
+
+    fn delete_ticket(&self, ticket_id: u64) -> Result<bool, String> {
+        let mut data = self.data.lock().unwrap();
+        let before = data.translations.len();
+        data.translations.retain(|(id, _), _| *id != ticket_id);
+        let had_translations = data.translations.len() != before;
+        Ok(data.tickets.remove(&ticket_id).is_some() || had_translations)
+    }
+
+    fn translation_langs(&self, ticket_id: u64) -> Result<Vec<String>, String> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .translations
+            .keys()
+            .filter(|(id, _)| *id == ticket_id)
+            .map(|(_, lang)| lang.clone())
+            .collect())
+    }
+
+    fn delete_translation(&self, ticket_id: u64, lang: &str) -> Result<bool, String> {
+        let mut data = self.data.lock().unwrap();
+        Ok(data.translations.remove(&(ticket_id, lang.to_string())).is_some())
+    }
+
+    fn read_state(&self, name: &str) -> Option<String> {
+        self.data.lock().unwrap().state.get(name).cloned()
+    }
+
+    fn write_state(&self, name: &str, json: &str) -> Result<(), String> {
+        self.data.lock().unwrap().state.insert(name.to_string(), json.to_string());
+        Ok(())
+    }
+
+    fn remove_state(&self, name: &str) -> Result<bool, String> {
+        Ok(self.data.lock().unwrap().state.remove(name).is_some())
+    }
+}
+
+/// 存储无法打开时的占位后端：状态文档读取为空，其余操作返回打开时的错误
+pub(crate) struct UnavailableStore(pub String);
+
+impl TicketStore for UnavailableStore {
+    fn save_ticket(&self, _: &Ticket, _: Option<&str>) -> Result<(), String> {
+        Err(self.0.clone())
+    }
+
+    fn load_ticket(&self, _: u64, _: Option<&str>) -> Result<Option<Ticket>, String> {
+        Err(self.0.clone())
+    }
+
+    fn list_tickets(&self, _: Option<&str>) -> Result<Vec<Ticket>, String> {
+        Err(self.0.clone())
+    }
+
+    fn list_headers(&self, _: Option<&str>, _: &TicketFilter) -> Result<Vec<Ticket>, String> {
+        Err(self.0.clone())
+    }
+
+    fn delete_ticket(&self, _: u64) -> Result<bool, String> {
+        Err(self.0.clone())
+    }
+
+    fn translation_langs(&self, _: u64) -> Result<Vec<String>, String> {
+        Err(self.0.clone())
+    }
+
+    fn delete_translation(&self, _: u64, _: &str) -> Result<bool, String> {
+        Err(self.0.clone())
+    }
+
+    fn read_state(&self, _: &str) -> Option<String> {
+        None
+    }
+
+    fn write_state(&self, _: &str, _: &str) -> Result<(), String> {
+        Err(self.0.clone())
+    }
+
+    fn remove_state(&self, _: &str) -> Result<bool, String> {
+        Err(self.0.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+
+    fn ticket(id: u64, subject: &str) -> Ticket {
+        Ticket { subject: Some(subject.to_string()), ..crate::test_util::ticket(id, crate::models::FreshdeskStatus::Open) }
+    }
+
+    #[test]
+    fn output_dir_selects_the_backend() {
+        assert_eq!(StoreLocation::parse("memory:"), StoreLocation::Memory(String::new()));
+        assert_eq!(StoreLocation::parse(" /data "), StoreLocation::Filesystem("/data".to_string()));
+        assert_eq!(
+            StoreLocation::parse("/data/tickets.DB"),
+            StoreLocation::Sqlite { data_dir: "/data".to_string(), db_path: "/data/tickets.DB".to_string() }
+        );
+        assert_eq!(
+            StoreLocation::parse("local.sqlite"),
+            StoreLocation::Sqlite { data_dir: ".".to_string(), db_path: "local.sqlite".to_string() }
+        );
+        let StoreLocation::Sqlite { data_dir, db_path } = StoreLocation::parse("sqlite:/data") else {
+            panic!("expected a SQLite location");
+        };
+        assert_eq!(data_dir, "/data");
+        assert!(db_path.ends_with(crate::sqlite_store::TICKETS_DB_FILE));
+        assert_eq!(StoreLocation::parse("memory:x").data_dir(), None);
+    }
+
+    #[test]
+    fn memory_store_is_shared_by_name() {
+        let first = Storage::new("memory:store-shared");
+        first.save_ticket(&ticket(1, "hello"), None).unwrap();
+        first.save_ticket(&ticket(1, "hallo"), Some("de")).unwrap();
+        first.write_state("cursor", &"2024-01-01").unwrap();
+
+        let second = Storage::new("memory:store-shared");
+        let listed = second.list_tickets(None).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].available_langs, ["de"]);
+        assert_eq!(second.list_tickets(Some("de")).unwrap()[0].subject.as_deref(), Some("hallo"));
+        assert_eq!(second.read_state::<String>("cursor").as_deref(), Some("2024-01-01"));
+
+        let other = Storage::new("memory:store-other");
+        assert!(other.list_tickets(None).unwrap().is_empty());
+        assert!(other.read_state::<String>("cursor").is_none());
+    }
+
+    #[test]
+    fn memory_store_deletes_translations_with_the_ticket() {
+        let store = MemoryStore::default();
+        store.save_ticket(&ticket(2, "a"), None).unwrap();
+        store.save_ticket(&ticket(3, "b"), None).unwrap();
+        store.save_ticket(&ticket(2, "a"), Some("fr")).unwrap();
+        assert_eq!(store.translation_langs(2).unwrap(), ["fr"]);
+        assert_eq!(store.list_tickets(None).unwrap().iter().map(|t| t.id).collect::<Vec<_>>(), [3, 2]);
+
+        assert!(store.delete_ticket(2).unwrap());
+        assert!(!store.delete_ticket(2).unwrap());
+        assert!(store.translation_langs(2).unwrap().is_empty());
+        assert!(store.load_ticket(2, Some("fr")).unwrap().is_none());
+    }
+
+    #[test]
+    fn unavailable_storage_reports_the_open_error() {
+        let dir = crate::test_util::temp_dir("store-file");
+        let file = dir.join("tickets");
+        std::fs::write(&file, "not a directory").unwrap();
+        let storage = Storage::new(&format!("sqlite:{}", file.display()));
+
+        let err = storage.list_tickets(None).unwrap_err();
+        assert!(err.contains("is unavailable"), "{}", err);
+        assert!(storage.save_ticket(&ticket(1, "x"), None).is_err());
+        assert!(storage.read_state::<String>("cursor").is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,497 @@
+use crate::api::{FreshdeskClient, SyncRange};
+use crate::attachments::{AttachmentPolicy, AttachmentStore};
+use crate::directory::Directory;
+use crate::events::EventSink;
+use crate::history::HistoryRetention;
+use crate::jobs::{SyncJob, SyncJobs};
+use crate::models::SyncStep;
+use crate::settings::Settings;
+use crate::storage::Storage;
+use chrono::{NaiveDate, Utc};
+use futures_util::StreamExt;
+use std::future::Future;
+use std::sync::Arc;
+
+/// 一次同步的参数
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    pub full_sync: bool,
+    /// 仅全量同步使用
+    pub range: SyncRange,
+    /// 并发拉取会话的请求数
+    pub concurrency: usize,
+    /// 附件下载限制；上限为 0 时不下载
+    pub attachments: AttachmentPolicy,
+    /// 版本历史的保留策略，每次同步前清理
+    pub history: HistoryRetention,
+}
+
+/// 会话并发数上限 (请求仍受共享限流器约束)
+pub const MAX_SYNC_CONCURRENCY: usize = 16;
+
+impl SyncOptions {
+    /// 从设置构造；start/end 为空时分别取 sync_start_date 与今天
+    pub fn from_settings(
+        settings: &Settings,
+        full_sync: bool,
+        start_date: Option<&str>,
+        end_date: Option<&str>,
+        window_days: Option<u32>,
+    ) -> Result<Self, String> {
+        let start = non_empty(start_date).unwrap_or(&settings.sync_start_date);
+        let end = non_empty(end_date).or_else(|| non_empty(Some(&settings.sync_end_date)));
+        let today = Utc::now().date_naive();
+
+        let start = parse_sync_date(start, false)?;
+        let open_end = end.is_none();
+        let end = match end {
+            Some(e) => parse_sync_date(e, true)?.min(today),
+            None => today,
+        };
+        if start > end {
+            return Err(format!("Sync start date {} is after end date {}", start, end));
+        }
+
+        Ok(SyncOptions {
+            full_sync,
+            range: SyncRange {
+                start,
+                end,
+                window_days: window_days.unwrap_or(settings.sync_window_days).max(1),
+                open_end,
+            },
+            concurrency: (settings.sync_concurrency as usize).clamp(1, MAX_SYNC_CONCURRENCY),
+            attachments: AttachmentPolicy::from_settings(settings),
+            history: HistoryRetention::from_settings(settings),
+        })
+    }
+
+    /// 启用附件下载时打开数据目录下的附件存储 (内存存储不下载附件)
+    pub fn attachment_store(&self, storage: &Storage) -> Option<AttachmentStore> {
+        (self.attachments.is_enabled() && !storage.is_in_memory())
+            .then(|| AttachmentStore::new(storage.data_dir(), self.attachments.clone()))
+    }
+}
+
+fn non_empty(s: Option<&str>) -> Option<&str> {
+    s.map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// 解析 "YYYY-MM-DD" 或 "YYYY-MM" (界面的月份选择器)；
+/// 只有月份时，作为起始日期取当月 1 日，作为截止日期取当月最后一天
+pub fn parse_sync_date(s: &str, end_of_month: bool) -> Result<NaiveDate, String> {
+    let s = s.trim();
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(d);
+    }
+    let first = NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid sync date: {} (expected YYYY-MM-DD or YYYY-MM)", s))?;
+    if !end_of_month {
+        return Ok(first);
+    }
+    let next_month = first
+        .checked_add_months(chrono::Months::new(1))
+        .ok_or_else(|| format!("Invalid sync date: {}", s))?;
+    Ok(next_month.pred_opt().unwrap_or(first))
+}
+
+/// 以任务形式运行同步：登记任务 (同一数据目录已有任务时拒绝)，
+/// 开始与结束时发送 `sync-job` 事件
+pub async fn run_sync_job(
+    jobs: &SyncJobs,
+    sink: &dyn EventSink,
+    client: &FreshdeskClient,
+    storage: &Storage,
+    options: &SyncOptions,
+) -> Result<String, String> {
+    run_job(jobs, sink, storage, options.full_sync, |job| async move {
+        sync_tickets(sink, client, storage, options, &job).await
+    })
+    .await
+}
+
+/// 以任务形式运行 `retry_failed_sync`，与普通同步共用同一数据目录的互斥
+pub async fn run_retry_job(
+    jobs: &SyncJobs,
+    sink: &dyn EventSink,
+    client: &FreshdeskClient,
+    storage: &Storage,
+    options: &SyncOptions,
+) -> Result<String, String> {
+    run_job(jobs, sink, storage, false, |job| async move {
+        retry_failed_sync(sink, client, storage, options, &job).await
+    })
+    .await
+}
+
+async fn run_job<F, Fut>(jobs: &SyncJobs, sink: &dyn EventSink, storage: &Storage, full_sync: bool, run: F) -> Result<String, String>
+where
+    F: FnOnce(Arc<SyncJob>) -> Fut,
+    Fut: Future<Output = Result<String, String>>,
+{
+    let job = jobs.start(storage.data_dir(), full_sync)?;
+    sink.log(&format!("🆔 Sync job {}", job.id()));
+    sink.event("sync-job", serde_json::json!(job.status()));
+
+    let result = run(job.clone()).await;
+
+    jobs.finish(&job, &result);
+    if let Err(e) = &result {
+        sink.log(&format!("⏹️ Sync job {} ended: {}", job.id(), e));
+    }
+    sink.event("sync-job", serde_json::json!(job.status()));
+    result
+}
+
+/// 同步工单到本地存储 (全量或增量)，返回给界面展示的摘要；
+/// 在每页 / 每张工单之间响应任务的暂停与取消
+pub async fn sync_tickets(
+    sink: &dyn EventSink,
+    client: &FreshdeskClient,
+    storage: &Storage,
+    options: &SyncOptions,
+    job: &SyncJob,
+) -> Result<String, String> {
+    let pending = storage.sync_failures().len();
+    if pending > 0 {
+        sink.log(&format!("ℹ️ {} tickets failed in earlier syncs; use retry_failed_sync to reprocess them", pending));
+    }
+    refresh_ticket_fields(sink, client, storage).await;
+    refresh_directory(sink, client, storage).await;
+    prune_history(sink, storage, &options.history);
+    let attachments = options.attachment_store(storage);
+
+    if options.full_sync {
+        // Full sync: fetch ALL tickets with immediate save
+        sink.log("🔄 Full sync - fetching and saving tickets immediately");
+        sink.progress(serde_json::json!({"phase": "fetching", "current": 0, "total": 100}));
+
+        let report = client
+            .fetch_and_save_all_tickets(sink, storage, &options.range, job, options.concurrency, attachments.as_ref())
+            .await?;
+
+        sink.log(&format!("✅ Full sync complete! Saved {} tickets.", report.saved));
+        sink.progress(serde_json::json!({"phase": "complete", "current": 100, "total": 100, "report": report}));
+
+        let summary = if !report.truncated_windows.is_empty() {
+            format!(
+                "Synced {} tickets ({} windows split, {} windows incomplete)",
+                report.saved,
+                report.split_windows,
+                report.truncated_windows.len()
+            )
+        } else {
+            format!("Synced {} tickets ({} windows split)", report.saved, report.split_windows)
+        };
+        Ok(with_failure_summary(summary, storage, sink))
+    } else {
+        // Incremental sync
+        let last_sync = storage.get_last_updated_at();
+        sink.log(&format!("📥 Incremental sync. Last: {:?}", last_sync));
+        sink.progress(serde_json::json!({"phase": "fetching", "current": 25, "total": 100}));
+
+        let tickets = match client.list_tickets_since(last_sync.as_deref(), sink).await {
+            Ok(t) => {
+                sink.log(&format!("   ✓ {} new/updated tickets", t.len()));
+                t
+            }
+            Err(e) => {
+                sink.log(&format!("   ❌ {}", e));
+                return Err(e);
+            }
+        };
+
+        let count = tickets.len();
+        if count == 0 {
+            sink.log("✅ No tickets to process.");
+            sink.progress(serde_json::json!({"phase": "complete", "current": 100, "total": 100}));
+            return Ok("No tickets".to_string());
+        }
+
+        sink.log(&format!("⚙️ Processing {} tickets ({} concurrent)...", count, options.concurrency));
+        let mut cursor = SyncCursor::default();
+        let mut processed = 0;
+        let mut saved = 0;
+
+        // 会话并发拉取，按列表顺序逐张保存；进度计数保持递增
+        let mut fetches = client.with_conversations(tickets, options.concurrency, attachments.as_ref());
+        while let Some((mut ticket, fetch_error)) = fetches.next().await {
+            // 取消时不更新 last sync time，下次增量同步会重新拉取
+            job.checkpoint().await?;
+            processed += 1;
+
+            let progress_pct = 50 + (processed as f32 / count as f32 * 50.0) as i32;
+            sink.progress(serde_json::json!({
+                "phase": "processing",
+                "current": progress_pct,
+                "total": 100,
+                "ticketId": ticket.id,
+                "processed": processed,
+                "totalTickets": count
+            }));
+
+            if processed % 100 == 0 || processed == count {
+                sink.log(&format!("⏳ {}/{} ({}%)", processed, count, progress_pct));
+            }
+
+            if let Some(e) = &fetch_error {
+                sink.log(&format!("   ⚠️ #{} saved with incomplete conversations: {}", ticket.id, e));
+            }
+            let updated_at = ticket.updated_at.clone();
+            match storage.save_synced_ticket(&mut ticket, fetch_error.as_deref()) {
+                Ok(()) => {
+                    saved += 1;
+                    cursor.passed(updated_at);
+                }
+                Err(e) => {
+                    sink.log(&format!("   ❌ #{} save failed: {}", ticket.id, e));
+                    // 已记入失败记录的由 retry 重新处理，否则游标不越过它，下次增量同步重新拉取
+                    if storage.sync_failures().iter().any(|f| f.ticket_id == ticket.id) {
+                        cursor.passed(updated_at);
+                    } else {
+                        cursor.held_back(updated_at);
+                    }
+                }
+            }
+        }
+
+        if let Some(updated_at) = cursor.value() {
+            let _ = storage.update_last_sync_time(&updated_at);
+        }
+
+        sink.log(&format!("✅ Saved {}/{} tickets.", saved, count));
+        sink.progress(serde_json::json!({"phase": "complete", "current": 100, "total": 100}));
+
+        Ok(with_failure_summary(format!("Synced {} tickets", saved), storage, sink))
+    }
+}
+
+/// 更新本地的工单字段定义；失败时沿用上次的定义，不影响工单同步
+async fn refresh_ticket_fields(sink: &dyn EventSink, client: &FreshdeskClient, storage: &Storage) {
+    match client.list_ticket_fields().await {
+        Ok(fields) => {
+            let custom = fields.iter().filter(|f| !f.default).count();
+            match storage.save_ticket_field_schema(fields) {
+                Ok(()) => sink.log(&format!("   ✓ Ticket fields updated ({} custom)", custom)),
+                Err(e) => sink.log(&format!("   ⚠️ Could not save ticket fields: {}", e)),
+            }
+        }
+        Err(e) => sink.log(&format!("   ⚠️ Could not fetch ticket fields: {}", e)),
+    }
+}
+
+/// 增量刷新联系人 (按上次的 updated_at)，整体刷新公司与客服；
+/// 某一类失败时保留其缓存，不影响工单同步
+async fn refresh_directory(sink: &dyn EventSink, client: &FreshdeskClient, storage: &Storage) {
+    let mut directory = Directory::load(storage);
+
+    match client.list_contacts(directory.contacts_updated_at.as_deref()).await {
+        Ok(contacts) => {
+            sink.log(&format!("   ✓ {} contacts updated", contacts.len()));
+            directory.merge_contacts(contacts);
+        }
+        Err(e) => sink.log(&format!("   ⚠️ Could not refresh contacts: {}", e)),
+    }
+    match client.list_companies().await {
+        Ok(companies) => directory.replace_companies(companies),
+        Err(e) => sink.log(&format!("   ⚠️ Could not refresh companies: {}", e)),
+    }
+    match client.list_agents().await {
+        Ok(agents) => directory.replace_agents(agents),
+        Err(e) => sink.log(&format!("   ⚠️ Could not refresh agents: {}", e)),
+    }
+
+    directory.refreshed_at = Some(Utc::now().to_rfc3339());
+    if let Err(e) = directory.save(storage) {
+        sink.log(&format!("   ⚠️ Could not save the contact directory: {}", e));
+    }
+}
+
+/// 按保留策略清理旧的工单版本；失败只记录日志
+fn prune_history(sink: &dyn EventSink, storage: &Storage, retention: &HistoryRetention) {
+    match storage.history().and_then(|history| history.prune(retention)) {
+        Ok(0) => {}
+        Ok(n) => sink.log(&format!("   🗑️ Pruned {} old ticket versions", n)),
+        Err(e) => sink.log(&format!("   ⚠️ Could not prune ticket history: {}", e)),
+    }
+}
+
+/// 只重新处理失败记录中的工单：本地已有的补拉会话，其余从 Freshdesk 重新获取
+pub async fn retry_failed_sync(
+    sink: &dyn EventSink,
+    client: &FreshdeskClient,
+    storage: &Storage,
+    options: &SyncOptions,
+    job: &SyncJob,
+) -> Result<String, String> {
+    let failures = storage.sync_failures();
+    if failures.is_empty() {
+        sink.log("✅ No failed tickets to retry.");
+        return Ok("No failed tickets".to_string());
+    }
+    sink.log(&format!("🔁 Retrying {} failed tickets...", failures.len()));
+
+    let mut tickets = Vec::new();
+    for failure in &failures {
+        job.checkpoint().await?;
+        let local = match failure.step {
+            SyncStep::FetchConversations => match storage.load_ticket(failure.ticket_id, None) {
+                Ok(local) => local,
+                Err(e) => {
+                    // 本地文件无法读取时保留失败记录，继续处理其他工单
+                    sink.log(&format!("   ❌ #{} could not load the local copy: {}", failure.ticket_id, e));
+                    storage.record_sync_failure(failure.ticket_id, failure.step, &e)?;
+                    continue;
+                }
+            },
+            _ => None,
+        };
+        match local {
+            Some(t) => tickets.push(t),
+            None => match client.get_ticket(failure.ticket_id).await {
+                Ok(t) => tickets.push(t),
+                Err(e) => {
+                    sink.log(&format!("   ❌ #{} fetch failed: {}", failure.ticket_id, e));
+                    storage.record_sync_failure(failure.ticket_id, SyncStep::FetchTicket, &e)?;
+                }
+            },
+        }
+    }
+
+    let mut recovered = 0;
+    let attachments = options.attachment_store(storage);
+    let mut fetches = client.with_conversations(tickets, options.concurrency, attachments.as_ref());
+    while let Some((mut ticket, fetch_error)) = fetches.next().await {
+        job.checkpoint().await?;
+        if let Some(e) = &fetch_error {
+            sink.log(&format!("   ⚠️ #{} still incomplete: {}", ticket.id, e));
+        }
+        match storage.save_synced_ticket(&mut ticket, fetch_error.as_deref()) {
+            Ok(()) if fetch_error.is_none() => recovered += 1,
+            Ok(()) => {}
+            Err(e) => sink.log(&format!("   ❌ #{} save failed: {}", ticket.id, e)),
+        }
+    }
+
+    sink.log(&format!("✅ {}/{} failed tickets recovered.", recovered, failures.len()));
+    Ok(with_failure_summary(
+        format!("Recovered {}/{} failed tickets", recovered, failures.len()),
+        storage,
+        sink,
+    ))
+}
+
+/// 增量同步的新游标：已处理工单中最新的 updated_at，但不越过保存失败且未记入失败记录的工单
+/// (`updated_since` 包含边界，下次会重新拉取它)
+#[derive(Debug, Default)]
+struct SyncCursor {
+    latest: Option<String>,
+    earliest_unsaved: Option<String>,
+}
+
+impl SyncCursor {
+    fn passed(&mut self, updated_at: Option<String>) {
+        if updated_at > self.latest {
+            self.latest = updated_at;
+        }
+    }
+
+    fn held_back(&mut self, updated_at: Option<String>) {
+        let Some(updated_at) = updated_at else {
+            return;
+        };
+        if self.earliest_unsaved.as_ref().is_none_or(|t| updated_at < *t) {
+            self.earliest_unsaved = Some(updated_at);
+        }
+    }
+
+    fn value(&self) -> Option<String> {
+        match (&self.latest, &self.earliest_unsaved) {
+            (Some(latest), Some(unsaved)) => Some(latest.min(unsaved).clone()),
+            (latest, None) => latest.clone(),
+            (None, Some(_)) => None,
+        }
+    }
+}
+
+/// 在摘要后列出仍未解决的失败工单
+fn with_failure_summary(summary: String, storage: &Storage, sink: &dyn EventSink) -> String {
+    const LISTED: usize = 10;
+    let failures = storage.sync_failures();
+    if failures.is_empty() {
+        return summary;
+    }
+
+    for f in &failures {
+        sink.log(&format!("   ✗ #{} [{}] {} (attempts: {})", f.ticket_id, f.step, f.error, f.attempts));
+    }
+    let mut listed: Vec<String> = failures
+        .iter()
+        .take(LISTED)
+        .map(|f| format!("#{} ({})", f.ticket_id, f.step))
+        .collect();
+    if failures.len() > LISTED {
+        listed.push(format!("and {} more", failures.len() - LISTED));
+    }
+    format!("{}; {} failed: {}", summary, failures.len(), listed.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::SyncJobs;
+    use crate::models::SyncFailure;
+
+    fn time(s: &str) -> Option<String> {
+        Some(s.to_string())
+    }
+
+    #[test]
+    fn cursor_advances_to_the_latest_saved_ticket() {
+        let mut cursor = SyncCursor::default();
+        cursor.passed(time("2024-05-02T10:00:00Z"));
+        cursor.passed(time("2024-05-03T08:00:00Z"));
+        cursor.passed(time("2024-05-01T00:00:00Z"));
+        cursor.passed(None);
+        assert_eq!(cursor.value(), time("2024-05-03T08:00:00Z"));
+    }
+
+    #[test]
+    fn cursor_stops_at_unrecorded_save_failures() {
+        let mut cursor = SyncCursor::default();
+        cursor.passed(time("2024-05-03T08:00:00Z"));
+        cursor.held_back(time("2024-05-02T12:00:00Z"));
+        cursor.held_back(time("2024-05-02T18:00:00Z"));
+        assert_eq!(cursor.value(), time("2024-05-02T12:00:00Z"));
+
+        let mut only_failed = SyncCursor::default();
+        only_failed.held_back(time("2024-05-02T12:00:00Z"));
+        assert_eq!(only_failed.value(), None);
+    }
+
+    #[tokio::test]
+    async fn retry_keeps_tickets_whose_local_copy_cannot_be_read() {
+        let dir = crate::test_util::temp_dir("sync-retry");
+        std::fs::create_dir_all(dir.join("tickets")).unwrap();
+        std::fs::write(dir.join("tickets").join("7_open.json"), "{ not json").unwrap();
+        let data_dir = dir.to_string_lossy().into_owned();
+
+        let storage = Storage::new(&data_dir);
+        storage.record_sync_failure(7, SyncStep::FetchConversations, "timeout").unwrap();
+        let options = SyncOptions::from_settings(&Settings::default(), false, None, None, None).unwrap();
+        let jobs = SyncJobs::default();
+        let job = jobs.start(&data_dir, false).unwrap();
+        let client = FreshdeskClient::new("acme", "key");
+
+        let summary = retry_failed_sync(&crate::events::ConsoleSink { quiet_progress: true }, &client, &storage, &options, &job)
+            .await
+            .unwrap();
+        assert!(summary.starts_with("Recovered 0/1"), "{}", summary);
+        let failures: Vec<SyncFailure> = storage.sync_failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].step, SyncStep::FetchConversations);
+        assert_eq!(failures[0].attempts, 2);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
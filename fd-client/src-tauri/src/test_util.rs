@@ -0,0 +1,17 @@
+//! 各模块测试共用的工单与临时目录
+
+use crate::models::{FreshdeskStatus, Ticket};
+use std::path::PathBuf;
+
+/// 主题为 `Ticket {id}` 的工单；其余字段由调用方用结构体更新语法补充
+pub fn ticket(id: u64, status: FreshdeskStatus) -> Ticket {
+    Ticket { id, status, subject: Some(format!("Ticket {}", id)), ..Default::default() }
+}
+
+/// 本进程独有的空临时目录 (`fd-{name}-{pid}`)，已有的同名目录先删除
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fd-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,392 @@
+use crate::events::EventSink;
+use crate::lang;
+use crate::models::{FreshdeskStatus, Ticket};
+use once_cell::sync::Lazy;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockWriteGuard};
+use std::time::{Duration, SystemTime};
+
+/// 检查外部修改 (其他进程、手工编辑) 的默认间隔 (秒)
+pub const DEFAULT_WATCH_INTERVAL_SECS: u32 = 2;
+
+/// 扫描期间本进程有写入时重新扫描的次数，之后持锁扫描
+const SCAN_ATTEMPTS: usize = 3;
+
+/// 进程内每个 tickets 目录一份索引
+static INDEXES: Lazy<Mutex<HashMap<PathBuf, Arc<TicketIndex>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// (工单 ID, 语言)；原文的语言为 None
+type IndexKey = (u64, Option<String>);
+
+/// 索引中的一个工单文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexEntry {
+    pub path: PathBuf,
+    /// 文件名中的状态
+    pub status: Option<FreshdeskStatus>,
+    pub modified: Option<SystemTime>,
+    pub len: u64,
+}
+
+impl IndexEntry {
+    fn stat(path: &Path) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        let stem = path.file_stem()?.to_str()?;
+        Some(IndexEntry {
+            path: path.to_path_buf(),
+            status: stem.split('_').nth(1).and_then(FreshdeskStatus::from_name),
+            modified: meta.modified().ok(),
+            len: meta.len(),
+        })
+    }
+}
+
+/// `{id}_{status}[_{lang}].json` → (ID, 语言)；语言为规范化的 BCP-47 代码
+pub fn parse_ticket_filename(path: &Path) -> Option<IndexKey> {
+    if path.extension()?.to_str()? != "json" {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    let (id, _) = stem.split_once('_')?;
+    let lang = stem.splitn(3, '_').nth(2).map(lang::normalize_lenient);
+    Some((id.parse().ok()?, lang))
+}
+
+/// 工单文件索引：ID 与语言 → 文件路径与元数据。首次使用时扫描一次 tickets 目录，
+/// 本进程的写入随时更新，外部修改由 `watch` 定期检查
+pub struct TicketIndex {
+    tickets_dir: PathBuf,
+    entries: RwLock<HashMap<IndexKey, IndexEntry>>,
+    built: AtomicBool,
+    /// 本进程对索引的修改次数 (`record`、`forget`)；不持锁扫描时据此判断扫描结果是否已过时
+    writes: AtomicU64,
+    /// 列表用的工单头部 (不含正文)：路径 → (读取时的文件元数据, 头部)
+    headers: Mutex<HashMap<PathBuf, (IndexEntry, Ticket)>>,
+    /// 正在运行的后台检查的停止标志
+    watcher: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+impl TicketIndex {
+    /// 数据目录对应的索引 (进程内共享)
+    pub fn shared(data_dir: &str) -> Arc<Self> {
+        let tickets_dir = Path::new(data_dir.trim_end_matches(['/', '\\'])).join("tickets");
+        let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+        indexes
+            .entry(tickets_dir.clone())
+            .or_insert_with(|| {
+                Arc::new(TicketIndex {
+                    tickets_dir,
+                    entries: RwLock::new(HashMap::new()),
+                    built: AtomicBool::new(false),
+                    writes: AtomicU64::new(0),
+                    headers: Mutex::new(HashMap::new()),
+                    watcher: Mutex::new(None),
+                })
+            })
+            .clone()
+    }
+
+    /// 扫描 tickets 目录；同一工单同一语言有多个文件时取最新修改的
+    fn scan(&self) -> HashMap<IndexKey, IndexEntry> {
+        let mut entries: HashMap<IndexKey, IndexEntry> = HashMap::new();
+        let Ok(dir) = fs::read_dir(&self.tickets_dir) else {
+            return entries;
+        };
+        for path in dir.flatten().map(|e| e.path()) {
+            let (Some(key), Some(entry)) = (parse_ticket_filename(&path), IndexEntry::stat(&path)) else {
+                continue;
+            };
+            match entries.get(&key) {
+                Some(existing) if existing.modified >= entry.modified => {}
+                _ => {
+                    entries.insert(key, entry);
+                }
+            }
+        }
+        entries
+    }
+
+    /// 不持锁扫描，再持写锁用扫描结果更新索引；扫描期间本进程有写入 (结果可能缺少该写入) 时重试
+    fn scan_and_apply<T>(&self, apply: impl Fn(&mut HashMap<IndexKey, IndexEntry>, HashMap<IndexKey, IndexEntry>) -> T) -> T {
+        for _ in 0..SCAN_ATTEMPTS {
+            let writes = self.writes.load(Ordering::SeqCst);
+            let scanned = self.scan();
+            let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+            if self.writes.load(Ordering::SeqCst) == writes {
+                return apply(&mut entries, scanned);
+            }
+        }
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let scanned = self.scan();
+        apply(&mut entries, scanned)
+    }
+
+    /// 尚未建立 (或已失效) 时扫描
+    fn ensure_built(&self) {
+        if self.built.load(Ordering::SeqCst) {
+            return;
+        }
+        self.scan_and_apply(|entries, scanned| {
+            if !self.built.load(Ordering::SeqCst) {
+                *entries = scanned;
+                self.built.store(true, Ordering::SeqCst);
+            }
+        });
+    }
+
+    /// 写锁；尚未建立时先扫描
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<IndexKey, IndexEntry>> {
+        self.ensure_built();
+        self.entries.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&HashMap<IndexKey, IndexEntry>) -> T) -> T {
+        self.ensure_built();
+        f(&self.entries.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// 下次使用时重新扫描 (批量修改文件后调用)
+    pub fn invalidate(&self) {
+        self.built.store(false, Ordering::SeqCst);
+    }
+
+    pub fn get(&self, ticket_id: u64, lang: Option<&str>) -> Option<IndexEntry> {
+        self.read(|entries| entries.get(&(ticket_id, lang.map(str::to_string))).cloned())
+    }
+
+    /// 某张工单的全部文件 (原文与译文)：(语言, 路径)
+    pub fn paths(&self, ticket_id: u64) -> Vec<(Option<String>, PathBuf)> {
+        self.read(|entries| {
+            entries
+                .iter()
+                .filter(|((id, _), _)| *id == ticket_id)
+                .map(|((_, lang), entry)| (lang.clone(), entry.path.clone()))
+                .collect()
+        })
+    }
+
+    /// 某张工单已有的译文语言
+    pub fn translation_langs(&self, ticket_id: u64) -> Vec<String> {
+        let mut langs: Vec<String> = self.read(|entries| {
+            entries
+                .keys()
+                .filter(|(id, _)| *id == ticket_id)
+                .filter_map(|(_, lang)| lang.clone())
+                .collect()
+        });
+        langs.sort();
+        langs
+    }
+
+    /// 某语言 (None 为原文) 的全部文件
+    pub fn files(&self, lang: Option<&str>) -> Vec<(u64, IndexEntry)> {
+        self.read(|entries| {
+            entries
+                .iter()
+                .filter(|((_, l), _)| l.as_deref() == lang)
+                .map(|((id, _), entry)| (*id, entry.clone()))
+                .collect()
+        })
+    }
+
+    /// 每张工单已有的译文语言
+    pub fn all_translation_langs(&self) -> HashMap<u64, Vec<String>> {
+        let mut langs: HashMap<u64, Vec<String>> = HashMap::new();
+        self.read(|entries| {
+            for (id, lang) in entries.keys() {
+                if let Some(lang) = lang {
+                    langs.entry(*id).or_default().push(lang.clone());
+                }
+            }
+        });
+        langs.values_mut().for_each(|l| l.sort());
+        langs
+    }
+
+    /// 文件对应的工单头部 (见 `Ticket::strip_bodies`)；文件未变化时取缓存，否则用 `load` 读取
+    pub fn header(&self, entry: &IndexEntry, load: impl FnOnce(&Path) -> Result<Ticket, String>) -> Result<Ticket, String> {
+        if let Some((cached, ticket)) = self.headers.lock().unwrap_or_else(|e| e.into_inner()).get(&entry.path) {
+            if cached == entry {
+                return Ok(ticket.clone());
+            }
+        }
+        let mut ticket = load(&entry.path)?;
+        ticket.strip_bodies();
+        ticket.available_langs.clear();
+        self.headers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(entry.path.clone(), (entry.clone(), ticket.clone()));
+        Ok(ticket)
+    }
+
+    /// 记录本进程写入的文件
+    pub fn record(&self, path: &Path) {
+        if let (Some(key), Some(entry)) = (parse_ticket_filename(path), IndexEntry::stat(path)) {
+            self.write().insert(key, entry);
+            self.writes.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// 移除已删除的文件
+    pub fn forget(&self, path: &Path) {
+        if let Some(key) = parse_ticket_filename(path) {
+            let mut entries = self.write();
+            if entries.get(&key).is_some_and(|e| e.path == path) {
+                entries.remove(&key);
+                self.writes.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        self.headers.lock().unwrap_or_else(|e| e.into_inner()).remove(path);
+    }
+
+    /// 重新扫描并与索引比较，返回有变化 (新增、修改、删除) 的工单 ID；
+    /// 扫描时不持锁，只在比较与替换时持写锁
+    pub fn refresh(&self) -> Vec<u64> {
+        if !self.built.load(Ordering::SeqCst) {
+            self.ensure_built();
+            return Vec::new();
+        }
+        self.scan_and_apply(|entries, scanned| {
+            let changed: BTreeSet<u64> = scanned
+                .iter()
+                .filter(|(key, entry)| entries.get(*key) != Some(*entry))
+                .map(|((id, _), _)| *id)
+                .chain(entries.keys().filter(|key| !scanned.contains_key(*key)).map(|(id, _)| *id))
+                .collect();
+            *entries = scanned;
+            let paths: HashSet<&PathBuf> = entries.values().map(|e| &e.path).collect();
+            self.headers
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .retain(|path, _| paths.contains(path));
+            changed.into_iter().collect()
+        })
+    }
+
+    /// 在后台线程中每隔 `interval` 检查外部修改，每张有变化的工单发送一次 `ticket-updated` 事件
+    /// (负载为工单 ID)；重复调用不会启动多个线程。
+    ///
+    /// 采用轮询而不是文件系统通知：数据目录常放在网络盘或同步盘上，通知在那里并不可靠，
+    /// 而且通知丢失时仍需要完整扫描来兜底。每次检查是一次 `read_dir` 加每个文件一次 `stat`
+    /// (不读取内容)，工单很多时可以在设置中调大间隔
+    pub fn watch(self: &Arc<Self>, sink: Arc<dyn EventSink>, interval: Duration) {
+        let mut watcher = self.watcher.lock().unwrap_or_else(|e| e.into_inner());
+        if watcher.is_some() {
+            return;
+        }
+        let running = Arc::new(AtomicBool::new(true));
+        *watcher = Some(running.clone());
+        let index = self.clone();
+        std::thread::spawn(move || {
+            index.ensure_built();
+            loop {
+                std::thread::sleep(interval);
+                if !running.load(Ordering::SeqCst) {
+                    break;
+                }
+                for ticket_id in index.refresh() {
+                    sink.event("ticket-updated", serde_json::json!(ticket_id));
+                }
+            }
+        });
+    }
+
+    /// 停止 `watch` 的后台检查
+    pub fn unwatch(&self) {
+        if let Some(running) = self.watcher.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            running.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{temp_dir, ticket};
+
+    fn tickets_dir(name: &str) -> (String, PathBuf) {
+        let data_dir = temp_dir(&format!("ticket-index-{}", name));
+        fs::create_dir_all(data_dir.join("tickets")).unwrap();
+        (data_dir.display().to_string(), data_dir.join("tickets"))
+    }
+
+    #[test]
+    fn filenames_parse_to_ticket_and_language() {
+        assert_eq!(parse_ticket_filename(Path::new("12_open.json")), Some((12, None)));
+        assert_eq!(parse_ticket_filename(Path::new("12_open_cn.json")), Some((12, Some("zh-CN".to_string()))));
+        assert_eq!(parse_ticket_filename(Path::new("12_status7_pt_br.json")), Some((12, Some("pt-BR".to_string()))));
+        assert_eq!(parse_ticket_filename(Path::new("12_open.json.tmp")), None);
+        assert_eq!(parse_ticket_filename(Path::new("notes.json")), None);
+    }
+
+    #[test]
+    fn refresh_reports_added_changed_and_removed_tickets() {
+        let (data_dir, dir) = tickets_dir("refresh");
+        fs::write(dir.join("1_open.json"), "{}").unwrap();
+        fs::write(dir.join("2_open.json"), "{}").unwrap();
+        fs::write(dir.join("3_open.json"), "{}").unwrap();
+        let index = TicketIndex::shared(&data_dir);
+        // 首次使用只建立索引
+        assert!(index.refresh().is_empty());
+        assert!(index.refresh().is_empty());
+
+        fs::write(dir.join("1_open.json"), "{\"id\": 1}").unwrap();
+        fs::rename(dir.join("2_open.json"), dir.join("2_closed.json")).unwrap();
+        fs::remove_file(dir.join("3_open.json")).unwrap();
+        fs::write(dir.join("4_open_de.json"), "{}").unwrap();
+        assert_eq!(index.refresh(), [1, 2, 3, 4]);
+        assert_eq!(index.get(2, None).unwrap().status, Some(FreshdeskStatus::Closed));
+        assert!(index.get(3, None).is_none());
+        assert_eq!(index.translation_langs(4), ["de"]);
+        assert!(index.refresh().is_empty());
+        fs::remove_dir_all(Path::new(&data_dir)).unwrap();
+    }
+
+    #[test]
+    fn own_writes_are_not_reported_as_changes() {
+        let (data_dir, dir) = tickets_dir("own-writes");
+        let index = TicketIndex::shared(&data_dir);
+        assert!(index.files(None).is_empty());
+
+        fs::write(dir.join("5_open.json"), "{}").unwrap();
+        index.record(&dir.join("5_open.json"));
+        fs::remove_file(dir.join("5_open.json")).unwrap();
+        fs::write(dir.join("5_pending.json"), "{}").unwrap();
+        index.forget(&dir.join("5_open.json"));
+        index.record(&dir.join("5_pending.json"));
+        assert!(index.refresh().is_empty());
+        assert_eq!(index.files(None).len(), 1);
+
+        // 失效后下次使用重新扫描，不报告变化
+        fs::write(dir.join("6_open.json"), "{}").unwrap();
+        index.invalidate();
+        assert!(index.refresh().is_empty());
+        assert!(index.get(6, None).is_some());
+        fs::remove_dir_all(Path::new(&data_dir)).unwrap();
+    }
+
+    #[test]
+    fn headers_are_cached_until_the_file_changes() {
+        let (data_dir, dir) = tickets_dir("headers");
+        let path = dir.join("7_open.json");
+        fs::write(&path, "{}").unwrap();
+        let index = TicketIndex::shared(&data_dir);
+        let load = |subject: &'static str| {
+            move |_: &Path| Ok(Ticket { subject: Some(subject.to_string()), ..ticket(7, FreshdeskStatus::Open) })
+        };
+
+        let entry = index.get(7, None).unwrap();
+        assert_eq!(index.header(&entry, load("first")).unwrap().subject.as_deref(), Some("first"));
+        assert_eq!(index.header(&entry, load("second")).unwrap().subject.as_deref(), Some("first"));
+
+        fs::write(&path, "{\"id\": 7}").unwrap();
+        index.record(&path);
+        let entry = index.get(7, None).unwrap();
+        assert_eq!(index.header(&entry, load("second")).unwrap().subject.as_deref(), Some("second"));
+        fs::remove_dir_all(Path::new(&data_dir)).unwrap();
+    }
+}
@@ -43,6 +43,10 @@ function AppNew() {
         apiKey, setApiKey,
         outputDir, setOutputDir,
         syncStartDate, setSyncStartDate,
+        syncEndDate, setSyncEndDate,
+        syncWindowDays, setSyncWindowDays,
+        syncConcurrency, setSyncConcurrency,
+        freshdeskDomain, setFreshdeskDomain,
         mqHost, setMqHost,
         mqPort, setMqPort,
         mqUsername, setMqUsername,
@@ -72,8 +76,13 @@ function AppNew() {
         isSyncing,
         progress,
         fullSync, setFullSync,
+        syncJob,
         logsEndRef,
         startSync,
+        retryFailed,
+        pauseSync,
+        resumeSync,
+        cancelSync,
         syncStatuses
     } = useSync(apiKey, outputDir, syncStartDate, loadTickets);
 
@@ -185,6 +194,11 @@ function AppNew() {
                         setFullSync={setFullSync}
                         syncStartDate={syncStartDate}
                         startSync={startSync}
+                        retryFailed={retryFailed}
+                        syncJob={syncJob}
+                        pauseSync={pauseSync}
+                        resumeSync={resumeSync}
+                        cancelSync={cancelSync}
                         syncStatuses={syncStatuses}
                         logs={logs}
                         logsEndRef={logsEndRef}
@@ -242,6 +256,14 @@ function AppNew() {
                         setOutputDir={setOutputDir}
                         syncStartDate={syncStartDate}
                         setSyncStartDate={setSyncStartDate}
+                        syncEndDate={syncEndDate}
+                        setSyncEndDate={setSyncEndDate}
+                        syncWindowDays={syncWindowDays}
+                        setSyncWindowDays={setSyncWindowDays}
+                        syncConcurrency={syncConcurrency}
+                        setSyncConcurrency={setSyncConcurrency}
+                        freshdeskDomain={freshdeskDomain}
+                        setFreshdeskDomain={setFreshdeskDomain}
                         mqHost={mqHost}
                         setMqHost={setMqHost}
                         mqPort={mqPort}
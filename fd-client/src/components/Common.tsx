@@ -15,12 +15,16 @@ export const StatusBadge: React.FC<{ status: number }> = ({ status }) => {
     );
 };
 
+/** 界面沿用的旧语言代码 → 后端使用的 BCP-47 代码 */
+export const toLangCode = (lang: string): string =>
+    ({ cn: 'zh-CN', jp: 'ja', kr: 'ko' } as Record<string, string>)[lang] || lang;
+
 export const LangLabel: React.FC<{ lang: string }> = ({ lang }) => {
     const labels: Record<string, { label: string; color: string }> = {
-        'cn': { label: 'ZH', color: 'bg-blue-500/20 text-blue-400 border-blue-500/30' },
+        'zh-CN': { label: 'ZH', color: 'bg-blue-500/20 text-blue-400 border-blue-500/30' },
         'en': { label: 'EN', color: 'bg-purple-500/20 text-purple-400 border-purple-500/30' },
     };
-    const l = labels[lang] || { label: lang.toUpperCase(), color: 'bg-slate-500/20 text-slate-400 border-slate-500/30' };
+    const l = labels[toLangCode(lang)] || { label: lang.toUpperCase(), color: 'bg-slate-500/20 text-slate-400 border-slate-500/30' };
     return (
         <span className={`px-1.5 py-0.5 text-[9px] font-bold rounded border ${l.color} flex-shrink-0`}>
             {l.label}
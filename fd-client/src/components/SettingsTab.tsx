@@ -1,6 +1,7 @@
-import React, { useState } from 'react';
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { open } from '@tauri-apps/plugin-dialog';
-import { NotebookLMConfig } from '../types';
+import { Language, NotebookLMConfig } from '../types';
 
 interface SettingsTabProps {
     apiKey: string;
@@ -9,6 +10,14 @@ interface SettingsTabProps {
     setOutputDir: (s: string) => void;
     syncStartDate: string;
     setSyncStartDate: (s: string) => void;
+    syncEndDate?: string;
+    setSyncEndDate?: (s: string) => void;
+    syncWindowDays?: number;
+    setSyncWindowDays?: (n: number) => void;
+    syncConcurrency?: number;
+    setSyncConcurrency?: (n: number) => void;
+    freshdeskDomain?: string;
+    setFreshdeskDomain?: (s: string) => void;
     mqHost: string;
     setMqHost: (s: string) => void;
     mqPort: number;
@@ -28,6 +37,10 @@ const SettingsTab: React.FC<SettingsTabProps> = ({
     apiKey, setApiKey,
     outputDir, setOutputDir,
     syncStartDate, setSyncStartDate,
+    syncEndDate, setSyncEndDate,
+    syncWindowDays, setSyncWindowDays,
+    syncConcurrency, setSyncConcurrency,
+    freshdeskDomain, setFreshdeskDomain,
     mqHost, setMqHost,
     mqPort, setMqPort,
     mqUsername, setMqUsername,
@@ -37,6 +50,11 @@ const SettingsTab: React.FC<SettingsTabProps> = ({
     setLogs
 }) => {
     const [showApiKey, setShowApiKey] = useState(false);
+    const [languages, setLanguages] = useState<Language[]>([]);
+
+    useEffect(() => {
+        invoke<Language[]>('list_languages_cmd').then(setLanguages).catch(console.error);
+    }, []);
     async function selectFolder() {
         try {
             const folder = await open({ directory: true, multiple: false, title: "Select Output Directory" });
@@ -207,6 +225,19 @@ const SettingsTab: React.FC<SettingsTabProps> = ({
                                 </div>
                             </div>
 
+                            {setFreshdeskDomain && (
+                                <div>
+                                    <label className="block text-xs font-medium text-slate-400 mb-2 uppercase">Freshdesk Domain</label>
+                                    <input
+                                        type="text"
+                                        value={freshdeskDomain ?? ''}
+                                        onChange={(e) => setFreshdeskDomain(e.target.value)}
+                                        className="w-full px-4 py-3 bg-slate-800/50 border border-white/10 rounded-lg text-white focus:outline-none focus:ring-2 focus:ring-indigo-500/50 transition-all"
+                                        placeholder="yourcompany.freshdesk.com"
+                                    />
+                                </div>
+                            )}
+
                             <div>
                                 <label className="block text-xs font-medium text-slate-400 mb-2 uppercase">Freshdesk API Key</label>
                                 <div className="flex gap-2">
@@ -256,6 +287,46 @@ const SettingsTab: React.FC<SettingsTabProps> = ({
                                 <p className="text-[10px] text-slate-500 mt-2">Tickets will be fetched starting from this month during full sync</p>
                             </div>
 
+                            {setSyncEndDate && setSyncWindowDays && (
+                                <div className="grid grid-cols-2 gap-4">
+                                    <div>
+                                        <label className="block text-xs font-medium text-slate-400 mb-2 uppercase">Full Sync End Date</label>
+                                        <input
+                                            type="month"
+                                            value={syncEndDate ?? ''}
+                                            onChange={(e) => setSyncEndDate(e.target.value)}
+                                            className="w-full px-4 py-3 bg-slate-800/50 border border-white/10 rounded-lg text-white focus:outline-none focus:ring-2 focus:ring-indigo-500/50 transition-all"
+                                        />
+                                        <p className="text-[10px] text-slate-500 mt-2">Leave empty to sync up to today</p>
+                                    </div>
+                                    <div>
+                                        <label className="block text-xs font-medium text-slate-400 mb-2 uppercase">Window Size (Days)</label>
+                                        <input
+                                            type="number"
+                                            min={1}
+                                            value={syncWindowDays ?? 7}
+                                            onChange={(e) => setSyncWindowDays(Math.max(1, parseInt(e.target.value) || 1))}
+                                            className="w-full px-4 py-3 bg-slate-800/50 border border-white/10 rounded-lg text-white focus:outline-none focus:ring-2 focus:ring-indigo-500/50 transition-all"
+                                        />
+                                    </div>
+                                </div>
+                            )}
+
+                            {setSyncConcurrency && (
+                                <div>
+                                    <label className="block text-xs font-medium text-slate-400 mb-2 uppercase">Concurrent Conversation Requests</label>
+                                    <input
+                                        type="number"
+                                        min={1}
+                                        max={16}
+                                        value={syncConcurrency ?? 4}
+                                        onChange={(e) => setSyncConcurrency(Math.min(16, Math.max(1, parseInt(e.target.value) || 1)))}
+                                        className="w-full px-4 py-3 bg-slate-800/50 border border-white/10 rounded-lg text-white focus:outline-none focus:ring-2 focus:ring-indigo-500/50 transition-all"
+                                    />
+                                    <p className="text-[10px] text-slate-500 mt-2">Requests still share the Freshdesk rate limit</p>
+                                </div>
+                            )}
+
                             <div>
                                 <label className="block text-xs font-medium text-slate-400 mb-2 uppercase">Translation Target Language (MQ/Manual)</label>
                                 <select
@@ -263,9 +334,9 @@ const SettingsTab: React.FC<SettingsTabProps> = ({
                                     onChange={(e) => setTranslationLang(e.target.value)}
                                     className="w-full px-4 py-3 bg-slate-800/50 border border-white/10 rounded-lg text-white focus:outline-none focus:ring-2 focus:ring-indigo-500/50 transition-all text-sm appearance-none"
                                 >
-                                    <option value="cn">简体中文 (Simplified Chinese)</option>
-                                    <option value="en">English (English)</option>
-                                    <option value="jp">日本語 (Japanese)</option>
+                                    {languages.map(l => (
+                                        <option key={l.code} value={l.code}>{l.nativeName} ({l.name})</option>
+                                    ))}
                                 </select>
                                 <p className="text-[10px] text-slate-500 mt-2">Defines the default language for MQ and manual translations</p>
                             </div>
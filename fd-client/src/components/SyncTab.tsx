@@ -1,5 +1,5 @@
 import React from 'react';
-import { Progress as ProgressType } from '../types';
+import { Progress as ProgressType, SyncJobStatus } from '../types';
 
 interface SyncTabProps {
     isSyncing: boolean;
@@ -8,6 +8,11 @@ interface SyncTabProps {
     setFullSync: (b: boolean) => void;
     syncStartDate: string;
     startSync: () => void;
+    retryFailed?: () => void;
+    syncJob?: SyncJobStatus | null;
+    pauseSync?: () => void;
+    resumeSync?: () => void;
+    cancelSync?: () => void;
     syncStatuses: () => void;
     logs: string[];
     logsEndRef: React.RefObject<HTMLDivElement | null>;
@@ -20,6 +25,11 @@ const SyncTab: React.FC<SyncTabProps> = ({
     setFullSync,
     syncStartDate,
     startSync,
+    retryFailed,
+    syncJob,
+    pauseSync,
+    resumeSync,
+    cancelSync,
     syncStatuses,
     logs,
     logsEndRef
@@ -90,6 +100,19 @@ const SyncTab: React.FC<SyncTabProps> = ({
                 )}
             </button>
 
+            {isSyncing && syncJob && (
+                <div className="flex gap-2 mb-2">
+                    {syncJob.state === 'paused' ? (
+                        <button onClick={resumeSync} className="flex-1 py-2 rounded-xl text-sm font-medium text-white bg-emerald-600 hover:bg-emerald-500">Resume</button>
+                    ) : (
+                        <button onClick={pauseSync} disabled={syncJob.state !== 'running'} className="flex-1 py-2 rounded-xl text-sm font-medium text-white bg-slate-700 hover:bg-slate-600 disabled:opacity-50">Pause</button>
+                    )}
+                    <button onClick={cancelSync} disabled={syncJob.state === 'cancelling'} className="flex-1 py-2 rounded-xl text-sm font-medium text-white bg-red-600/80 hover:bg-red-500 disabled:opacity-50">
+                        {syncJob.state === 'cancelling' ? 'Cancelling...' : 'Cancel'}
+                    </button>
+                </div>
+            )}
+
             <button
                 onClick={syncStatuses}
                 disabled={isSyncing}
@@ -99,6 +122,16 @@ const SyncTab: React.FC<SyncTabProps> = ({
                 Sync File Statuses
             </button>
 
+            {retryFailed && (
+                <button
+                    onClick={retryFailed}
+                    disabled={isSyncing}
+                    className="w-full py-3 rounded-xl font-medium text-slate-300 transition-all duration-300 flex items-center justify-center gap-2 mb-4 bg-slate-700/50 border border-white/10 hover:bg-slate-700 hover:text-white disabled:opacity-50 disabled:cursor-not-allowed flex-shrink-0"
+                >
+                    Retry Failed Tickets
+                </button>
+            )}
+
             {/* Console */}
             <div className="flex-1 bg-slate-950 rounded-xl border border-white/10 overflow-hidden flex flex-col min-h-0">
                 <div className="px-4 py-2 border-b border-white/10 flex items-center gap-2 flex-shrink-0">
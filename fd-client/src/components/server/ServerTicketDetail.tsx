@@ -156,13 +156,13 @@ const ServerTicketDetail = React.forwardRef<ServerTicketDetailHandle, ServerTick
             // 构造 Rust 期望的 Ticket 结构
             const rustTicket = {
                 id: ticket.id,
-                externalId: ticket.externalId,
+                external_id: ticket.externalId,
                 subject: ticket.subject,
-                descriptionText: parsedData?.description || '',
+                description_text: parsedData?.description || '',
                 content: ticket.content,
-                status: ticket.status,
+                workflow_status: ticket.status,
                 priority: 0,
-                createdAt: ticket.createdAt,
+                created_at: ticket.createdAt,
                 conversations: (parsedData?.conversations || []).map(c => ({
                     id: c.id,
                     body_text: c.bodyText,
@@ -190,7 +190,7 @@ const ServerTicketDetail = React.forwardRef<ServerTicketDetailHandle, ServerTick
             })) || [];
 
             const finalTranslatedContent = JSON.stringify({
-                description: result.descriptionText, // Ticket 结构有 rename_all="camelCase"
+                description: result.description_text, // Ticket 与 Freshdesk 原生格式一致 (snake_case)
                 conversations: translatedConversations
             });
 
@@ -6,13 +6,17 @@ export function useSettings() {
   const [apiKey, setApiKey] = useState("");
   const [outputDir, setOutputDir] = useState("data");
   const [syncStartDate, setSyncStartDate] = useState("2025-01");
+  const [syncEndDate, setSyncEndDate] = useState("");
+  const [syncWindowDays, setSyncWindowDays] = useState(7);
+  const [syncConcurrency, setSyncConcurrency] = useState(4);
+  const [freshdeskDomain, setFreshdeskDomain] = useState("simsonn.freshdesk.com");
 
   // MQ 配置状态（扁平化）
   const [mqHost, setMqHost] = useState('localhost');
   const [mqPort, setMqPort] = useState(5672);
   const [mqUsername, setMqUsername] = useState('guest');
   const [mqPassword, setMqPassword] = useState('guest');
-  const [translationLang, setTranslationLang] = useState('cn');
+  const [translationLang, setTranslationLang] = useState('zh-CN');
 
   // NotebookLM配置状态
   const [notebookLMConfig, setNotebookLMConfig] = useState<NotebookLMConfig>({
@@ -30,6 +34,10 @@ export function useSettings() {
       if (settings.api_key) setApiKey(settings.api_key);
       if (settings.output_dir) setOutputDir(settings.output_dir);
       if (settings.sync_start_date) setSyncStartDate(settings.sync_start_date);
+      if (settings.sync_end_date) setSyncEndDate(settings.sync_end_date);
+      if (settings.sync_window_days) setSyncWindowDays(settings.sync_window_days);
+      if (settings.sync_concurrency) setSyncConcurrency(settings.sync_concurrency);
+      if (settings.freshdesk_domain) setFreshdeskDomain(settings.freshdesk_domain);
       // MQ 配置
       if (settings.mq_host) setMqHost(settings.mq_host);
       if (settings.mq_port) setMqPort(settings.mq_port);
@@ -61,10 +69,14 @@ export function useSettings() {
         mqUsername,
         mqPassword,
         translationLang,
+        freshdeskDomain,
+        syncEndDate,
+        syncWindowDays,
+        syncConcurrency,
       }).catch(console.error);
     }, 500);
     return () => clearTimeout(timeout);
-  }, [apiKey, outputDir, syncStartDate, mqHost, mqPort, mqUsername, mqPassword, translationLang]);
+  }, [apiKey, outputDir, syncStartDate, mqHost, mqPort, mqUsername, mqPassword, translationLang, freshdeskDomain, syncEndDate, syncWindowDays, syncConcurrency]);
 
   // 自动保存 NotebookLM 配置
   useEffect(() => {
@@ -82,6 +94,10 @@ export function useSettings() {
     apiKey, setApiKey,
     outputDir, setOutputDir,
     syncStartDate, setSyncStartDate,
+    syncEndDate, setSyncEndDate,
+    syncWindowDays, setSyncWindowDays,
+    syncConcurrency, setSyncConcurrency,
+    freshdeskDomain, setFreshdeskDomain,
     mqHost, setMqHost,
     mqPort, setMqPort,
     mqUsername, setMqUsername,
@@ -1,13 +1,14 @@
 import { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import { Progress } from '../types';
+import { Progress, SyncJobStatus } from '../types';
 
 export function useSync(apiKey: string, outputDir: string, syncStartDate: string, loadTickets: () => void) {
   const [logs, setLogs] = useState<string[]>([]);
   const [isSyncing, setIsSyncing] = useState(false);
   const [progress, setProgress] = useState<Progress | null>(null);
   const [fullSync, setFullSync] = useState(false);
+  const [syncJob, setSyncJob] = useState<SyncJobStatus | null>(null);
   const logsEndRef = useRef<HTMLDivElement>(null);
 
   useEffect(() => {
@@ -21,9 +22,13 @@ export function useSync(apiKey: string, outputDir: string, syncStartDate: string
     const unlistenProgress = listen<Progress>("progress", (event) => {
       setProgress(event.payload);
     });
+    const unlistenJob = listen<SyncJobStatus>("sync-job", (event) => {
+      setSyncJob(event.payload);
+    });
     return () => {
       unlistenLog.then((f) => f());
       unlistenProgress.then((f) => f());
+      unlistenJob.then((f) => f());
     };
   }, []);
 
@@ -46,6 +51,32 @@ export function useSync(apiKey: string, outputDir: string, syncStartDate: string
     setProgress(null);
   }
 
+  async function retryFailed() {
+    setIsSyncing(true);
+    setLogs([]);
+    try {
+      const msg = await invoke("retry_failed_sync", { apiKey, outputDir });
+      setLogs((prev) => [...prev, `✅ ${msg}`]);
+      loadTickets();
+    } catch (error) {
+      setLogs((prev) => [...prev, `❌ Error: ${error}`]);
+    }
+    setIsSyncing(false);
+  }
+
+  async function controlSync(command: "pause_sync_job_cmd" | "resume_sync_job_cmd" | "cancel_sync_job_cmd") {
+    if (!syncJob) return;
+    try {
+      setSyncJob(await invoke<SyncJobStatus>(command, { jobId: syncJob.id }));
+    } catch (error) {
+      setLogs((prev) => [...prev, `❌ Error: ${error}`]);
+    }
+  }
+
+  const pauseSync = () => controlSync("pause_sync_job_cmd");
+  const resumeSync = () => controlSync("resume_sync_job_cmd");
+  const cancelSync = () => controlSync("cancel_sync_job_cmd");
+
   async function syncStatuses() {
     setLogs((prev) => [...prev, "🔄 Syncing file statuses..."]);
     try {
@@ -62,8 +93,13 @@ export function useSync(apiKey: string, outputDir: string, syncStartDate: string
     isSyncing,
     progress,
     fullSync, setFullSync,
+    syncJob,
     logsEndRef,
     startSync,
+    retryFailed,
+    pauseSync,
+    resumeSync,
+    cancelSync,
     syncStatuses
   };
 }
@@ -1,5 +1,6 @@
 import { useState, useEffect, useMemo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { Ticket } from '../types';
 
 export function useTickets(outputDir: string) {
@@ -37,6 +38,40 @@ export function useTickets(outputDir: string) {
 
   useEffect(() => { loadTickets(); }, [outputDir, listLang]);
 
+  // 工单文件被其他进程新建、修改或删除 (ticket-updated)：更新列表中的该工单与正在查看的工单
+  useEffect(() => {
+    if (!outputDir) return;
+    const selectedId = selectedTicket?.id;
+    const unlisten = listen<number>('ticket-updated', async (event) => {
+      const id = event.payload;
+      try {
+        const listed = await invoke<Ticket | null>("load_ticket_cmd", {
+          outputDir,
+          ticketId: id,
+          lang: listLang === 'original' ? null : listLang
+        });
+        setTickets(prev => {
+          if (!listed) return prev.filter(t => t.id !== id);
+          if (prev.some(t => t.id === id)) return prev.map(t => t.id === id ? listed : t);
+          // 新工单按 ID 倒序插入 (与 list_local_tickets 的顺序一致)
+          const index = prev.findIndex(t => t.id < id);
+          return index === -1 ? [...prev, listed] : [...prev.slice(0, index), listed, ...prev.slice(index)];
+        });
+        if (id === selectedId) {
+          const shown = await invoke<Ticket | null>("load_ticket_cmd", {
+            outputDir,
+            ticketId: id,
+            lang: displayLang === 'original' ? null : displayLang
+          });
+          if (shown) setSelectedTicket(prev => prev && shown);
+        }
+      } catch (error) {
+        console.error(error);
+      }
+    });
+    return () => { unlisten.then(f => f()); };
+  }, [outputDir, listLang, displayLang, selectedTicket?.id]);
+
   const memoizedData = useMemo(() => {
     const searchFiltered = (tickets || []).filter((t) => {
       if (debouncedSearchQuery.trim()) {
@@ -2,6 +2,7 @@ import { useState, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { save } from '@tauri-apps/plugin-dialog';
 import { Ticket } from '../types';
+import { toLangCode } from '../components/Common';
 
 export function useTranslation(
   outputDir: string,
@@ -45,7 +46,7 @@ export function useTranslation(
         targetLang
       });
 
-      const updatedLangs = [...(selectedTicket.available_langs || []), targetLang];
+      const updatedLangs = [...(selectedTicket.available_langs || []), toLangCode(targetLang)];
       const deduplicated = Array.from(new Set(updatedLangs));
       setSelectedTicket({ ...translated, available_langs: deduplicated });
     } catch (error) {
@@ -77,7 +78,7 @@ export function useTranslation(
           currentStep++;
           setBatchProgress({ current: currentStep, total: totalSteps });
 
-          if (ticket?.available_langs?.includes(targetLang)) continue;
+          if (ticket?.available_langs?.includes(toLangCode(targetLang))) continue;
 
           let retryCount = 0;
           let success = false;
@@ -90,7 +91,7 @@ export function useTranslation(
               });
 
               if (selectedTicket?.id === Number(id)) {
-                const updatedLangs = [...(selectedTicket.available_langs || []), targetLang];
+                const updatedLangs = [...(selectedTicket.available_langs || []), toLangCode(targetLang)];
                 const deduplicated = Array.from(new Set(updatedLangs));
                 setSelectedTicket({ ...translated, available_langs: deduplicated });
                 setDisplayLang(targetLang);
@@ -1,27 +1,176 @@
 export interface Ticket {
   id: number;
   subject: string;
+  /** 已清理的 HTML 描述 */
+  description?: string;
   description_text: string | null;
+  /** Freshdesk 状态：2 open / 3 pending / 4 resolved / 5 closed */
   status: number;
+  /** AutoPilot 流转状态 (仅来自服务端的工单) */
+  workflow_status?: string;
   priority: number;
   created_at: string;
   updated_at: string;
+  tags?: string[];
+  type?: string;
+  source?: number;
+  group_id?: number;
+  product_id?: number;
+  company_id?: number;
+  due_by?: string;
+  fr_due_by?: string;
+  custom_fields?: Record<string, string | number | boolean | null>;
+  attachments?: Attachment[];
   conversations?: Conversation[];
   available_langs?: string[];
+  conversations_incomplete?: boolean;
+}
+
+export interface Attachment {
+  id: number;
+  name: string;
+  content_type?: string;
+  size: number;
+  attachment_url?: string;
+  /** 已下载到本地时的内容哈希，用 get_attachment_path_cmd 取本地路径 */
+  sha256?: string;
+  /** 未下载的原因 */
+  skipped?: string;
 }
 
 export interface Conversation {
   id: number;
+  /** 已清理的 HTML 正文 */
+  body?: string;
   body_text: string;
   created_at: string;
   incoming: boolean;
   private: boolean;
+  attachments?: Attachment[];
+}
+
+/** list_local_tickets 的筛选条件 */
+export interface TicketFilter {
+  tags?: string[];
+  ticketType?: string;
+  source?: number;
+  groupId?: number;
+  productId?: number;
+  companyId?: number;
+  requesterId?: number;
+  responderId?: number;
+  status?: number;
+  priority?: number;
+  /** 创建时间范围 (YYYY-MM-DD，含首尾) */
+  createdFrom?: string;
+  createdTo?: string;
+  dueBefore?: string;
+  customFields?: Record<string, string | number | boolean | null>;
+}
+
+export type SortKey = 'id' | 'created_at' | 'updated_at' | 'last_activity' | 'priority' | 'status' | 'subject';
+
+/** query_local_tickets 的条件；翻页时传入上一页的 nextCursor，其他条件保持不变 */
+export interface TicketListQuery {
+  filter?: TicketFilter;
+  /** 按该语言的译文列出 */
+  lang?: string;
+  hasLangs?: string[];
+  /** 还没有该语言译文的工单 */
+  missingLang?: string;
+  sort?: SortKey;
+  order?: 'asc' | 'desc';
+  cursor?: string;
+  /** 默认 50，最多 500 */
+  limit?: number;
+}
+
+/** 列表中的工单摘要；完整内容用 load_ticket_cmd 读取 */
+export interface TicketSummary {
+  id: number;
+  subject?: string | null;
+  status: number;
+  priority: number;
+  requesterId?: number | null;
+  tags: string[];
+  createdAt?: string | null;
+  updatedAt?: string | null;
+  lastActivityAt?: string | null;
+  conversationCount: number;
+  attachmentCount: number;
+  availableLangs: string[];
+}
+
+export interface TicketPage {
+  total: number;
+  items: TicketSummary[];
+  nextCursor?: string | null;
+}
+
+/** list_requester_tickets_cmd：某个联系人的工单摘要与联系人信息 */
+export interface RequesterTickets {
+  requesterId: number;
+  /** 联系人缓存中没有时为 null */
+  contact?: Contact | null;
+  company?: string | null;
+  tickets: TicketSummary[];
+}
+
+/** search_tickets 的条件；lang 为 "original" 时只搜原文，日期为 YYYY-MM-DD */
+export interface SearchQuery {
+  query: string;
+  status?: number;
+  lang?: string;
+  requesterId?: number;
+  from?: string;
+  to?: string;
+  page?: number;
+  pageSize?: number;
+}
+
+export interface SearchHit {
+  ticketId: number;
+  lang?: string | null;
+  subject?: string | null;
+  status: number;
+  createdAt?: string | null;
+  updatedAt?: string | null;
+  /** 命中的词以 <mark> 标出 */
+  snippet: string;
+}
+
+export interface SearchResults {
+  total: number;
+  page: number;
+  pageSize: number;
+  hits: SearchHit[];
+}
+
+export interface TicketField {
+  id: number;
+  name: string;
+  label: string;
+  type: string;
+  default: boolean;
+  choices?: unknown;
 }
 
 export interface Settings {
   api_key: string;
   output_dir: string;
   sync_start_date: string;
+  sync_end_date: string;
+  sync_window_days: number;
+  sync_concurrency: number;
+  attachment_max_mb: number;
+  attachment_mime_types: string;
+  history_max_versions: number;
+  history_max_days: number;
+  /** 检查其他进程修改工单文件的间隔 (秒)，0 表示不检查 */
+  watch_interval_secs: number;
+  freshdesk_domain: string;
+  active_account: string;
+  accounts: AccountProfile[];
   // MQ 配置
   mq_host: string;
   mq_port: number;
@@ -30,6 +179,41 @@ export interface Settings {
   translation_lang: string;
 }
 
+/** list_languages_cmd 返回的语言；code 为 BCP-47 代码，aliases 为兼容的旧代码 (如 cn) */
+export interface Language {
+  code: string;
+  name: string;
+  nativeName: string;
+  aliases: string[];
+}
+
+export type IssueKind = 'orphan_translation' | 'status_mismatch' | 'duplicate' | 'corrupt' | 'unrecognized';
+
+export interface IntegrityIssue {
+  kind: IssueKind;
+  path: string;
+  ticket_id?: number | null;
+  lang?: string | null;
+  detail: string;
+  /** 修复模式下的处理结果 */
+  action?: 'renamed' | 'quarantined' | null;
+}
+
+/** check_data_dir_cmd 的结果 */
+export interface IntegrityReport {
+  files: number;
+  issues: IntegrityIssue[];
+  quarantine_dir?: string | null;
+  failed: string[];
+}
+
+export interface AccountProfile {
+  name: string;
+  domain: string;
+  api_key: string;
+  output_dir: string;
+}
+
 export interface Progress {
   phase: string;
   current: number;
@@ -39,6 +223,18 @@ export interface Progress {
   totalTickets?: number;
 }
 
+export type SyncJobState = 'running' | 'paused' | 'cancelling' | 'completed' | 'failed' | 'cancelled';
+
+export interface SyncJobStatus {
+  id: string;
+  dataDir: string;
+  fullSync: boolean;
+  state: SyncJobState;
+  startedAt: string;
+  finishedAt?: string | null;
+  message?: string | null;
+}
+
 export interface NotebookLMConfig {
   cookie: string;
   atToken: string;
@@ -48,3 +244,49 @@ export interface NotebookLMConfig {
   prompt: string;
   sourceIds?: string[];
 }
+
+export interface Contact {
+  id: number;
+  name: string;
+  email?: string;
+  phone?: string;
+  company_id?: number;
+}
+
+export interface Company {
+  id: number;
+  name: string;
+  domains: string[];
+}
+
+export interface Agent {
+  id: number;
+  contact: { name: string; email?: string };
+}
+
+/** 联系人 / 公司 / 客服缓存 (get_directory_cmd) */
+export interface Directory {
+  contacts: Record<string, Contact>;
+  companies: Record<string, Company>;
+  agents: Record<string, Agent>;
+  contactsUpdatedAt?: string;
+  refreshedAt?: string;
+}
+
+/** get_ticket_history_cmd 返回的一个版本 */
+export interface TicketVersion {
+  version: number;
+  savedAt: string;
+  status: number;
+  updatedAt?: string | null;
+  conversations: number;
+  /** 相对上一版本变化的字段 */
+  changes: string[];
+}
+
+/** diff_ticket_versions_cmd 返回的字段变化；嵌套字段形如 custom_fields.cf_x、conversations[123] */
+export interface FieldChange {
+  field: string;
+  before?: unknown;
+  after?: unknown;
+}